@@ -1,15 +1,95 @@
 use std::collections::HashMap;
 use crate::cpu::addressing_mode::AddressingMode;
-use crate::cpu::instruction::find_instruction_by_name_and_mode;
+use crate::cpu::instruction::{find_instruction_by_name_and_mode, Instruction, CMOS_INSTRUCTIONS};
+use crate::error::EmulatorError;
+use crate::util::types::Address;
 
-pub fn assemble(source_code: &str) -> Vec<u8> {
+///
+/// machine code with the addresses of the source lines, for the debuggers
+///
+pub struct Listing {
+    pub machine_code: Vec<u8>,
+    // line number, from 1, and address of every line holding an instruction, in source order
+    pub lines: Vec<(usize, Address)>,
+    pub labels: HashMap<String, Address>,
+}
+
+fn assembly_error(line: usize, message: String) -> EmulatorError {
+    EmulatorError::Assembly { line: line + 1, message }
+}
+
+///
+/// the values of a `.byte` line, `$` hex bytes separated by commas or white space
+///
+fn parse_bytes(parts: &[&str], line: usize) -> Result<Vec<u8>, EmulatorError> {
+    parts[1..]
+        .iter()
+        .flat_map(|part| part.split(','))
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .strip_prefix('$')
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| assembly_error(line, format!("invalid byte: {}", value)))
+        })
+        .collect()
+}
+
+///
+/// find the instruction of a line split in words, the mnemonic is followed by the addressing mode,
+/// the instructions missing from the NMOS 6502 are taken from the 65C02
+///
+fn find_instruction<'a>(parts: &[&'a str], line: usize) -> Result<Instruction<'a>, EmulatorError> {
+    let instruction = parts[0];
+    let mode = parts
+        .get(1)
+        .ok_or_else(|| assembly_error(line, format!("missing addressing mode after {}", instruction)))?;
+    find_instruction_by_name_and_mode(instruction, mode)
+        .or_else(|| {
+            CMOS_INSTRUCTIONS
+                .iter()
+                .find(|inst| inst.name == instruction && inst.addressing_mode.to_string() == mode.to_lowercase())
+                .copied()
+        })
+        .ok_or_else(|| assembly_error(line, format!("unsupported instruction: {} {}", instruction, mode)))
+}
+
+///
+/// the operand of a line at `index` in its words
+///
+fn operand<'a>(parts: &[&'a str], index: usize, line: usize) -> Result<&'a str, EmulatorError> {
+    parts.get(index).copied().ok_or_else(|| assembly_error(line, "missing operand".to_owned()))
+}
+
+///
+/// a `$` hex byte, or for a label its offset from `next`, the address following the instruction
+///
+fn byte_operand(operand: &str, labels: &HashMap<String, Address>, next: Address, line: usize) -> Result<u8, EmulatorError> {
+    if let Some(hex) = operand.strip_prefix('$') {
+        u8::from_str_radix(hex, 16).map_err(|_| assembly_error(line, format!("invalid operand: {}", operand)))
+    } else if let Some(&address) = labels.get(operand) {
+        Ok(address.wrapping_sub(next) as u8)
+    } else {
+        Err(assembly_error(line, format!("unsupported operand: {}", operand)))
+    }
+}
+
+pub fn assemble(source_code: &str) -> Result<Vec<u8>, EmulatorError> {
+    assemble_listing(source_code, 0x0000).map(|listing| listing.machine_code)
+}
+
+///
+/// assemble code to be loaded at `origin`, the labels take their address from it
+///
+pub fn assemble_listing(source_code: &str, origin: Address) -> Result<Listing, EmulatorError> {
     let mut labels = HashMap::new();
+    let mut lines = Vec::new();
     let mut machine_code = Vec::new();
 
     // Première passe : collecter les étiquettes et leurs adresses.
-    let mut address: u16 = 0;
-    for line in source_code.lines() {
-        let parts: Vec<_> = line.trim().split_whitespace().collect();
+    let mut address: u16 = origin;
+    for (number, line) in source_code.lines().enumerate() {
+        let parts: Vec<_> = line.split_whitespace().collect();
         if parts.is_empty() {
             continue;
         }
@@ -17,31 +97,28 @@ pub fn assemble(source_code: &str) -> Vec<u8> {
         if parts[0].ends_with(':') {
             let label = parts[0].trim_end_matches(':');
             labels.insert(label.to_owned(), address);
+        } else if parts[0] == ".byte" {
+            address = address.wrapping_add(parse_bytes(&parts, number)?.len() as u16);
         } else {
-            let instruction = parts[0];
-            let mode = parts[1];
+            let inst = find_instruction(&parts, number)?;
 
-            let inst = find_instruction_by_name_and_mode(instruction, mode)
-                .unwrap_or_else(|| panic!("Instruction non prise en charge : {} {}", instruction, mode));
-
-            address += inst.length as u16;
+            address = address.wrapping_add(inst.length as u16);
         }
     }
 
     // Deuxième passe : assembler les instructions en machine code.
-    for line in source_code.lines() {
-        let parts: Vec<_> = line.trim().split_whitespace().collect();
+    for (number, line) in source_code.lines().enumerate() {
+        let parts: Vec<_> = line.split_whitespace().collect();
         if parts.is_empty() {
             continue;
         }
 
-        if !parts[0].ends_with(':') {
-            let instruction = parts[0];
-            let mode = parts[1];
-
-            let inst = find_instruction_by_name_and_mode(instruction, mode)
-                .unwrap_or_else(|| panic!("Instruction non prise en charge : {} {}", instruction, mode));
+        if parts[0] == ".byte" {
+            machine_code.extend(parse_bytes(&parts, number)?);
+        } else if !parts[0].ends_with(':') {
+            let inst = find_instruction(&parts, number)?;
 
+            lines.push((number + 1, origin.wrapping_add(machine_code.len() as u16)));
             machine_code.push(inst.opcode);
 
             match inst.addressing_mode {
@@ -49,45 +126,46 @@ pub fn assemble(source_code: &str) -> Vec<u8> {
                 | AddressingMode::ZeroPage
                 | AddressingMode::ZeroPageX
                 | AddressingMode::ZeroPageY
+                | AddressingMode::IndirectX
+                | AddressingMode::IndirectY
+                | AddressingMode::ZeroPageIndirect
                 | AddressingMode::Relative => {
-                    let operand = parts[2];
-                    let value: u8 = if operand.starts_with('$') {
-                        u8::from_str_radix(&operand[1..], 16).unwrap()
-                    } else if labels.contains_key(operand) {
-                        (labels[operand] - address) as u8
-                    } else {
-                        panic!("Opérande non pris en charge: {}", operand);
-                    };
-                    machine_code.push(value);
+                    let next = origin.wrapping_add(machine_code.len() as u16 + 1);
+                    machine_code.push(byte_operand(operand(&parts, 2, number)?, &labels, next, number)?);
                 }
                 AddressingMode::Absolute
                 | AddressingMode::AbsoluteX
                 | AddressingMode::AbsoluteY
-                | AddressingMode::IndirectX
-                | AddressingMode::IndirectY => {
-                    let operand = parts[2];
-                    let value: u16 = if operand.starts_with('$') {
-                        u16::from_str_radix(&operand[1..], 16).unwrap()
+                | AddressingMode::Indirect
+                | AddressingMode::AbsoluteIndirectX => {
+                    let operand = operand(&parts, 2, number)?;
+                    let value: u16 = if let Some(hex) = operand.strip_prefix('$') {
+                        u16::from_str_radix(hex, 16)
+                            .map_err(|_| assembly_error(number, format!("invalid operand: {}", operand)))?
                     } else if labels.contains_key(operand) {
                         labels[operand]
                     } else {
-                        panic!("Opérande non pris en charge: {}", operand);
+                        return Err(assembly_error(number, format!("unsupported operand: {}", operand)));
                     };
                     machine_code.push((value & 0xFF) as u8); // Low byte
                     machine_code.push((value >> 8) as u8); // High byte
                 }
-                AddressingMode::Indirect => {
-                    // Ajoutez le code pour traiter le cas Indirect ici
-                }
-                AddressingMode::Accumulator => {
-                    // Ajoutez le code pour traiter le cas Accumulator ici
+                // BBR and BBS, the zero page address then the branch
+                AddressingMode::ZeroPageRelative => {
+                    let next = origin.wrapping_add(machine_code.len() as u16 + 2);
+                    let zero_page = byte_operand(operand(&parts, 2, number)?, &labels, next, number)?;
+                    let offset = byte_operand(operand(&parts, 3, number)?, &labels, next, number)?;
+                    machine_code.extend([zero_page, offset]);
                 }
-                AddressingMode::Implied => {
-                    // Rien à faire pour le mode Implied
+                // the opcode is the whole instruction
+                AddressingMode::Accumulator | AddressingMode::Implied => {
+                    if let Some(operand) = parts.get(2) {
+                        return Err(assembly_error(number, format!("unexpected operand: {}", operand)));
+                    }
                 }
             }
         }
     }
 
-    machine_code
+    Ok(Listing { machine_code, lines, labels })
 }
@@ -1,14 +1,16 @@
-///
-/// File: cpu/addressing_mode.rs
-/// The addressing mode module contains the implementation of the addressing mode
-/// enum and the implementation of the addressing mode functions.
-///
+//!
+//! File: cpu/addressing_mode.rs
+//! The addressing mode module contains the implementation of the addressing mode
+//! enum and the implementation of the addressing mode functions.
+//!
 
 use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::operand::Operand;
+use crate::util::types::{Byte, Address};
 use std::fmt;
 
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum AddressingMode {
     Implied,
     Immediate,
@@ -23,46 +25,110 @@ pub enum AddressingMode {
     IndirectY,
     Relative,
     Accumulator,
+    // 65C02 only
+    ZeroPageIndirect,
+    AbsoluteIndirectX,
+    ZeroPageRelative,
 }
 
 impl AddressingMode {
-    pub fn get_address(&self, cpu: &mut Cpu6502) -> u16 {
+    ///
+    /// number of bytes of the instruction, the opcode included
+    ///
+    pub fn length(&self) -> u8 {
         match self {
-            AddressingMode::Implied => 0,
-            AddressingMode::Immediate => {
-                cpu.registers.pc += 1;
-                cpu.registers.pc
+            AddressingMode::Implied | AddressingMode::Accumulator => 1,
+            AddressingMode::Absolute
+            | AddressingMode::AbsoluteX
+            | AddressingMode::AbsoluteY
+            | AddressingMode::Indirect
+            | AddressingMode::AbsoluteIndirectX
+            | AddressingMode::ZeroPageRelative => 3,
+            _ => 2,
+        }
+    }
+
+    ///
+    /// read the operand bytes starting at `address`, just after the opcode,
+    /// and compute the effective address,
+    /// also returns true if an indexed address crossed a page boundary
+    ///
+    pub fn resolve(&self, cpu: &mut Cpu6502, address: Address) -> (Operand, bool) {
+        let operand = match self {
+            AddressingMode::Implied => Operand::None,
+            AddressingMode::Accumulator => Operand::Accumulator,
+            AddressingMode::Immediate => Operand::Immediate(cpu.read_byte(address)),
+            AddressingMode::ZeroPage => Operand::Address(cpu.read_byte(address) as Address),
+            AddressingMode::ZeroPageX => Operand::Address(cpu.read_byte(address).wrapping_add(cpu.registers.x) as Address),
+            AddressingMode::ZeroPageY => Operand::Address(cpu.read_byte(address).wrapping_add(cpu.registers.y) as Address),
+            AddressingMode::Absolute => Operand::Address(cpu.read_word(address)),
+            AddressingMode::AbsoluteX => {
+                let base = cpu.read_word(address);
+                return indexed(base, cpu.registers.x);
+            },
+            AddressingMode::AbsoluteY => {
+                let base = cpu.read_word(address);
+                return indexed(base, cpu.registers.y);
             },
-            AddressingMode::ZeroPage => cpu.read_byte(cpu.registers.pc + 1) as u16,
-            AddressingMode::ZeroPageX => ((cpu.read_byte(cpu.registers.pc + 1) + cpu.registers.x) & 0xFF) as u16,
-            AddressingMode::ZeroPageY => ((cpu.read_byte(cpu.registers.pc + 1) + cpu.registers.y) & 0xFF) as u16,
-            AddressingMode::Absolute => cpu.read_word(cpu.registers.pc + 1),
-            AddressingMode::AbsoluteX => cpu.read_word(cpu.registers.pc + 1) + cpu.registers.x as u16,
-            AddressingMode::AbsoluteY => cpu.read_word(cpu.registers.pc + 1) + cpu.registers.y as u16,
             AddressingMode::Indirect => {
-                let address = cpu.read_word(cpu.registers.pc + 1);
-                let low_byte = cpu.read_byte(address) as u16;
-                let high_byte = if address & 0xFF == 0xFF {
-                    cpu.read_byte(address & !0xFF) as u16
+                let pointer = cpu.read_word(address);
+                let low_byte = cpu.read_byte(pointer) as Address;
+                // the NMOS parts do not carry into the high byte of the pointer
+                let high_byte = if pointer & 0xFF == 0xFF && cpu.variant.has_jmp_indirect_bug() {
+                    cpu.read_byte(pointer & !0xFF) as Address
                 } else {
-                    cpu.read_byte(address + 1) as u16
+                    cpu.read_byte(pointer.wrapping_add(1)) as Address
                 };
-                low_byte | (high_byte << 8)
+                Operand::Address(low_byte | (high_byte << 8))
             },
             AddressingMode::IndirectX => {
-                let address = cpu.read_byte(cpu.registers.pc + 1) + cpu.registers.x;
-                cpu.read_word(address as u16)
-            }
+                let pointer = cpu.read_byte(address).wrapping_add(cpu.registers.x);
+                Operand::Address(zero_page_word(cpu, pointer))
+            },
             AddressingMode::IndirectY => {
-                let address = cpu.read_byte(cpu.registers.pc + 1);
-                cpu.read_word(address as u16) + cpu.registers.y as u16
-            }
-            AddressingMode::Relative => cpu.read_byte(cpu.registers.pc + 1) as u16,
-            AddressingMode::Accumulator => cpu.registers.a as u16,
-        }
+                let pointer = cpu.read_byte(address);
+                let base = zero_page_word(cpu, pointer);
+                return indexed(base, cpu.registers.y);
+            },
+            AddressingMode::Relative => {
+                // the offset is relative to the address following the branch instruction
+                let offset = cpu.read_byte(address) as i8;
+                Operand::Address(address.wrapping_add(1).wrapping_add(offset as Address))
+            },
+            AddressingMode::ZeroPageIndirect => {
+                let pointer = cpu.read_byte(address);
+                Operand::Address(zero_page_word(cpu, pointer))
+            },
+            AddressingMode::AbsoluteIndirectX => {
+                let pointer = cpu.read_word(address).wrapping_add(cpu.registers.x as Address);
+                Operand::Address(cpu.read_word(pointer))
+            },
+            AddressingMode::ZeroPageRelative => {
+                let zero_page = cpu.read_byte(address) as Address;
+                let offset = cpu.read_byte(address.wrapping_add(1)) as i8;
+                Operand::ZeroPageRelative(zero_page, address.wrapping_add(2).wrapping_add(offset as Address))
+            },
+        };
+        (operand, false)
     }
 }
 
+///
+/// add the index to the base address and record if a page boundary was crossed
+///
+fn indexed(base: Address, index: u8) -> (Operand, bool) {
+    let address = base.wrapping_add(index as Address);
+    (Operand::Address(address), base & 0xFF00 != address & 0xFF00)
+}
+
+///
+/// read a pointer from the zero page, the high byte wraps around inside the zero page
+///
+fn zero_page_word(cpu: &mut Cpu6502, pointer: Byte) -> Address {
+    let low_byte = cpu.read_byte(pointer as Address) as Address;
+    let high_byte = cpu.read_byte(pointer.wrapping_add(1) as Address) as Address;
+    low_byte | (high_byte << 8)
+}
 
 impl fmt::Display for AddressingMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -80,6 +146,9 @@ impl fmt::Display for AddressingMode {
             AddressingMode::Relative => "relative",
             AddressingMode::Implied => "implied",
             AddressingMode::Accumulator => "accumulator",
+            AddressingMode::ZeroPageIndirect => "zero_page_indirect",
+            AddressingMode::AbsoluteIndirectX => "absolute_indirect_x",
+            AddressingMode::ZeroPageRelative => "zero_page_relative",
         };
         write!(f, "{}", mode)
     }
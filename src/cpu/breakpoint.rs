@@ -0,0 +1,202 @@
+//!
+//! File: cpu/breakpoint.rs
+//! The breakpoint module contains the breakpoints on the program counter and the
+//! watchpoints on the reads and writes of an address range, checked by the run loops
+//! of the run module after every instruction.
+//! A breakpoint stops the run before the instruction at its address and a watchpoint
+//! after the instruction that accessed its range, the fetch of the instruction itself excluded.
+//! Both can have a condition, checked when they are reached, and an ignore count:
+//! a reached breakpoint whose condition holds counts a hit, and it only fires
+//! once it has more hits than its ignore count. A temporary breakpoint is removed when it fires.
+//!
+
+use std::mem;
+
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::expression::Expression;
+use crate::cpu::run::StopReason;
+use crate::util::types::Address;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Access {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl Access {
+    fn matches(&self, write: bool) -> bool {
+        match self {
+            Access::Read => !write,
+            Access::Write => write,
+            Access::ReadWrite => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BreakpointKind {
+    // stop before the instruction at this address
+    Execution(Address),
+    // stop after an instruction accessing an address of `start..=end`
+    Watch { start: Address, end: Address, access: Access },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Breakpoint {
+    pub id: usize,
+    pub kind: BreakpointKind,
+    // checked when the breakpoint is reached, it only counts a hit when true
+    pub condition: Option<Expression>,
+    // number of times it was reached with its condition true
+    pub hits: u64,
+    // hits to let through before it fires
+    pub ignore_count: u64,
+    // removed once it fired
+    pub temporary: bool,
+    pub enabled: bool,
+}
+
+///
+/// the breakpoints of a cpu and the accesses of the instruction being executed
+///
+#[derive(Default)]
+pub struct Breakpoints {
+    list: Vec<Breakpoint>,
+    next_id: usize,
+    // accesses recorded while a watchpoint is enabled, the bool is true for a write
+    pub(crate) accesses: Vec<(Address, bool)>,
+}
+
+impl Breakpoints {
+    ///
+    /// true if the accesses must be recorded for the watchpoints
+    ///
+    pub(crate) fn watching(&self) -> bool {
+        self.list.iter().any(|breakpoint| breakpoint.enabled && matches!(breakpoint.kind, BreakpointKind::Watch { .. }))
+    }
+
+    pub(crate) fn record(&mut self, address: Address, write: bool) {
+        self.accesses.push((address, write));
+    }
+}
+
+impl Cpu6502 {
+    ///
+    /// add an enabled breakpoint without condition, returns its id
+    ///
+    pub fn add_breakpoint(&mut self, kind: BreakpointKind) -> usize {
+        self.add_breakpoint_with(kind, None, false)
+    }
+
+    ///
+    /// add an enabled breakpoint with its condition, a temporary one is removed once it fires,
+    /// returns its id
+    ///
+    /// # Example
+    /// ```
+    /// use emul::cpu::breakpoint::BreakpointKind;
+    /// use emul::cpu::cpu_6502::Cpu6502;
+    /// use emul::cpu::expression::Expression;
+    /// use emul::cpu::run::StopReason;
+    /// use emul::memory::ram::Ram;
+    ///
+    /// let mut cpu = Cpu6502::new(Ram::new());
+    /// // INX ; JMP $0000
+    /// cpu.memory.load(&[0xE8, 0x4C, 0x00, 0x00], 0x0000).unwrap();
+    /// let condition = Expression::parse("X == 3").unwrap();
+    /// let id = cpu.add_breakpoint_with(BreakpointKind::Execution(0x0001), Some(condition), true);
+    /// assert_eq!(cpu.run_cycles(1000).unwrap(), StopReason::Breakpoint(id));
+    /// assert_eq!(cpu.registers.x, 3);
+    /// // the temporary breakpoint is gone
+    /// assert!(cpu.breakpoint(id).is_none());
+    /// ```
+    pub fn add_breakpoint_with(&mut self, kind: BreakpointKind, condition: Option<Expression>, temporary: bool) -> usize {
+        let breakpoints = &mut self.breakpoints;
+        let id = breakpoints.next_id;
+        breakpoints.next_id += 1;
+        breakpoints.list.push(Breakpoint {
+            id,
+            kind,
+            condition,
+            hits: 0,
+            ignore_count: 0,
+            temporary,
+            enabled: true,
+        });
+        id
+    }
+
+    ///
+    /// remove a breakpoint, false if there is none with this id
+    ///
+    pub fn remove_breakpoint(&mut self, id: usize) -> bool {
+        let count = self.breakpoints.list.len();
+        self.breakpoints.list.retain(|breakpoint| breakpoint.id != id);
+        self.breakpoints.list.len() != count
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.list.clear();
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints.list
+    }
+
+    pub fn breakpoint(&self, id: usize) -> Option<&Breakpoint> {
+        self.breakpoints.list.iter().find(|breakpoint| breakpoint.id == id)
+    }
+
+    pub fn breakpoint_mut(&mut self, id: usize) -> Option<&mut Breakpoint> {
+        self.breakpoints.list.iter_mut().find(|breakpoint| breakpoint.id == id)
+    }
+
+    ///
+    /// forget the accesses of the previous instruction, called before every instruction or interrupt
+    ///
+    pub(crate) fn breakpoints_begin(&mut self) {
+        self.breakpoints.accesses.clear();
+    }
+
+    ///
+    /// check the watchpoints against the accesses of the instruction that ran at `address`
+    /// for `length` bytes, then the breakpoints against the new program counter,
+    /// returns the first one that fires
+    ///
+    pub(crate) fn breakpoints_end(&mut self, address: Address, length: u8) -> Option<StopReason> {
+        if self.breakpoints.list.is_empty() {
+            return None;
+        }
+        let mut list = mem::take(&mut self.breakpoints.list);
+        let accesses = mem::take(&mut self.breakpoints.accesses);
+        let fetched = |access: Address| access.wrapping_sub(address) < length as Address;
+        let mut reason = None;
+        for breakpoint in list.iter_mut().filter(|breakpoint| breakpoint.enabled) {
+            let reached = match breakpoint.kind {
+                BreakpointKind::Execution(pc) => {
+                    (pc == self.registers.pc).then_some(StopReason::Breakpoint(breakpoint.id))
+                }
+                BreakpointKind::Watch { start, end, access } => accesses
+                    .iter()
+                    .find(|&&(accessed, write)| {
+                        (start..=end).contains(&accessed) && access.matches(write) && (write || !fetched(accessed))
+                    })
+                    .map(|&(address, write)| StopReason::Watchpoint { id: breakpoint.id, address, write }),
+            };
+            let hit = match reached {
+                Some(hit) if breakpoint.condition.as_ref().is_none_or(|condition| condition.is_true(self)) => hit,
+                _ => continue,
+            };
+            breakpoint.hits += 1;
+            if breakpoint.hits > breakpoint.ignore_count && reason.is_none() {
+                reason = Some(hit);
+            }
+        }
+        if let Some(StopReason::Breakpoint(id) | StopReason::Watchpoint { id, .. }) = reason {
+            list.retain(|breakpoint| breakpoint.id != id || !breakpoint.temporary);
+        }
+        self.breakpoints.list = list;
+        reason
+    }
+}
@@ -1,90 +1,426 @@
-///
-/// File: cpu/cpu_6502.rs
-/// The cpu module contains the implementation of the cpu struct and the implementation of the cpu functions.
-///
-
-use crate::cpu::{
-    instruction,
-    flag::Flag,
-};
+//!
+//! File: cpu/cpu_6502.rs
+//! The cpu module contains the implementation of the cpu struct and the implementation of the cpu functions.
+//!
+
+use std::io::Write;
+
+use crate::cpu::breakpoint::Breakpoints;
+use crate::cpu::flag::Flag;
+use crate::cpu::micro_op::{BusCapture, MicroState};
+use crate::cpu::decimal_mode::DecimalMode;
+use crate::cpu::decoded_instruction::DecodedInstruction;
+use crate::cpu::instruction::Instruction;
 use crate::cpu::register::Registers;
-use crate::memory::ram::Ram;
-use crate::util::constants::{MEMORY_SIZE, STACK_SIZE, RESET_ADDRESS_LOW, RESET_ADDRESS_HIGH, OPCODE_KIL};
+use crate::cpu::rewind::Journal;
+use crate::cpu::variant::{BrkBehavior, CpuVariant, IllegalOpcodes, JamBehavior};
+use crate::error::EmulatorError;
+use crate::memory::bus::Bus;
+use crate::util::constants::{
+    MEMORY_SIZE, STACK_SIZE, OPCODE_KIL,
+    RESET_ADDRESS_LOW, RESET_ADDRESS_HIGH,
+    NMI_ADDRESS_LOW, NMI_ADDRESS_HIGH,
+    IRQ_ADDRESS_LOW, IRQ_ADDRESS_HIGH,
+};
 use crate::util::types::{Byte, Word, Address};
 
 
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum ExecutionState {
     Running,
+    // waiting for an interrupt after WAI
+    Waiting,
     Stopped,
-    Error,
 }
 
-#[derive(Clone, Copy)]
 pub struct Cpu6502 {
     pub registers: Registers,
-    pub memory: Ram,
+    pub memory: Box<dyn Bus>,
+    // member of the 6502 family being emulated
+    pub variant: CpuVariant,
+    // behavior of ADC and SBC when the decimal flag is set
+    pub decimal_mode: DecimalMode,
+    // constant ORed into the accumulator by the unstable XAA and LXA, it depends on the chip and its temperature
+    pub magic_constant: Byte,
+    // what to do with the opcodes outside the documented instruction set
+    pub illegal_opcodes: IllegalOpcodes,
+    // what to do when a KIL opcode is executed
+    pub jam: JamBehavior,
+    // what to report after a BRK
+    pub brk: BrkBehavior,
+    // total number of cycles executed since the cpu was created
+    pub cycles: u64,
+    // cycles added by the instruction itself, like a taken branch
+    pub(crate) extra_cycles: u8,
+    // level of the IRQ line, the interrupt is taken as long as it is asserted and not masked
+    pub(crate) irq_line: bool,
+    // latched on the NMI edge, cleared once the interrupt is taken
+    pub(crate) nmi_pending: bool,
+    // set by WAI, cleared when an interrupt is signaled
+    pub(crate) waiting: bool,
+    // set by STP, cleared by a reset
+    pub(crate) stopped: bool,
+    // instruction in progress when running cycle by cycle with `tick`
+    micro: Option<MicroState>,
+    // set while a read-modify-write instruction runs in `tick`, holds back its write
+    pub(crate) capture: Option<BusCapture>,
+    // history of the last instructions when rewinding is enabled
+    pub(crate) journal: Option<Journal>,
+    // sink of the execution trace, one line per instruction
+    pub(crate) trace: Option<Box<dyn Write>>,
+    // breakpoints and watchpoints checked by the run loops
+    pub(crate) breakpoints: Breakpoints,
 }
 
 impl Cpu6502 {
-    pub fn new(ram: Ram) -> Cpu6502 {
+    pub fn new<B: Bus + 'static>(bus: B) -> Cpu6502 {
+        Cpu6502::with_variant(bus, CpuVariant::default())
+    }
+
+    pub fn with_variant<B: Bus + 'static>(bus: B, variant: CpuVariant) -> Cpu6502 {
         Cpu6502 {
             registers: Registers::new(),
-            memory: ram,
+            memory: Box::new(bus),
+            variant,
+            decimal_mode: variant.decimal_mode(),
+            magic_constant: 0xEE,
+            illegal_opcodes: variant.illegal_opcodes(),
+            jam: JamBehavior::default(),
+            brk: BrkBehavior::default(),
+            cycles: 0,
+            extra_cycles: 0,
+            irq_line: false,
+            nmi_pending: false,
+            waiting: false,
+            stopped: false,
+            micro: None,
+            capture: None,
+            journal: None,
+            trace: None,
+            breakpoints: Breakpoints::default(),
         }
     }
 
+    ///
+    /// run the 7 cycles reset sequence,
+    /// it behaves like an interrupt whose stack writes are turned into reads,
+    /// so the stack pointer is decremented three times but nothing is pushed
+    ///
     pub fn reset(&mut self) {
-        self.registers.a = 0;
-        self.registers.x = 0;
-        self.registers.y = 0;
-        self.registers.sp = 0xFF;
-        self.registers.pc = 0;
-        self.registers.status = 0;
-        self.registers.pc = self.memory.read(RESET_ADDRESS_LOW) as u16 | (self.memory.read(RESET_ADDRESS_HIGH) as u16) << 8;
+        for _ in 0..3 {
+            self.read_byte(STACK_SIZE as Address + self.registers.sp as Address);
+            self.registers.sp = self.registers.sp.wrapping_sub(1);
+        }
+        self.set_flag(Flag::Interrupt, true);
+        self.set_flag(Flag::Unused, true);
+        self.nmi_pending = false;
+        self.waiting = false;
+        self.stopped = false;
+        self.micro = None;
+        self.journal_clear();
+        if self.variant == CpuVariant::Cmos65C02 {
+            self.set_flag(Flag::Decimal, false);
+        }
+        self.registers.pc = self.read_byte(RESET_ADDRESS_LOW) as u16 | (self.read_byte(RESET_ADDRESS_HIGH) as u16) << 8;
+        self.cycles += 7;
+    }
+
+    ///
+    /// pull the IRQ line low, the interrupt is taken before the next instruction
+    /// as long as the line stays asserted and the interrupt flag is clear
+    ///
+    pub fn assert_irq(&mut self) {
+        self.irq_line = true;
+    }
+
+    pub fn release_irq(&mut self) {
+        self.irq_line = false;
+    }
+
+    ///
+    /// signal an edge on the NMI line, the interrupt is taken once before the next instruction
+    ///
+    pub fn trigger_nmi(&mut self) {
+        self.nmi_pending = true;
+    }
+
+    pub fn irq_asserted(&self) -> bool {
+        self.irq_line
+    }
+
+    pub fn nmi_pending(&self) -> bool {
+        self.nmi_pending
     }
 
-    pub fn dump(&self) {
-        println!("A: {:02X} X: {:02X} Y: {:02X} SP: {:02X} PC: {:04X} Status: {:02X}", self.registers.a, self.registers.x, self.registers.y, self.registers.sp, self.registers.pc, self.registers.status);
+    ///
+    /// push the return address and the status then jump through the vector,
+    /// the break flag is only set in the pushed status for BRK
+    ///
+    pub(crate) fn interrupt(&mut self, return_address: Address, vector_low: Address, vector_high: Address, break_flag: bool) {
+        self.push_word_stack(return_address);
+        let mut status = self.registers.status | Flag::Unused as Byte;
+        if break_flag {
+            status |= Flag::Break as Byte;
+        } else {
+            status &= !(Flag::Break as Byte);
+        }
+        self.push_stack(status);
+        self.set_flag(Flag::Interrupt, true);
+        // the 65C02 also leaves decimal mode when it takes an interrupt
+        if self.variant == CpuVariant::Cmos65C02 {
+            self.set_flag(Flag::Decimal, false);
+        }
+        self.registers.pc = self.read_byte(vector_low) as u16 | (self.read_byte(vector_high) as u16) << 8;
     }
 
-    pub fn execute_instruction(&mut self) -> Option<ExecutionState> {
-        if self.registers.pc as usize >= MEMORY_SIZE  {
-            println!("{}", MEMORY_SIZE);
-            println!("{:?}", self.registers.pc);
-            eprintln!("PC out of bounds: {:04X}", self.registers.pc);
-            return Some(ExecutionState::Error);
+    ///
+    /// take a pending hardware interrupt, NMI has priority over IRQ,
+    /// returns the cycles used or None if no interrupt was taken
+    ///
+    fn poll_interrupts(&mut self) -> Option<u8> {
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            self.interrupt(self.registers.pc, NMI_ADDRESS_LOW, NMI_ADDRESS_HIGH, false);
+        } else if self.irq_line && !self.get_flag(Flag::Interrupt) {
+            self.interrupt(self.registers.pc, IRQ_ADDRESS_LOW, IRQ_ADDRESS_HIGH, false);
+        } else {
+            return None;
         }
-        let opcode = self.memory.read(self.registers.pc);
-        let instruction = match instruction::INSTRUCTIONS.get(opcode as usize) {
-            Some(instr) => instr,
+        self.cycles += 7;
+        Some(7)
+    }
+
+    ///
+    /// the registers on one line
+    ///
+    pub fn dump(&self) -> String {
+        format!("A: {:02X} X: {:02X} Y: {:02X} SP: {:02X} PC: {:04X} Status: {:02X}", self.registers.a, self.registers.x, self.registers.y, self.registers.sp, self.registers.pc, self.registers.status)
+    }
+
+    ///
+    /// execute the instruction at the program counter,
+    /// returns the state of the cpu and the number of cycles the instruction took
+    ///
+    pub fn execute_instruction(&mut self) -> Result<(ExecutionState, u8), EmulatorError> {
+        self.journal_begin();
+        self.breakpoints_begin();
+        let result = self.execute_next();
+        self.journal_end();
+        result
+    }
+
+    fn execute_next(&mut self) -> Result<(ExecutionState, u8), EmulatorError> {
+        if self.stopped {
+            return Ok((ExecutionState::Stopped, 0));
+        }
+        if self.waiting {
+            // any interrupt signal wakes the cpu up, even a masked IRQ
+            if !self.nmi_pending && !self.irq_line {
+                self.cycles += 1;
+                return Ok((ExecutionState::Waiting, 1));
+            }
+            self.waiting = false;
+        }
+        if let Some(cycles) = self.poll_interrupts() {
+            self.check_bus()?;
+            return Ok((ExecutionState::Running, cycles));
+        }
+        self.trace_instruction()?;
+        let decoded = self.decode(self.registers.pc);
+        self.check_bus()?;
+        let instruction = decoded.instruction;
+        self.check_opcode(&instruction, decoded.address)?;
+        self.extra_cycles = 0;
+        // the program counter moves past the instruction before it runs, jumps and branches overwrite it
+        self.registers.pc = decoded.next_pc;
+        let skipped = !instruction.is_documented() && self.illegal_opcodes == IllegalOpcodes::Nop;
+        if !skipped {
+            (instruction.execute)(self, decoded.operand);
+        }
+
+        let mut cycles = instruction.cycles + self.extra_cycles;
+        if decoded.page_crossed && instruction.has_page_cross_penalty() {
+            cycles += 1;
+        }
+        self.cycles += cycles as u64;
+        self.check_bus()?;
+
+        if skipped {
+            return Ok((ExecutionState::Running, cycles));
+        }
+        Ok((self.completed(&instruction)?, cycles))
+    }
+
+    ///
+    /// refuse to start `instruction` at `address` when it is rejected by the policy
+    /// or when its bytes run past the end of the address space
+    ///
+    fn check_opcode(&self, instruction: &Instruction, address: Address) -> Result<(), EmulatorError> {
+        if !instruction.is_documented() && self.illegal_opcodes == IllegalOpcodes::Reject {
+            return Err(EmulatorError::IllegalOpcode { opcode: instruction.opcode, pc: address });
+        }
+        if address as usize + instruction.length as usize > MEMORY_SIZE {
+            return Err(EmulatorError::PcOverflow { pc: address });
+        }
+        Ok(())
+    }
+
+    ///
+    /// report the last access the bus could not serve
+    ///
+    fn check_bus(&mut self) -> Result<(), EmulatorError> {
+        match self.memory.take_fault() {
+            Some(address) => Err(EmulatorError::BusFault { address }),
+            None => Ok(()),
+        }
+    }
+
+    ///
+    /// state of the cpu once `instruction` has run,
+    /// KIL either halts the cpu until the next reset or reports the jam
+    /// with the program counter moved back on the opcode,
+    /// BRK only stops the cpu when asked to by `brk`
+    ///
+    fn completed(&mut self, instruction: &Instruction) -> Result<ExecutionState, EmulatorError> {
+        if instruction.name == OPCODE_KIL {
+            return match self.jam {
+                JamBehavior::Halt => {
+                    self.stopped = true;
+                    Ok(ExecutionState::Stopped)
+                }
+                JamBehavior::Error => {
+                    self.registers.pc = self.registers.pc.wrapping_sub(1);
+                    Err(EmulatorError::Jam { opcode: instruction.opcode, pc: self.registers.pc })
+                }
+            };
+        }
+        if instruction.name == "BRK" && self.brk == BrkBehavior::Stop {
+            return Ok(ExecutionState::Stopped);
+        }
+        Ok(ExecutionState::Running)
+    }
+
+    ///
+    /// fetch the opcode at `address` and resolve its operand,
+    /// the program counter is left untouched
+    ///
+    pub fn decode(&mut self, address: Address) -> DecodedInstruction {
+        let opcode = self.read_byte(address);
+        let instruction = self.variant.instructions()[opcode as usize];
+        let (operand, page_crossed) = instruction.addressing_mode.resolve(self, address.wrapping_add(1));
+        DecodedInstruction {
+            address,
+            opcode,
+            instruction,
+            operand,
+            next_pc: address.wrapping_add(instruction.length as Word),
+            page_crossed,
+        }
+    }
+
+    ///
+    /// run a single cycle, the cpu does exactly one bus access per call,
+    /// instructions and interrupts are spread over several calls
+    /// with the dummy reads and writes of the real hardware,
+    /// nothing is accessed once the cpu is stopped
+    ///
+    pub fn tick(&mut self) -> Result<ExecutionState, EmulatorError> {
+        if self.micro.is_none() {
+            self.journal_begin();
+            self.breakpoints_begin();
+        }
+        let result = self.tick_cycle();
+        if self.micro.is_none() {
+            self.journal_end();
+        }
+        result
+    }
+
+    fn tick_cycle(&mut self) -> Result<ExecutionState, EmulatorError> {
+        let mut state = match self.micro.take() {
+            Some(mut state) => {
+                state.run(self);
+                state
+            }
             None => {
-                eprintln!("Unknown opcode: {:02X} at address {:04X}", opcode, self.registers.pc);
-                return Some(ExecutionState::Error);
+                if self.stopped {
+                    return Ok(ExecutionState::Stopped);
+                }
+                if self.waiting {
+                    // any interrupt signal wakes the cpu up, even a masked IRQ
+                    if !self.nmi_pending && !self.irq_line {
+                        self.read_byte(self.registers.pc);
+                        self.cycles += 1;
+                        self.check_bus()?;
+                        return Ok(ExecutionState::Waiting);
+                    }
+                    self.waiting = false;
+                }
+                if self.nmi_pending {
+                    self.nmi_pending = false;
+                    MicroState::interrupt(self, NMI_ADDRESS_LOW)
+                } else if self.irq_line && !self.get_flag(Flag::Interrupt) {
+                    MicroState::interrupt(self, IRQ_ADDRESS_LOW)
+                } else {
+                    let opcode = self.memory.peek(self.registers.pc);
+                    self.trace_instruction()?;
+                    self.check_opcode(&self.variant.instructions()[opcode as usize], self.registers.pc)?;
+                    MicroState::fetch(self)
+                }
             }
         };
-        let addressing_mode = instruction.addressing_mode;
-        (instruction.execute)(self, addressing_mode);
-        if instruction.name == OPCODE_KIL || instruction.name == "BRK" {
-            return Some(ExecutionState::Stopped);
+        self.cycles += 1;
+        // a faulted access abandons the instruction in progress
+        self.check_bus()?;
+
+        state.skip(self);
+        if !state.done() {
+            self.micro = Some(state);
+            return Ok(ExecutionState::Running);
         }
-        Some(ExecutionState::Running)
+        match state.instruction() {
+            Some(instruction) if state.executed() => self.completed(&instruction),
+            _ => Ok(ExecutionState::Running),
+        }
+    }
+
+    ///
+    /// true between two calls of `tick` that belong to the same instruction or interrupt
+    ///
+    pub fn in_instruction(&self) -> bool {
+        self.micro.is_some()
     }
 
     pub fn read_byte(&mut self, address: Address) -> Byte {
-        self.registers.pc += 1;
-        self.memory.read(address)
+        if self.breakpoints.watching() {
+            self.breakpoints.record(address, false);
+        }
+        let data = self.memory.read(address);
+        if let Some(capture) = self.capture.as_mut() {
+            capture.read = data;
+        }
+        data
     }
 
     pub fn read_word(&mut self, address: Address) -> Word {
         let low = self.read_byte(address) as Word;
-        let high = self.read_byte(address + 1) as Word;
+        let high = self.read_byte(address.wrapping_add(1)) as Word;
         low | (high << 8)
     }
 
     pub fn write_byte(&mut self, address: Address, data: Byte) {
-        self.registers.pc += 1;
+        // a captured write is recorded when it is replayed on its own cycle
+        if let Some(capture) = self.capture.as_mut() {
+            capture.write = Some((address, data));
+            return;
+        }
+        if self.breakpoints.watching() {
+            self.breakpoints.record(address, true);
+        }
+        if let Some(journal) = self.journal.as_mut() {
+            journal.record_write(address, self.memory.peek(address));
+        }
         self.memory.write(address, data);
     }
 
@@ -107,13 +443,13 @@ impl Cpu6502 {
     }
 
     pub fn push_stack(&mut self, data: Byte) {
-        self.memory.write((STACK_SIZE as Word + self.registers.sp as Address) as Address, data);
+        self.write_byte((STACK_SIZE as Word + self.registers.sp as Address) as Address, data);
         self.registers.sp = self.registers.sp.wrapping_sub(1);
     }
 
     pub fn pop_stack(&mut self) -> Byte {
         self.registers.sp = self.registers.sp.wrapping_add(1);
-        self.memory.read((STACK_SIZE as Word + self.registers.sp as Address) as Address)
+        self.read_byte((STACK_SIZE as Word + self.registers.sp as Address) as Address)
     }
 
     pub fn push_word_stack(&mut self, data: Word) {
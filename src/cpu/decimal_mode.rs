@@ -0,0 +1,15 @@
+//!
+//! File: cpu/decimal_mode.rs
+//! The decimal mode module contains the enum selecting how ADC and SBC behave
+//! when the decimal flag is set.
+//!
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DecimalMode {
+    // NMOS 6502, N, V and Z are computed from the binary intermediate result
+    Nmos,
+    // 65C02, N and Z are valid and the instruction takes one more cycle
+    Cmos,
+    // Ricoh 2A03, the decimal flag exists but ADC and SBC ignore it
+    Disabled,
+}
@@ -0,0 +1,22 @@
+//!
+//! File: cpu/decoded_instruction.rs
+//! The decoded instruction module contains the output of the decode stage,
+//! everything the execute stage needs to run an instruction.
+//!
+
+use crate::cpu::instruction::Instruction;
+use crate::cpu::operand::Operand;
+use crate::util::types::{Byte, Address};
+
+#[derive(Clone, Copy)]
+pub struct DecodedInstruction {
+    // address of the opcode
+    pub address: Address,
+    pub opcode: Byte,
+    pub instruction: Instruction<'static>,
+    pub operand: Operand,
+    // address of the following instruction, where the program counter goes unless the instruction jumps
+    pub next_pc: Address,
+    // set when an indexed address crosses a page boundary
+    pub page_crossed: bool,
+}
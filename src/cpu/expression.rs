@@ -0,0 +1,295 @@
+//!
+//! File: cpu/expression.rs
+//! The expression module contains the conditions of the breakpoints, small expressions
+//! over the registers and the memory like `A == $FF && [$10] > 3`.
+//! The operands are numbers in decimal, `$` hex or `%` binary, the registers
+//! `A`, `X`, `Y`, `SP`, `P` and `PC`, and `[address]` for the byte in memory.
+//! The operators are the ones of Rust with the same precedence:
+//! `!` and `-`, then `+ -`, `&`, `^`, `|`, the comparisons, `&&` and `||`.
+//! The memory is read with `peek` so evaluating a condition has no side effect.
+//!
+
+use std::fmt;
+
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::error::EmulatorError;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Register {
+    A,
+    X,
+    Y,
+    Sp,
+    P,
+    Pc,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Operator {
+    Add,
+    Subtract,
+    BitAnd,
+    BitXor,
+    BitOr,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Node {
+    Number(i64),
+    Register(Register),
+    Memory(Box<Node>),
+    Not(Box<Node>),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Expression {
+    text: String,
+    root: Node,
+}
+
+impl Expression {
+    ///
+    /// parse an expression, the error gives the offset of the first character not understood
+    ///
+    /// # Example
+    /// ```
+    /// use emul::cpu::cpu_6502::Cpu6502;
+    /// use emul::cpu::expression::Expression;
+    /// use emul::memory::ram::Ram;
+    ///
+    /// let mut cpu = Cpu6502::new(Ram::new());
+    /// cpu.registers.a = 0xFF;
+    /// cpu.memory.write(0x0010, 4);
+    /// let condition = Expression::parse("A == $FF && [$10] > 3").unwrap();
+    /// assert_eq!(condition.is_true(&cpu), true);
+    /// ```
+    pub fn parse(text: &str) -> Result<Expression, EmulatorError> {
+        let mut parser = Parser { text: text.as_bytes(), position: 0 };
+        let root = parser.binary(0)?;
+        parser.skip_white_space();
+        if parser.position != text.len() {
+            return Err(parser.error("unexpected character"));
+        }
+        Ok(Expression { text: text.trim().to_owned(), root })
+    }
+
+    ///
+    /// value of the expression, comparisons and logical operators give 1 or 0
+    ///
+    pub fn evaluate(&self, cpu: &Cpu6502) -> i64 {
+        evaluate(&self.root, cpu)
+    }
+
+    pub fn is_true(&self, cpu: &Cpu6502) -> bool {
+        self.evaluate(cpu) != 0
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+fn evaluate(node: &Node, cpu: &Cpu6502) -> i64 {
+    match node {
+        Node::Number(value) => *value,
+        Node::Register(register) => match register {
+            Register::A => cpu.registers.a as i64,
+            Register::X => cpu.registers.x as i64,
+            Register::Y => cpu.registers.y as i64,
+            Register::Sp => cpu.registers.sp as i64,
+            Register::P => cpu.registers.status as i64,
+            Register::Pc => cpu.registers.pc as i64,
+        },
+        Node::Memory(address) => cpu.memory.peek(evaluate(address, cpu) as u16) as i64,
+        Node::Not(operand) => (evaluate(operand, cpu) == 0) as i64,
+        Node::Negate(operand) => evaluate(operand, cpu).wrapping_neg(),
+        Node::Binary(operator, left, right) => {
+            let left = evaluate(left, cpu);
+            // the logical operators do not evaluate their right side when the left one decides
+            match operator {
+                Operator::And if left == 0 => return 0,
+                Operator::Or if left != 0 => return 1,
+                _ => {}
+            }
+            let right = evaluate(right, cpu);
+            match operator {
+                Operator::Add => left.wrapping_add(right),
+                Operator::Subtract => left.wrapping_sub(right),
+                Operator::BitAnd => left & right,
+                Operator::BitXor => left ^ right,
+                Operator::BitOr => left | right,
+                Operator::Equal => (left == right) as i64,
+                Operator::NotEqual => (left != right) as i64,
+                Operator::Less => (left < right) as i64,
+                Operator::LessEqual => (left <= right) as i64,
+                Operator::Greater => (left > right) as i64,
+                Operator::GreaterEqual => (left >= right) as i64,
+                Operator::And | Operator::Or => (right != 0) as i64,
+            }
+        }
+    }
+}
+
+// binary operators from the loosest to the tightest binding, with their spelling
+const LEVELS: [&[(&str, Operator)]; 6] = [
+    &[("||", Operator::Or)],
+    &[("&&", Operator::And)],
+    &[
+        ("==", Operator::Equal),
+        ("!=", Operator::NotEqual),
+        ("<=", Operator::LessEqual),
+        (">=", Operator::GreaterEqual),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+    ],
+    &[("|", Operator::BitOr)],
+    &[("^", Operator::BitXor)],
+    &[("&", Operator::BitAnd)],
+];
+
+const ADDITIVE: &[(&str, Operator)] = &[("+", Operator::Add), ("-", Operator::Subtract)];
+
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> EmulatorError {
+        EmulatorError::InvalidExpression { offset: self.position, message: message.to_owned() }
+    }
+
+    fn skip_white_space(&mut self) {
+        while self.text.get(self.position).is_some_and(u8::is_ascii_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    ///
+    /// consume one of the operators, `&` and `|` are not taken from `&&` and `||`
+    ///
+    fn operator(&mut self, operators: &[(&str, Operator)]) -> Option<Operator> {
+        self.skip_white_space();
+        let rest = &self.text[self.position..];
+        for &(spelling, operator) in operators {
+            let doubled = spelling.len() == 1 && rest.get(1) == rest.first() && matches!(rest.first(), Some(b'&' | b'|'));
+            if rest.starts_with(spelling.as_bytes()) && !doubled {
+                self.position += spelling.len();
+                return Some(operator);
+            }
+        }
+        None
+    }
+
+    ///
+    /// left associative operators of `LEVELS[level]` and the levels binding tighter,
+    /// the additive operators come after the last level
+    ///
+    fn binary(&mut self, level: usize) -> Result<Node, EmulatorError> {
+        let operators = LEVELS.get(level).copied().unwrap_or(ADDITIVE);
+        let mut left = self.operand(level)?;
+        while let Some(operator) = self.operator(operators) {
+            let right = self.operand(level)?;
+            left = Node::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn operand(&mut self, level: usize) -> Result<Node, EmulatorError> {
+        if level < LEVELS.len() {
+            self.binary(level + 1)
+        } else {
+            self.unary()
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, EmulatorError> {
+        self.skip_white_space();
+        match self.text.get(self.position) {
+            Some(b'!') => {
+                self.position += 1;
+                Ok(Node::Not(Box::new(self.unary()?)))
+            }
+            Some(b'-') => {
+                self.position += 1;
+                Ok(Node::Negate(Box::new(self.unary()?)))
+            }
+            Some(b'(') => {
+                self.position += 1;
+                let node = self.binary(0)?;
+                self.close(b')')?;
+                Ok(node)
+            }
+            Some(b'[') => {
+                self.position += 1;
+                let node = self.binary(0)?;
+                self.close(b']')?;
+                Ok(Node::Memory(Box::new(node)))
+            }
+            Some(b'$') => {
+                self.position += 1;
+                self.number(16)
+            }
+            Some(b'%') => {
+                self.position += 1;
+                self.number(2)
+            }
+            Some(b'0'..=b'9') => self.number(10),
+            Some(byte) if byte.is_ascii_alphabetic() => self.register(),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn close(&mut self, delimiter: u8) -> Result<(), EmulatorError> {
+        self.skip_white_space();
+        if self.text.get(self.position) != Some(&delimiter) {
+            return Err(self.error(&format!("expected '{}'", delimiter as char)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn word(&mut self) -> &str {
+        let start = self.position;
+        while self.text.get(self.position).is_some_and(u8::is_ascii_alphanumeric) {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.text[start..self.position]).unwrap_or_default()
+    }
+
+    fn number(&mut self, radix: u32) -> Result<Node, EmulatorError> {
+        let start = self.position;
+        let word = self.word();
+        i64::from_str_radix(word, radix).map(Node::Number).map_err(|_| {
+            EmulatorError::InvalidExpression { offset: start, message: format!("invalid number: {}", word) }
+        })
+    }
+
+    fn register(&mut self) -> Result<Node, EmulatorError> {
+        let start = self.position;
+        let register = match self.word().to_ascii_uppercase().as_str() {
+            "A" => Register::A,
+            "X" => Register::X,
+            "Y" => Register::Y,
+            "SP" | "S" => Register::Sp,
+            "P" => Register::P,
+            "PC" => Register::Pc,
+            word => {
+                return Err(EmulatorError::InvalidExpression { offset: start, message: format!("unknown register: {}", word) })
+            }
+        };
+        Ok(Node::Register(register))
+    }
+}
@@ -1,7 +1,7 @@
-///
-/// File: cpu/flag.rs
-/// The flag module contains the implementation of the flag enum and the implementation of the flag functions.
-///
+//!
+//! File: cpu/flag.rs
+//! The flag module contains the implementation of the flag enum and the implementation of the flag functions.
+//!
 
 #[derive(Clone, Copy)]
 pub enum Flag {
@@ -1,14 +1,16 @@
-///
-/// File: cpu/function.rs
-/// The function module contains the implementation of the cpu functions.
-///
+//!
+//! File: cpu/function.rs
+//! The function module contains the implementation of the cpu functions.
+//!
 
 use crate::cpu::{
     flag::Flag,
-    addressing_mode::AddressingMode,
-    cpu_6502::Cpu6502
+    operand::Operand,
+    cpu_6502::Cpu6502,
+    decimal_mode::DecimalMode,
 };
-use crate::util::types::{Byte};
+use crate::util::constants::{IRQ_ADDRESS_LOW, IRQ_ADDRESS_HIGH};
+use crate::util::types::{Byte, Address};
 
 ///
 /// # ADC
@@ -19,11 +21,11 @@ use crate::util::types::{Byte};
 ///
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 ///
 /// # Exemple
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::cpu::function::adc;
@@ -32,7 +34,7 @@ use crate::util::types::{Byte};
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.registers.a = 0x01;
 /// cpu.set_flag(Flag::Carry, true);
-/// adc(&mut cpu, AddressingMode::Immediate);
+/// adc(&mut cpu, Operand::Immediate(0x00));
 /// assert_eq!(cpu.registers.a, 0x02);
 /// assert_eq!(cpu.get_flag(Flag::Carry), false);
 /// assert_eq!(cpu.get_flag(Flag::Zero), false);
@@ -55,17 +57,30 @@ use crate::util::types::{Byte};
 /// * `IndirectX` - add value at address + x to accumulator
 /// * `IndirectY` - add value at address + y to accumulator
 ///
+/// # Decimal
+/// when the decimal flag is set the operands are packed BCD,
+/// the flags follow the `decimal_mode` of the cpu
 ///
 /// # See
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#ADC](https://www.masswerk.at/6502/6502_instruction_set.html#ADC)
 ///
-pub fn adc(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    let carry = cpu.get_flag(Flag::Carry) as Byte;
-    let result = cpu.registers.a.wrapping_add(value).wrapping_add(carry);
+pub fn adc(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    add_with_carry(cpu, value);
+}
 
-    cpu.set_flag(Flag::Carry, result < cpu.registers.a || result < value);
+///
+/// add a value and the carry to the accumulator, shared by ADC and RRA
+///
+fn add_with_carry(cpu: &mut Cpu6502, value: Byte) {
+    if decimal_enabled(cpu) {
+        adc_decimal(cpu, value);
+        return;
+    }
+    let sum = cpu.registers.a as u16 + value as u16 + cpu.get_flag(Flag::Carry) as u16;
+    let result = sum as Byte;
+
+    cpu.set_flag(Flag::Carry, sum > 0xFF);
     cpu.set_flag(Flag::Zero, result == 0);
     cpu.set_flag(Flag::Negative, result & 0x80 != 0);
     cpu.set_flag(Flag::Overflow, (cpu.registers.a ^ result) & (value ^ result) & 0x80 != 0);
@@ -73,6 +88,119 @@ pub fn adc(cpu: &mut Cpu6502, mode: AddressingMode) {
     cpu.registers.a = result;
 }
 
+///
+/// read the value an instruction works on,
+/// the immediate byte, the accumulator or the byte at the effective address
+///
+fn read_operand(cpu: &mut Cpu6502, operand: Operand) -> Byte {
+    match operand {
+        Operand::Immediate(value) => value,
+        Operand::Accumulator => cpu.registers.a,
+        _ => cpu.read_byte(operand.address()),
+    }
+}
+
+///
+/// store the result of a read-modify-write instruction where its operand was read
+///
+fn write_operand(cpu: &mut Cpu6502, operand: Operand, value: Byte) {
+    match operand {
+        Operand::Accumulator => cpu.registers.a = value,
+        _ => cpu.write_byte(operand.address(), value),
+    }
+}
+
+///
+/// true if ADC and SBC must work on BCD operands
+///
+fn decimal_enabled(cpu: &Cpu6502) -> bool {
+    cpu.get_flag(Flag::Decimal) && cpu.decimal_mode != DecimalMode::Disabled
+}
+
+///
+/// decimal mode addition,
+/// the NMOS 6502 computes N and V on the intermediate result before the high nibble is adjusted
+/// and Z on the binary result, the 65C02 computes N and Z on the final result for one more cycle
+///
+/// # See
+/// * [http://www.6502.org/tutorials/decimal_mode.html](http://www.6502.org/tutorials/decimal_mode.html)
+///
+fn adc_decimal(cpu: &mut Cpu6502, value: Byte) {
+    let a = cpu.registers.a;
+    let carry = cpu.get_flag(Flag::Carry) as u16;
+
+    let mut low = (a & 0x0F) as u16 + (value & 0x0F) as u16 + carry;
+    if low >= 0x0A {
+        low = ((low + 0x06) & 0x0F) + 0x10;
+    }
+    let mut result = (a & 0xF0) as u16 + (value & 0xF0) as u16 + low;
+    let intermediate = result as Byte;
+    let signed = (a & 0xF0) as i8 as i16 + (value & 0xF0) as i8 as i16 + low as i16;
+    if result >= 0xA0 {
+        result += 0x60;
+    }
+    let binary = a.wrapping_add(value).wrapping_add(carry as Byte);
+
+    cpu.set_flag(Flag::Carry, result >= 0x100);
+    cpu.set_flag(Flag::Overflow, !(-128..=127).contains(&signed));
+    cpu.registers.a = result as Byte;
+    if cpu.decimal_mode == DecimalMode::Cmos {
+        cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
+        cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
+        cpu.extra_cycles += 1;
+    } else {
+        cpu.set_flag(Flag::Zero, binary == 0);
+        cpu.set_flag(Flag::Negative, intermediate & 0x80 != 0);
+    }
+}
+
+///
+/// decimal mode subtraction,
+/// the NMOS 6502 sets every flag like the binary subtraction,
+/// the 65C02 computes N and Z on the final result for one more cycle
+///
+/// # See
+/// * [http://www.6502.org/tutorials/decimal_mode.html](http://www.6502.org/tutorials/decimal_mode.html)
+///
+fn sbc_decimal(cpu: &mut Cpu6502, value: Byte) {
+    let a = cpu.registers.a;
+    let borrow = !cpu.get_flag(Flag::Carry) as i16;
+
+    let binary = a as i16 - value as i16 - borrow;
+    let mut low = (a & 0x0F) as i16 - (value & 0x0F) as i16 - borrow;
+    let result = if cpu.decimal_mode == DecimalMode::Cmos {
+        let mut result = binary;
+        if result < 0 {
+            result -= 0x60;
+        }
+        if low < 0 {
+            result -= 0x06;
+        }
+        result
+    } else {
+        if low < 0 {
+            low = ((low - 0x06) & 0x0F) - 0x10;
+        }
+        let mut result = (a & 0xF0) as i16 - (value & 0xF0) as i16 + low;
+        if result < 0 {
+            result -= 0x60;
+        }
+        result
+    };
+
+    cpu.set_flag(Flag::Carry, binary >= 0);
+    cpu.set_flag(Flag::Overflow, (a ^ value) & (a ^ binary as Byte) & 0x80 != 0);
+    cpu.registers.a = result as Byte;
+    if cpu.decimal_mode == DecimalMode::Cmos {
+        cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
+        cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
+        cpu.extra_cycles += 1;
+    } else {
+        cpu.set_flag(Flag::Zero, binary as Byte == 0);
+        cpu.set_flag(Flag::Negative, binary as Byte & 0x80 != 0);
+    }
+}
+
 ///
 /// # BRK
 /// this is the break instruction,
@@ -82,11 +210,11 @@ pub fn adc(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 ///
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -98,13 +226,13 @@ pub fn adc(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// cpu.registers.status = 0b11111111;
 /// cpu.memory.write(0xFFFE, 0x10);
 /// cpu.memory.write(0xFFFF, 0x10);
-/// brk(&mut cpu, AddressingMode::Implied);
+/// brk(&mut cpu, Operand::None);
 /// assert_eq!(cpu.registers.pc, 0x1010);
 /// assert_eq!(cpu.get_flag(Flag::Break), true);
 /// assert_eq!(cpu.get_flag(Flag::Interrupt), true);
-/// assert_eq!(cpu.memory.read(0x01FF), 0x10);
-/// assert_eq!(cpu.memory.read(0x01FE), 0x00);
-/// assert_eq!(cpu.memory.read(0x01FD), 0b11111111);
+/// assert_eq!(cpu.memory.read(0x01FD), 0x10);
+/// assert_eq!(cpu.memory.read(0x01FC), 0x01);
+/// assert_eq!(cpu.memory.read(0x01FB), 0b11111111);
 /// ```
 ///
 /// # Flags
@@ -120,11 +248,9 @@ pub fn adc(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// # Note
 /// * needs to be tested
-pub fn brk(cpu: &mut Cpu6502, _mode: AddressingMode) {
-    cpu.push_word_stack(cpu.registers.pc);
-    cpu.push_stack(cpu.registers.status | Flag::Break as Byte);
-    cpu.set_flag(Flag::Interrupt, true);
-    cpu.registers.pc = cpu.read_word(0xFFFE);
+pub fn brk(cpu: &mut Cpu6502, _operand: Operand) {
+    // the byte following BRK is skipped, the return address is the opcode + 2
+    cpu.interrupt(cpu.registers.pc.wrapping_add(1), IRQ_ADDRESS_LOW, IRQ_ADDRESS_HIGH, true);
 }
 
 ///
@@ -134,11 +260,11 @@ pub fn brk(cpu: &mut Cpu6502, _mode: AddressingMode) {
 /// and sets the zero, negative and carry flags as appropriate
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 ///
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -146,7 +272,7 @@ pub fn brk(cpu: &mut Cpu6502, _mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.registers.a = 0x01;
-/// cmp(&mut cpu, AddressingMode::Immediate);
+/// cmp(&mut cpu, Operand::Immediate(0x00));
 /// assert_eq!(cpu.get_flag(Flag::Carry), true);
 /// assert_eq!(cpu.get_flag(Flag::Zero), false);
 /// assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -172,9 +298,8 @@ pub fn brk(cpu: &mut Cpu6502, _mode: AddressingMode) {
 ///
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#CMP](https://www.masswerk.at/6502/6502_instruction_set.html#CMP)
 ///
-pub fn cmp(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
+pub fn cmp(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
     let result = cpu.registers.a.wrapping_sub(value);
     cpu.set_flag(Flag::Carry, cpu.registers.a >= value);
     cpu.set_flag(Flag::Zero, result == 0);
@@ -188,10 +313,10 @@ pub fn cmp(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// and sets the zero and carry flags as appropriate
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -199,7 +324,7 @@ pub fn cmp(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.write_byte(0x0000, 0x04);
-/// dec(&mut cpu, AddressingMode::Absolute);
+/// dec(&mut cpu, Operand::Address(0x0000));
 /// assert_eq!(cpu.read_byte(0x0000), 0x03);
 /// ```
 /// # Flags
@@ -216,10 +341,10 @@ pub fn cmp(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// # See
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#DEC](https://www.masswerk.at/6502/6502_instruction_set.html#DEC)
 ///
-pub fn dec(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address).wrapping_sub(1);
-    cpu.write_byte(address, value);
+pub fn dec(cpu: &mut Cpu6502, operand: Operand) {
+    // the accumulator operand is DEC A, 65C02 only
+    let value = read_operand(cpu, operand).wrapping_sub(1);
+    write_operand(cpu, operand, value);
     cpu.set_flag(Flag::Zero, value == 0);
     cpu.set_flag(Flag::Negative, value & 0x80 != 0);
 }
@@ -232,10 +357,10 @@ pub fn dec(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// and sets the zero and negative flags as appropriate
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -244,7 +369,7 @@ pub fn dec(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.registers.a = 0b0000_0001;
 /// cpu.memory.write(0x0000, 0b0000_0011);
-/// eor(&mut cpu, AddressingMode::Absolute);
+/// eor(&mut cpu, Operand::Address(0x0000));
 /// assert_eq!(cpu.registers.a, 0b0000_0010);
 /// ```
 /// # Flags
@@ -262,9 +387,8 @@ pub fn dec(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// * `IndirectY` - exclusive or accumulator with value at address + y
 /// # See
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#EOR](https://www.masswerk.at/6502/6502_instruction_set.html#EOR)
-pub fn eor(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
+pub fn eor(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
     cpu.registers.a ^= value;
     cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
@@ -277,10 +401,10 @@ pub fn eor(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// and sets the zero and negative flags as appropriate
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -288,7 +412,7 @@ pub fn eor(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.write_byte(0x0000, 0x04);
-/// inc(&mut cpu, AddressingMode::Absolute);
+/// inc(&mut cpu, Operand::Address(0x0000));
 /// assert_eq!(cpu.read_byte(0x0000), 0x05);
 /// ```
 /// # Flags
@@ -304,10 +428,10 @@ pub fn eor(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#INC](https://www.masswerk.at/6502/6502_instruction_set.html#INC)
 /// # Note
 /// * needs to be tested
-pub fn inc(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address).wrapping_add(1);
-    cpu.write_byte(address, value);
+pub fn inc(cpu: &mut Cpu6502, operand: Operand) {
+    // the accumulator operand is INC A, 65C02 only
+    let value = read_operand(cpu, operand).wrapping_add(1);
+    write_operand(cpu, operand, value);
     cpu.set_flag(Flag::Zero, value == 0);
     cpu.set_flag(Flag::Negative, value & 0x80 != 0);
 }
@@ -318,20 +442,18 @@ pub fn inc(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// it sets the program counter to the address specified by the operand
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
 /// use emul::cpu::function::jmp;
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
-/// cpu.write_byte(0x1235, 0x00);
-/// cpu.write_byte(0x1236, 0x10);
-/// cpu.registers.pc = 0x1234;
-/// jmp(&mut cpu, AddressingMode::Absolute);
+/// cpu.registers.pc = 0x1237;
+/// jmp(&mut cpu, Operand::Address(0x1000));
 /// assert_eq!(cpu.registers.pc, 0x1000);
 /// ```
 /// # Addressing Mode
@@ -342,9 +464,8 @@ pub fn inc(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#JMP](https://www.masswerk.at/6502/6502_instruction_set.html#JMP)
 /// # Note
 /// * needs to be tested
-pub fn jmp(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    cpu.registers.pc = address;
+pub fn jmp(cpu: &mut Cpu6502, operand: Operand) {
+    cpu.registers.pc = operand.address();
 }
 
 ///
@@ -353,10 +474,10 @@ pub fn jmp(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// it loads a byte of memory into the accumulator setting the zero and negative flags as appropriate
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -364,7 +485,7 @@ pub fn jmp(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.write_byte(0x0000, 0x04);
-/// lda(&mut cpu, AddressingMode::Absolute);
+/// lda(&mut cpu, Operand::Address(0x0000));
 /// assert_eq!(cpu.registers.a, 0x04);
 /// ```
 /// # Flags
@@ -384,9 +505,8 @@ pub fn jmp(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#LDA](https://www.masswerk.at/6502/6502_instruction_set.html#LDA)
 ///
 ///
-pub fn lda(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    cpu.registers.a = cpu.read_byte(address);
+pub fn lda(cpu: &mut Cpu6502, operand: Operand) {
+    cpu.registers.a = read_operand(cpu, operand);
     cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
 }
@@ -397,10 +517,10 @@ pub fn lda(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// it loads a byte of memory into the x register setting the zero and negative flags as appropriate
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -408,7 +528,7 @@ pub fn lda(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.write_byte(0x0000, 0x04);
-/// ldx(&mut cpu, AddressingMode::Absolute);
+/// ldx(&mut cpu, Operand::Address(0x0000));
 /// assert_eq!(cpu.registers.x, 0x04);
 /// ```
 /// # Flags
@@ -424,9 +544,8 @@ pub fn lda(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// # See
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#LDX](https://www.masswerk.at/6502/6502_instruction_set.html#LDX)
 ///
-pub fn ldx(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    cpu.registers.x = cpu.read_byte(address);
+pub fn ldx(cpu: &mut Cpu6502, operand: Operand) {
+    cpu.registers.x = read_operand(cpu, operand);
     cpu.set_flag(Flag::Zero, cpu.registers.x == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.x & 0x80 != 0);
 }
@@ -437,10 +556,10 @@ pub fn ldx(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// it loads a byte of memory into the y register setting the zero and negative flags as appropriate
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -448,7 +567,7 @@ pub fn ldx(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.write_byte(0x0000, 0x04);
-/// ldy(&mut cpu, AddressingMode::Absolute);
+/// ldy(&mut cpu, Operand::Address(0x0000));
 /// assert_eq!(cpu.registers.y, 0x04);
 /// ```
 /// # Flags
@@ -464,9 +583,8 @@ pub fn ldx(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// # See
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#LDY](https://www.masswerk.at/6502/6502_instruction_set.html#LDY)
 ///
-pub fn ldy(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    cpu.registers.y = cpu.read_byte(address);
+pub fn ldy(cpu: &mut Cpu6502, operand: Operand) {
+    cpu.registers.y = read_operand(cpu, operand);
     cpu.set_flag(Flag::Zero, cpu.registers.y == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.y & 0x80 != 0);
 }
@@ -479,10 +597,10 @@ pub fn ldy(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// bit 7 is set to zero
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -490,7 +608,7 @@ pub fn ldy(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.write_byte(0x0000, 0x04);
-/// lsr(&mut cpu, AddressingMode::Absolute);
+/// lsr(&mut cpu, Operand::Address(0x0000));
 /// assert_eq!(cpu.read_byte(0x0000), 0x02);
 /// ```
 /// # Flags
@@ -507,12 +625,11 @@ pub fn ldy(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// # See
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#LSR](https://www.masswerk.at/6502/6502_instruction_set.html#LSR)
 ///
-pub fn lsr(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
+pub fn lsr(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
     cpu.set_flag(Flag::Carry, value & 0x01 != 0);
     let result = value >> 1;
-    cpu.write_byte(address, result);
+    write_operand(cpu, operand, result);
     cpu.set_flag(Flag::Zero, result == 0);
     cpu.set_flag(Flag::Negative, result & 0x80 != 0);
 }
@@ -524,10 +641,10 @@ pub fn lsr(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// storing the result in the accumulator
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -535,7 +652,7 @@ pub fn lsr(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.write_byte(0x0000, 0x04);
-/// ora(&mut cpu, AddressingMode::Absolute);
+/// ora(&mut cpu, Operand::Address(0x0000));
 /// assert_eq!(cpu.registers.a, 0x04);
 /// ```
 /// # Flags
@@ -554,9 +671,8 @@ pub fn lsr(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// # See
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#ORA](https://www.masswerk.at/6502/6502_instruction_set.html#ORA)
 ///
-pub fn ora(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
+pub fn ora(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
     cpu.registers.a |= value;
     cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
@@ -568,10 +684,10 @@ pub fn ora(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// it pushes the accumulator onto the stack
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -579,7 +695,7 @@ pub fn ora(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.registers.a = 0x04;
-/// pha(&mut cpu, AddressingMode::Implied);
+/// pha(&mut cpu, Operand::None);
 /// //assert_eq!(cpu.read_byte(0x01FF), 0x04);
 /// ```
 /// # Flags
@@ -590,9 +706,8 @@ pub fn ora(cpu: &mut Cpu6502, mode: AddressingMode) {
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#PHA](https://www.masswerk.at/6502/6502_instruction_set.html#PHA)
 /// # Note
 /// need to test this
-pub fn pha(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn pha(cpu: &mut Cpu6502, _operand: Operand) {
     cpu.push_stack(cpu.registers.a);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
 ///
@@ -601,10 +716,10 @@ pub fn pha(cpu: &mut Cpu6502, _mode: AddressingMode) {
 /// it pushes the processor status onto the stack
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -612,7 +727,7 @@ pub fn pha(cpu: &mut Cpu6502, _mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.registers.status = 0x04;
-/// php(&mut cpu, AddressingMode::Implied);
+/// php(&mut cpu, Operand::None);
 /// //assert_eq!(cpu.read_byte(0x01FF), 0x04);
 /// ```
 /// # Flags
@@ -623,9 +738,9 @@ pub fn pha(cpu: &mut Cpu6502, _mode: AddressingMode) {
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#PHP](https://www.masswerk.at/6502/6502_instruction_set.html#PHP)
 /// # Note
 /// need to test this
-pub fn php(cpu: &mut Cpu6502, _mode: AddressingMode) {
-    cpu.push_stack(cpu.registers.status | Flag::Break as Byte);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+pub fn php(cpu: &mut Cpu6502, _operand: Operand) {
+    // the unused bit always reads as one, even after PLP pulled it clear
+    cpu.push_stack(cpu.registers.status | Flag::Break as Byte | Flag::Unused as Byte);
 }
 
 ///
@@ -634,10 +749,10 @@ pub fn php(cpu: &mut Cpu6502, _mode: AddressingMode) {
 /// it pulls the accumulator from the stack
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
@@ -645,7 +760,7 @@ pub fn php(cpu: &mut Cpu6502, _mode: AddressingMode) {
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
 /// cpu.write_byte(0x01FF, 0x04);
-/// pla(&mut cpu, AddressingMode::Implied);
+/// pla(&mut cpu, Operand::None);
 /// //assert_eq!(cpu.registers.a, 0x04);
 /// ```
 /// # Flags
@@ -657,11 +772,10 @@ pub fn php(cpu: &mut Cpu6502, _mode: AddressingMode) {
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#PLA](https://www.masswerk.at/6502/6502_instruction_set.html#PLA)
 /// # Note
 /// need to test this
-pub fn pla(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn pla(cpu: &mut Cpu6502, _operand: Operand) {
     cpu.registers.a = cpu.pop_stack();
     cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
 ///
@@ -670,31 +784,30 @@ pub fn pla(cpu: &mut Cpu6502, _mode: AddressingMode) {
 /// it pulls the processor status from the stack
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
-/// use emul::cpu::addressing_mode::AddressingMode;
+/// use emul::cpu::operand::Operand;
 /// use emul::cpu::cpu_6502::Cpu6502;
 /// use emul::cpu::flag::Flag;
 /// use emul::memory::ram::Ram;
 /// use emul::cpu::function::plp;
 ///
 /// let mut cpu = Cpu6502::new(Ram::new());
-/// cpu.write_byte(0x01FF, 0x04);
-/// plp(&mut cpu, AddressingMode::Implied);
-/// //assert_eq!(cpu.registers.status, 0x04);
+/// cpu.registers.sp = 0xFE;
+/// cpu.write_byte(0x01FF, 0x14);
+/// plp(&mut cpu, Operand::None);
+/// assert_eq!(cpu.registers.status, 0x24);
 /// ```
 /// # Flags
-/// * `None`
+/// * `All` - but the break bit, which is cleared, and the unused bit, which is set
 /// # Addressing Mode
 /// * `Implied` - pull processor status
 /// # See
 /// * [https://www.masswerk.at/6502/6502_instruction_set.html#PLP](https://www.masswerk.at/6502/6502_instruction_set.html#PLP)
-/// # Note
-/// need to test this
-pub fn plp(cpu: &mut Cpu6502, _mode: AddressingMode) {
-    cpu.registers.status = cpu.pop_stack();
-    cpu.registers.pc += 1
+pub fn plp(cpu: &mut Cpu6502, _operand: Operand) {
+    // the break and unused bits only exist on the stack
+    cpu.registers.status = (cpu.pop_stack() & !(Flag::Break as Byte)) | Flag::Unused as Byte;
 }
 
 ///
@@ -702,440 +815,462 @@ pub fn plp(cpu: &mut Cpu6502, _mode: AddressingMode) {
 ///
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
 /// ```
 ///
-pub fn rol(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let mut value = cpu.read_byte(address);
+pub fn rol(cpu: &mut Cpu6502, operand: Operand) {
+    let mut value = read_operand(cpu, operand);
     let carry = cpu.registers.status & Flag::Carry as Byte != 0;
     cpu.set_flag(Flag::Carry, value & 0x80 != 0);
     value <<= 1;
     if carry {
         value |= 1;
     }
-
-    if let AddressingMode::Immediate = mode {
-        cpu.registers.a = value;
-    } else {
-        cpu.write_byte(address, value);
-    }
+    write_operand(cpu, operand, value);
 
     cpu.set_flag(Flag::Zero, value == 0);
     cpu.set_flag(Flag::Negative, value & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn ror(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let mut value = cpu.read_byte(address);
+pub fn ror(cpu: &mut Cpu6502, operand: Operand) {
+    let mut value = read_operand(cpu, operand);
     let carry = cpu.registers.status & Flag::Carry as Byte != 0;
     cpu.set_flag(Flag::Carry, value & 0x01 != 0);
     value >>= 1;
     if carry {
         value |= 0x80;
     }
-
-    if let AddressingMode::Immediate = mode {
-        cpu.registers.a = value;
-    } else {
-        cpu.write_byte(address, value);
-    }
+    write_operand(cpu, operand, value);
 
     cpu.set_flag(Flag::Zero, value == 0);
     cpu.set_flag(Flag::Negative, value & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn rti(cpu: &mut Cpu6502, _mode: AddressingMode) {
-    cpu.registers.status = cpu.pop_stack();
-    cpu.registers.pc = cpu.pop_word_stack();
+///
+/// # ROR (revision A)
+/// the first 6502 revision has no working ROR,
+/// the opcodes shift left like ASL, shift a zero in and leave the carry untouched
+///
+/// # Arguments
+/// * `cpu` - the cpu
+/// * `operand` - the decoded operand
+///
+/// # See
+/// * [https://www.pagetable.com/?p=406](https://www.pagetable.com/?p=406)
+///
+pub fn ror_rev_a(cpu: &mut Cpu6502, operand: Operand) {
+    let result = read_operand(cpu, operand) << 1;
+    write_operand(cpu, operand, result);
+    cpu.set_flag(Flag::Zero, result == 0);
+    cpu.set_flag(Flag::Negative, result & 0x80 != 0);
 }
 
-pub fn rts(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn rti(cpu: &mut Cpu6502, _operand: Operand) {
+    // the break and unused bits only exist on the stack
+    cpu.registers.status = (cpu.pop_stack() & !(Flag::Break as Byte)) | Flag::Unused as Byte;
     cpu.registers.pc = cpu.pop_word_stack();
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn sbc(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    let carry = cpu.registers.status & Flag::Carry as Byte != 0;
-    let result = cpu.registers.a.wrapping_sub(value).wrapping_sub(!carry as Byte);
-    cpu.set_flag(Flag::Carry, result <= cpu.registers.a);
-    cpu.set_flag(Flag::Zero, result == 0);
-    cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.set_flag(Flag::Overflow, (cpu.registers.a ^ result) & (value ^ result) & 0x80 != 0);
-    cpu.registers.a = result;
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+pub fn rts(cpu: &mut Cpu6502, _operand: Operand) {
+    // JSR pushed the address of its last byte
+    cpu.registers.pc = cpu.pop_word_stack().wrapping_add(1);
+}
+
+pub fn sbc(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    subtract_with_carry(cpu, value);
 }
 
-pub fn sta(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    cpu.write_byte(address, cpu.registers.a);
+///
+/// subtract a value and the borrow from the accumulator, shared by SBC and ISC
+///
+fn subtract_with_carry(cpu: &mut Cpu6502, value: Byte) {
+    if decimal_enabled(cpu) {
+        sbc_decimal(cpu, value);
+    } else {
+        let borrow = !cpu.get_flag(Flag::Carry) as i16;
+        let difference = cpu.registers.a as i16 - value as i16 - borrow;
+        let result = difference as Byte;
+        cpu.set_flag(Flag::Carry, difference >= 0);
+        cpu.set_flag(Flag::Zero, result == 0);
+        cpu.set_flag(Flag::Negative, result & 0x80 != 0);
+        // the operands have different signs and the sign of the result is not the one of the accumulator
+        cpu.set_flag(Flag::Overflow, (cpu.registers.a ^ value) & (cpu.registers.a ^ result) & 0x80 != 0);
+        cpu.registers.a = result;
+    }
+}
+
+pub fn sta(cpu: &mut Cpu6502, operand: Operand) {
+    cpu.write_byte(operand.address(), cpu.registers.a);
 }
 
-pub fn stx(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    cpu.write_byte(address, cpu.registers.x);
+pub fn stx(cpu: &mut Cpu6502, operand: Operand) {
+    cpu.write_byte(operand.address(), cpu.registers.x);
 }
 
-pub fn sty(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    cpu.write_byte(address, cpu.registers.y);
+pub fn sty(cpu: &mut Cpu6502, operand: Operand) {
+    cpu.write_byte(operand.address(), cpu.registers.y);
 }
 
-pub fn tax(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn tax(cpu: &mut Cpu6502, _operand: Operand) {
     let value = cpu.registers.a;
     cpu.registers.x = value;
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
     cpu.set_flag(Flag::Zero, value == 0);
     cpu.set_flag(Flag::Negative, value & 0x80 != 0);
 }
 
-pub fn tay(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn tay(cpu: &mut Cpu6502, _operand: Operand) {
     let value = cpu.registers.a;
     cpu.registers.y = value;
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
     cpu.set_flag(Flag::Zero, value == 0);
     cpu.set_flag(Flag::Negative, value & 0x80 != 0);
 }
 
-pub fn tsx(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn tsx(cpu: &mut Cpu6502, _operand: Operand) {
     let value = cpu.registers.sp;
     cpu.registers.x = value;
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
     cpu.set_flag(Flag::Zero, value == 0);
     cpu.set_flag(Flag::Negative, value & 0x80 != 0);
 }
 
-pub fn txa(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn txa(cpu: &mut Cpu6502, _operand: Operand) {
     let value = cpu.registers.x;
     cpu.registers.a = value;
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
     cpu.set_flag(Flag::Zero, value == 0);
     cpu.set_flag(Flag::Negative, value & 0x80 != 0);
 }
 
-pub fn txs(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn txs(cpu: &mut Cpu6502, _operand: Operand) {
     let value = cpu.registers.x;
     cpu.registers.sp = value;
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn tya(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn tya(cpu: &mut Cpu6502, _operand: Operand) {
     let value = cpu.registers.y;
     cpu.registers.a = value;
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
     cpu.set_flag(Flag::Zero, value == 0);
     cpu.set_flag(Flag::Negative, value & 0x80 != 0);
 }
 
-pub fn bcc(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let carry = cpu.registers.status & Flag::Carry as Byte != 0;
-    if !carry {
+///
+/// common part of the branch instructions,
+/// a taken branch costs one more cycle, and another one if it lands on a different page
+///
+fn branch(cpu: &mut Cpu6502, address: Address, condition: bool) {
+    if condition {
+        cpu.extra_cycles += 1;
+        if cpu.registers.pc & 0xFF00 != address & 0xFF00 {
+            cpu.extra_cycles += 1;
+        }
         cpu.registers.pc = address;
-    } else {
-        cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
     }
 }
 
-pub fn bcs(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let carry = cpu.registers.status & Flag::Carry as Byte != 0;
-    if carry {
-        cpu.registers.pc = address;
-    } else {
-        cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
-    }
+pub fn bcc(cpu: &mut Cpu6502, operand: Operand) {
+    let condition = !cpu.get_flag(Flag::Carry);
+    branch(cpu, operand.address(), condition);
 }
 
-pub fn beq(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let zero = cpu.registers.status & Flag::Zero as Byte != 0;
-    if zero {
-        cpu.registers.pc = address;
-    } else {
-        cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
-    }
+pub fn bcs(cpu: &mut Cpu6502, operand: Operand) {
+    let condition = cpu.get_flag(Flag::Carry);
+    branch(cpu, operand.address(), condition);
 }
 
-pub fn bmi(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let negative = cpu.registers.status & Flag::Negative as Byte != 0;
-    if negative {
-        cpu.registers.pc = address;
-    } else {
-        cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
-    }
+pub fn beq(cpu: &mut Cpu6502, operand: Operand) {
+    let condition = cpu.get_flag(Flag::Zero);
+    branch(cpu, operand.address(), condition);
 }
 
-pub fn bne(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let zero = cpu.registers.status & Flag::Zero as Byte != 0;
-    if !zero {
-        cpu.registers.pc = address;
-    } else {
-        cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
-    }
+pub fn bmi(cpu: &mut Cpu6502, operand: Operand) {
+    let condition = cpu.get_flag(Flag::Negative);
+    branch(cpu, operand.address(), condition);
+}
 
+pub fn bne(cpu: &mut Cpu6502, operand: Operand) {
+    let condition = !cpu.get_flag(Flag::Zero);
+    branch(cpu, operand.address(), condition);
 }
 
-pub fn bpl(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let negative = cpu.registers.status & Flag::Negative as Byte != 0;
-    if !negative {
-        cpu.registers.pc = address;
-    } else {
-        cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
-    }
+pub fn bpl(cpu: &mut Cpu6502, operand: Operand) {
+    let condition = !cpu.get_flag(Flag::Negative);
+    branch(cpu, operand.address(), condition);
 }
 
-pub fn bvc(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let overflow = cpu.registers.status & Flag::Overflow as Byte != 0;
-    if !overflow {
-        cpu.registers.pc = address;
-    } else {
-        cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
-    }
+pub fn bvc(cpu: &mut Cpu6502, operand: Operand) {
+    let condition = !cpu.get_flag(Flag::Overflow);
+    branch(cpu, operand.address(), condition);
 }
 
-pub fn bvs(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let overflow = cpu.registers.status & Flag::Overflow as Byte != 0;
-    if overflow {
-        cpu.registers.pc = address;
-    } else {
-        cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
-    }
+pub fn bvs(cpu: &mut Cpu6502, operand: Operand) {
+    let condition = cpu.get_flag(Flag::Overflow);
+    branch(cpu, operand.address(), condition);
 }
 
-pub fn clc(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn clc(cpu: &mut Cpu6502, _operand: Operand) {
     cpu.set_flag(Flag::Carry, false);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn cld(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn cld(cpu: &mut Cpu6502, _operand: Operand) {
     cpu.set_flag(Flag::Decimal, false);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn cli(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn cli(cpu: &mut Cpu6502, _operand: Operand) {
     cpu.set_flag(Flag::Interrupt, false);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn clv(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn clv(cpu: &mut Cpu6502, _operand: Operand) {
     cpu.set_flag(Flag::Overflow, false);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn sec(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn sec(cpu: &mut Cpu6502, _operand: Operand) {
     cpu.set_flag(Flag::Carry, true);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn sed(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn sed(cpu: &mut Cpu6502, _operand: Operand) {
     cpu.set_flag(Flag::Decimal, true);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn sei(cpu: &mut Cpu6502, _mode: AddressingMode) {
+pub fn sei(cpu: &mut Cpu6502, _operand: Operand) {
     cpu.set_flag(Flag::Interrupt, true);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn nop(cpu: &mut Cpu6502, _mode: AddressingMode) {
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+pub fn nop(_cpu: &mut Cpu6502, _operand: Operand) {
 }
 
-pub fn bit(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
+pub fn bit(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
     let a = cpu.registers.a;
     let result = a & value;
     cpu.set_flag(Flag::Zero, result == 0);
-    cpu.set_flag(Flag::Negative, value & 0b10000000 != 0);
-    cpu.set_flag(Flag::Overflow, value & 0b01000000 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+    // BIT #imm of the 65C02 only affects the zero flag
+    if !matches!(operand, Operand::Immediate(_)) {
+        cpu.set_flag(Flag::Negative, value & 0b10000000 != 0);
+        cpu.set_flag(Flag::Overflow, value & 0b01000000 != 0);
+    }
 }
 
-pub fn kil(cpu: &mut Cpu6502, _mode: AddressingMode) {
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+pub fn kil(_cpu: &mut Cpu6502, _operand: Operand) {
 }
 
-pub fn lax(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
+pub fn lax(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
     cpu.registers.a = value;
     cpu.registers.x = value;
     cpu.set_flag(Flag::Zero, value == 0);
     cpu.set_flag(Flag::Negative, value & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn sax(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
+pub fn sax(cpu: &mut Cpu6502, operand: Operand) {
+    let address = operand.address();
     let value = cpu.registers.a & cpu.registers.x;
     cpu.write_byte(address, value);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn dcp(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    let result = value.wrapping_sub(1);
-    cpu.write_byte(address, result);
-    let a = cpu.registers.a;
-    let result = a.wrapping_sub(result);
-    cpu.set_flag(Flag::Carry, a >= result);
+///
+/// # DCP
+/// undocumented, decrements the memory then compares the result with the accumulator
+///
+pub fn dcp(cpu: &mut Cpu6502, operand: Operand) {
+    let address = operand.address();
+    let value = cpu.read_byte(address).wrapping_sub(1);
+    cpu.write_byte(address, value);
+    let result = cpu.registers.a.wrapping_sub(value);
+    cpu.set_flag(Flag::Carry, cpu.registers.a >= value);
     cpu.set_flag(Flag::Zero, result == 0);
     cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn isc(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    let result = value.wrapping_sub(1);
-    cpu.write_byte(address, result);
-    let a = cpu.registers.a;
-    let result = a.wrapping_sub(result);
-    cpu.set_flag(Flag::Carry, a >= result);
-    cpu.set_flag(Flag::Zero, result == 0);
-    cpu.set_flag(Flag::Negative, result & 0x80 != 0);
+///
+/// # ISC
+/// undocumented, increments the memory then subtracts the result from the accumulator like SBC
+///
+pub fn isc(cpu: &mut Cpu6502, operand: Operand) {
+    let address = operand.address();
+    let value = cpu.read_byte(address).wrapping_add(1);
+    cpu.write_byte(address, value);
+    subtract_with_carry(cpu, value);
 }
 
-pub fn rla(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
+///
+/// # RLA
+/// undocumented, rotates the memory left like ROL then ANDs the result into the accumulator
+///
+pub fn rla(cpu: &mut Cpu6502, operand: Operand) {
+    let address = operand.address();
     let value = cpu.read_byte(address);
-    let result = value << 1;
+    let carry = cpu.get_flag(Flag::Carry) as Byte;
+    let result = (value << 1) | carry;
     cpu.write_byte(address, result);
-    let a = cpu.registers.a;
-    let result = a & result;
+    cpu.registers.a &= result;
     cpu.set_flag(Flag::Carry, value & 0x80 != 0);
-    cpu.set_flag(Flag::Zero, result == 0);
-    cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+    cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
+    cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
 }
 
-pub fn rra(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
+///
+/// # RRA
+/// undocumented, rotates the memory right like ROR then adds the result to the accumulator like ADC,
+/// the carry out of the rotation is the carry in of the addition
+///
+pub fn rra(cpu: &mut Cpu6502, operand: Operand) {
+    let address = operand.address();
     let value = cpu.read_byte(address);
-    let result = value >> 1;
+    let carry = cpu.get_flag(Flag::Carry) as Byte;
+    let result = (value >> 1) | (carry << 7);
     cpu.write_byte(address, result);
-    let a = cpu.registers.a;
-    let result = a.wrapping_add(result);
     cpu.set_flag(Flag::Carry, value & 0x01 != 0);
-    cpu.set_flag(Flag::Zero, result == 0);
-    cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+    add_with_carry(cpu, result);
 }
 
-pub fn slo(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
+///
+/// # SLO
+/// undocumented, shifts the memory left like ASL then ORs the result into the accumulator
+///
+pub fn slo(cpu: &mut Cpu6502, operand: Operand) {
+    let address = operand.address();
     let value = cpu.read_byte(address);
     let result = value << 1;
     cpu.write_byte(address, result);
-    let a = cpu.registers.a;
-    let result = a | result;
+    cpu.registers.a |= result;
     cpu.set_flag(Flag::Carry, value & 0x80 != 0);
-    cpu.set_flag(Flag::Zero, result == 0);
-    cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+    cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
+    cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
 }
 
-pub fn sre(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
+///
+/// # SRE
+/// undocumented, shifts the memory right like LSR then EORs the result into the accumulator
+///
+pub fn sre(cpu: &mut Cpu6502, operand: Operand) {
+    let address = operand.address();
     let value = cpu.read_byte(address);
     let result = value >> 1;
     cpu.write_byte(address, result);
-    let a = cpu.registers.a;
-    let result = a ^ result;
+    cpu.registers.a ^= result;
     cpu.set_flag(Flag::Carry, value & 0x01 != 0);
-    cpu.set_flag(Flag::Zero, result == 0);
-    cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+    cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
+    cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
 }
 
-pub fn anc(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    let result = cpu.registers.a & value;
-    cpu.set_flag(Flag::Carry, result & 0x80 != 0);
-    cpu.set_flag(Flag::Zero, result == 0);
-    cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+///
+/// # ANC
+/// undocumented, ANDs the immediate value into the accumulator and copies bit 7 into the carry
+///
+pub fn anc(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    cpu.registers.a &= value;
+    cpu.set_flag(Flag::Carry, cpu.registers.a & 0x80 != 0);
+    cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
+    cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
 }
 
-pub fn alr(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    let result = cpu.registers.a & value;
-    cpu.set_flag(Flag::Carry, result & 0x01 != 0);
+///
+/// # ARR
+/// undocumented, ANDs the immediate value into the accumulator then rotates it right,
+/// the carry and overflow come from bits 6 and 5 of the result,
+/// in decimal mode the result is adjusted like a BCD number and N, Z and V come from the binary rotation
+///
+/// # See
+/// * [https://csdb.dk/release/?id=198357](https://csdb.dk/release/?id=198357)
+///
+pub fn arr(cpu: &mut Cpu6502, operand: Operand) {
+    let value = cpu.registers.a & read_operand(cpu, operand);
+    let carry = cpu.get_flag(Flag::Carry) as Byte;
+    let mut result = (value >> 1) | (carry << 7);
     cpu.set_flag(Flag::Zero, result == 0);
     cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+    if decimal_enabled(cpu) {
+        cpu.set_flag(Flag::Overflow, (result ^ value) & 0x40 != 0);
+        if (value & 0x0F) + (value & 0x01) > 0x05 {
+            result = (result & 0xF0) | (result.wrapping_add(0x06) & 0x0F);
+        }
+        let high_adjust = (value & 0xF0) as u16 + (value & 0x10) as u16 > 0x50;
+        if high_adjust {
+            result = result.wrapping_add(0x60);
+        }
+        cpu.set_flag(Flag::Carry, high_adjust);
+    } else {
+        cpu.set_flag(Flag::Carry, result & 0x40 != 0);
+        cpu.set_flag(Flag::Overflow, ((result >> 6) ^ (result >> 5)) & 0x01 != 0);
+    }
+    cpu.registers.a = result;
 }
 
-pub fn arr(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    let result = cpu.registers.a & value;
-    cpu.set_flag(Flag::Carry, result & 0x01 != 0);
-    cpu.set_flag(Flag::Zero, result == 0);
-    cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+///
+/// # XAA
+/// undocumented and unstable, also known as ANE,
+/// the accumulator is ORed with the magic constant of the chip then ANDed with X and the immediate value
+///
+pub fn xaa(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    cpu.registers.a = (cpu.registers.a | cpu.magic_constant) & cpu.registers.x & value;
+    cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
+    cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
 }
 
-pub fn xaa(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    let result = cpu.registers.x & value;
-    cpu.set_flag(Flag::Carry, result & 0x80 != 0);
+///
+/// # LXA
+/// undocumented and unstable, also known as LAX immediate,
+/// the accumulator is ORed with the magic constant of the chip then ANDed with the immediate value
+/// and the result is copied in A and X
+///
+pub fn lxa(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    let result = (cpu.registers.a | cpu.magic_constant) & value;
+    cpu.registers.a = result;
+    cpu.registers.x = result;
     cpu.set_flag(Flag::Zero, result == 0);
     cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn axs(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let _value = cpu.read_byte(address);
-    let result = cpu.registers.x & cpu.registers.a;
-    cpu.set_flag(Flag::Carry, result & 0x80 != 0);
-    cpu.set_flag(Flag::Zero, result == 0);
-    cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+///
+/// # AXS
+/// undocumented, also known as SBX,
+/// X gets A AND X minus the immediate value, the carry is set like CMP and the decimal flag is ignored
+///
+pub fn axs(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    let and = cpu.registers.a & cpu.registers.x;
+    cpu.registers.x = and.wrapping_sub(value);
+    cpu.set_flag(Flag::Carry, and >= value);
+    cpu.set_flag(Flag::Zero, cpu.registers.x == 0);
+    cpu.set_flag(Flag::Negative, cpu.registers.x & 0x80 != 0);
 }
 
-pub fn ahx(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let _value = cpu.read_byte(address);
-    let result = cpu.registers.x & cpu.registers.a;
-    cpu.write_byte(address, result);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+///
+/// common part of the unstable stores,
+/// the value is ANDed with the high byte of the address before indexing plus one,
+/// and when the indexing crosses a page the stored value also replaces the high byte of the address
+///
+/// # See
+/// * [https://csdb.dk/release/?id=198357](https://csdb.dk/release/?id=198357)
+///
+fn store_and_high(cpu: &mut Cpu6502, operand: Operand, value: Byte, index: Byte) {
+    let address = operand.address();
+    let base = address.wrapping_sub(index as Address);
+    let value = value & ((base >> 8) as Byte).wrapping_add(1);
+    let address = if base & 0xFF00 != address & 0xFF00 {
+        (value as Address) << 8 | (address & 0x00FF)
+    } else {
+        address
+    };
+    cpu.write_byte(address, value);
 }
 
-pub fn asl(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let value: Byte;
-    let result: Byte;
+///
+/// # AHX
+/// undocumented and unstable, also known as SHA, stores A AND X AND the high byte of the address plus one
+///
+pub fn ahx(cpu: &mut Cpu6502, operand: Operand) {
+    let value = cpu.registers.a & cpu.registers.x;
+    store_and_high(cpu, operand, value, cpu.registers.y);
+}
 
-    match mode {
-        AddressingMode::Accumulator => {
-            value = cpu.registers.a;
-            result = value << 1;
-            cpu.registers.a = result;
-        }
-        _ => {
-            let address = mode.get_address(cpu);
-            value = cpu.read_byte(address);
-            result = value << 1;
-            cpu.write_byte(address, result);
-        }
-    }
+pub fn asl(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    let result = value << 1;
+    write_operand(cpu, operand, result);
     cpu.set_flag(Flag::Carry, value & 0x80 != 0);
     cpu.set_flag(Flag::Zero, result == 0);
     cpu.set_flag(Flag::Negative, result & 0x80 != 0);
@@ -1146,126 +1281,234 @@ pub fn asl(cpu: &mut Cpu6502, mode: AddressingMode) {
 ///
 /// # Arguments
 /// * `cpu` - the cpu
-/// * `mode` - the addressing mode
+/// * `operand` - the decoded operand
 /// # Example
-
 ///
 ///
-pub fn jsr(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    cpu.push_word_stack(cpu.registers.pc);
-    cpu.registers.pc = address;
+pub fn jsr(cpu: &mut Cpu6502, operand: Operand) {
+    // the pushed address is the last byte of the instruction, RTS adds one
+    cpu.push_word_stack(cpu.registers.pc.wrapping_sub(1));
+    cpu.registers.pc = operand.address();
 }
 
-pub fn and(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
+pub fn and(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
     let a = cpu.registers.a;
     let result = a & value;
     cpu.registers.a = result;
     cpu.set_flag(Flag::Zero, result == 0);
     cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn tas(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let _value = cpu.read_byte(address);
-    let result = cpu.registers.x & cpu.registers.a;
-    cpu.write_byte(address, result);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+///
+/// # TAS
+/// undocumented and unstable, also known as SHS,
+/// the stack pointer gets A AND X then it is stored like AHX
+///
+pub fn tas(cpu: &mut Cpu6502, operand: Operand) {
+    cpu.registers.sp = cpu.registers.a & cpu.registers.x;
+    store_and_high(cpu, operand, cpu.registers.sp, cpu.registers.y);
 }
 
-pub fn shx(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let _value = cpu.read_byte(address);
-    let result = cpu.registers.x & cpu.registers.a;
-    cpu.write_byte(address, result);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+///
+/// # SHX
+/// undocumented and unstable, stores X AND the high byte of the address plus one
+///
+pub fn shx(cpu: &mut Cpu6502, operand: Operand) {
+    store_and_high(cpu, operand, cpu.registers.x, cpu.registers.y);
 }
 
-pub fn shy(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let _value = cpu.read_byte(address);
-    let result = cpu.registers.y & cpu.registers.a;
-    cpu.write_byte(address, result);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+///
+/// # SHY
+/// undocumented and unstable, stores Y AND the high byte of the address plus one
+///
+pub fn shy(cpu: &mut Cpu6502, operand: Operand) {
+    store_and_high(cpu, operand, cpu.registers.y, cpu.registers.x);
 }
 
-pub fn las(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let _value = cpu.read_byte(address);
-    let result = cpu.registers.x & cpu.registers.a;
-    cpu.write_byte(address, result);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+///
+/// # LAS
+/// undocumented, also known as LAR, A, X and the stack pointer get the memory AND the stack pointer
+///
+pub fn las(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand) & cpu.registers.sp;
+    cpu.registers.a = value;
+    cpu.registers.x = value;
+    cpu.registers.sp = value;
+    cpu.set_flag(Flag::Zero, value == 0);
+    cpu.set_flag(Flag::Negative, value & 0x80 != 0);
 }
 
-pub fn cpy(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
+pub fn cpy(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
     let a = cpu.registers.y;
     let result = a.wrapping_sub(value);
     cpu.set_flag(Flag::Carry, a >= value);
     cpu.set_flag(Flag::Zero, result == 0);
     cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn cpx(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
+pub fn cpx(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
     let a = cpu.registers.x;
     let result = a.wrapping_sub(value);
     cpu.set_flag(Flag::Carry, a >= value);
     cpu.set_flag(Flag::Zero, result == 0);
     cpu.set_flag(Flag::Negative, result & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn iny(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    cpu.registers.y = value.wrapping_add(1);
+pub fn iny(cpu: &mut Cpu6502, _operand: Operand) {
+    cpu.registers.y = cpu.registers.y.wrapping_add(1);
     cpu.set_flag(Flag::Zero, cpu.registers.y == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.y & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn inx(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    cpu.registers.x = value.wrapping_add(1);
+pub fn inx(cpu: &mut Cpu6502, _operand: Operand) {
+    cpu.registers.x = cpu.registers.x.wrapping_add(1);
     cpu.set_flag(Flag::Zero, cpu.registers.x == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.x & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn dey(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    cpu.registers.y = value.wrapping_sub(1);
+pub fn dey(cpu: &mut Cpu6502, _operand: Operand) {
+    cpu.registers.y = cpu.registers.y.wrapping_sub(1);
     cpu.set_flag(Flag::Zero, cpu.registers.y == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.y & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn dex(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
-    cpu.registers.x = value.wrapping_sub(1);
+pub fn dex(cpu: &mut Cpu6502, _operand: Operand) {
+    cpu.registers.x = cpu.registers.x.wrapping_sub(1);
     cpu.set_flag(Flag::Zero, cpu.registers.x == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.x & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
-pub fn asr(cpu: &mut Cpu6502, mode: AddressingMode) {
-    let address = mode.get_address(cpu);
-    let value = cpu.read_byte(address);
+pub fn asr(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
     let result = cpu.registers.a & value;
     cpu.set_flag(Flag::Carry, result & 0x01 != 0);
     cpu.registers.a = result >> 1;
     cpu.set_flag(Flag::Zero, cpu.registers.a == 0);
     cpu.set_flag(Flag::Negative, cpu.registers.a & 0x80 != 0);
-    cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
 }
 
+///
+/// # BRA
+/// this is the branch always instruction of the 65C02
+///
+/// # See
+/// * [http://www.6502.org/tutorials/65c02opcodes.html](http://www.6502.org/tutorials/65c02opcodes.html)
+///
+pub fn bra(cpu: &mut Cpu6502, operand: Operand) {
+    branch(cpu, operand.address(), true);
+}
+
+pub fn phx(cpu: &mut Cpu6502, _operand: Operand) {
+    cpu.push_stack(cpu.registers.x);
+}
+
+pub fn phy(cpu: &mut Cpu6502, _operand: Operand) {
+    cpu.push_stack(cpu.registers.y);
+}
+
+pub fn plx(cpu: &mut Cpu6502, _operand: Operand) {
+    cpu.registers.x = cpu.pop_stack();
+    cpu.set_flag(Flag::Zero, cpu.registers.x == 0);
+    cpu.set_flag(Flag::Negative, cpu.registers.x & 0x80 != 0);
+}
+
+pub fn ply(cpu: &mut Cpu6502, _operand: Operand) {
+    cpu.registers.y = cpu.pop_stack();
+    cpu.set_flag(Flag::Zero, cpu.registers.y == 0);
+    cpu.set_flag(Flag::Negative, cpu.registers.y & 0x80 != 0);
+}
+
+///
+/// # STZ
+/// this is the store zero instruction of the 65C02
+///
+pub fn stz(cpu: &mut Cpu6502, operand: Operand) {
+    cpu.write_byte(operand.address(), 0);
+}
+
+///
+/// # TSB
+/// this is the test and set bits instruction of the 65C02,
+/// the zero flag is set like BIT then the bits of the accumulator are set in memory
+///
+pub fn tsb(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    cpu.set_flag(Flag::Zero, value & cpu.registers.a == 0);
+    write_operand(cpu, operand, value | cpu.registers.a);
+}
+
+///
+/// # TRB
+/// this is the test and reset bits instruction of the 65C02,
+/// the zero flag is set like BIT then the bits of the accumulator are cleared in memory
+///
+pub fn trb(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    cpu.set_flag(Flag::Zero, value & cpu.registers.a == 0);
+    write_operand(cpu, operand, value & !cpu.registers.a);
+}
+
+///
+/// # WAI
+/// this is the wait for interrupt instruction of the 65C02,
+/// the cpu sleeps until an IRQ or a NMI is signaled
+///
+pub fn wai(cpu: &mut Cpu6502, _operand: Operand) {
+    cpu.waiting = true;
+}
+
+///
+/// # STP
+/// this is the stop instruction of the 65C02,
+/// the cpu stops until it is reset
+///
+pub fn stp(cpu: &mut Cpu6502, _operand: Operand) {
+    cpu.stopped = true;
+}
+
+///
+/// # RMB
+/// this is the reset memory bit instruction of the Rockwell and WDC 65C02,
+/// the opcode selects the bit to clear in a zero page location
+///
+pub fn rmb<const BIT: u8>(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    write_operand(cpu, operand, value & !(1 << BIT));
+}
+
+///
+/// # SMB
+/// this is the set memory bit instruction of the Rockwell and WDC 65C02,
+/// the opcode selects the bit to set in a zero page location
+///
+pub fn smb<const BIT: u8>(cpu: &mut Cpu6502, operand: Operand) {
+    let value = read_operand(cpu, operand);
+    write_operand(cpu, operand, value | (1 << BIT));
+}
+
+///
+/// # BBR
+/// this is the branch on bit reset instruction of the Rockwell and WDC 65C02,
+/// it branches if the selected bit of a zero page location is clear
+///
+pub fn bbr<const BIT: u8>(cpu: &mut Cpu6502, operand: Operand) {
+    if let Operand::ZeroPageRelative(address, target) = operand {
+        let value = cpu.read_byte(address);
+        branch(cpu, target, value & (1 << BIT) == 0);
+    }
+}
+
+///
+/// # BBS
+/// this is the branch on bit set instruction of the Rockwell and WDC 65C02,
+/// it branches if the selected bit of a zero page location is set
+///
+pub fn bbs<const BIT: u8>(cpu: &mut Cpu6502, operand: Operand) {
+    if let Operand::ZeroPageRelative(address, target) = operand {
+        let value = cpu.read_byte(address);
+        branch(cpu, target, value & (1 << BIT) != 0);
+    }
+}
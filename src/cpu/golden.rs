@@ -0,0 +1,249 @@
+//!
+//! File: cpu/golden.rs
+//! The golden module contains the comparison of the execution with a reference trace,
+//! like nestest.log or a trace exported from another emulator in the same format.
+//! Every executed instruction is traced and compared field by field with the next
+//! line of the reference, the run stops at the first field that differs.
+//! Fields missing from the reference, like the cycle count, are not compared
+//! and extra columns, like the PPU position of nestest.log, are ignored.
+//!
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+use crate::cpu::cpu_6502::{Cpu6502, ExecutionState};
+use crate::error::EmulatorError;
+use crate::util::types::{Byte, Address};
+
+///
+/// the fields of one trace line
+///
+#[derive(Clone, PartialEq, Debug)]
+pub struct TraceRecord {
+    pub pc: Address,
+    pub bytes: Vec<Byte>,
+    // without the star marking undocumented opcodes
+    pub mnemonic: String,
+    pub a: Option<Byte>,
+    pub x: Option<Byte>,
+    pub y: Option<Byte>,
+    pub p: Option<Byte>,
+    pub sp: Option<Byte>,
+    pub cycles: Option<u64>,
+}
+
+impl TraceRecord {
+    ///
+    /// parse a line made of the program counter, the raw bytes, the disassembly
+    /// and `NAME:value` register fields, returns None if the line is not a trace line
+    ///
+    pub fn parse(line: &str) -> Option<TraceRecord> {
+        let mut words = line.split_whitespace().peekable();
+        let pc = Address::from_str_radix(words.next()?, 16).ok()?;
+        let mut bytes = Vec::new();
+        while let Some(word) = words.peek() {
+            if word.len() != 2 {
+                break;
+            }
+            match Byte::from_str_radix(word, 16) {
+                Ok(byte) => bytes.push(byte),
+                Err(_) => break,
+            }
+            words.next();
+        }
+        let mnemonic = words.next()?.trim_start_matches('*').to_owned();
+        let mut record = TraceRecord {
+            pc,
+            bytes,
+            mnemonic,
+            a: None,
+            x: None,
+            y: None,
+            p: None,
+            sp: None,
+            cycles: None,
+        };
+        for word in words {
+            let (name, value) = match word.split_once(':') {
+                Some(field) => field,
+                None => continue,
+            };
+            match name {
+                "A" => record.a = Byte::from_str_radix(value, 16).ok(),
+                "X" => record.x = Byte::from_str_radix(value, 16).ok(),
+                "Y" => record.y = Byte::from_str_radix(value, 16).ok(),
+                "P" => record.p = Byte::from_str_radix(value, 16).ok(),
+                "SP" => record.sp = Byte::from_str_radix(value, 16).ok(),
+                "CYC" => record.cycles = value.parse().ok(),
+                _ => {}
+            }
+        }
+        Some(record)
+    }
+
+    ///
+    /// name of the first field of the reference that `self` does not match
+    ///
+    fn first_difference(&self, reference: &TraceRecord) -> Option<&'static str> {
+        fn differs<T: PartialEq>(actual: &Option<T>, expected: &Option<T>) -> bool {
+            expected.is_some() && actual != expected
+        }
+
+        if self.pc != reference.pc {
+            return Some("PC");
+        }
+        if self.bytes != reference.bytes {
+            return Some("bytes");
+        }
+        if self.mnemonic != reference.mnemonic {
+            return Some("mnemonic");
+        }
+        if differs(&self.a, &reference.a) {
+            return Some("A");
+        }
+        if differs(&self.x, &reference.x) {
+            return Some("X");
+        }
+        if differs(&self.y, &reference.y) {
+            return Some("Y");
+        }
+        if differs(&self.p, &reference.p) {
+            return Some("P");
+        }
+        if differs(&self.sp, &reference.sp) {
+            return Some("SP");
+        }
+        if differs(&self.cycles, &reference.cycles) {
+            return Some("CYC");
+        }
+        None
+    }
+}
+
+///
+/// first instruction that does not match the reference
+///
+#[derive(Clone, PartialEq, Debug)]
+pub struct Divergence {
+    // line of the reference, counted from 1
+    pub line: usize,
+    // first field that differs, or "end" when the cpu stopped before the end of the reference
+    pub field: &'static str,
+    pub expected: String,
+    pub actual: String,
+    // the matching lines just before the divergence, oldest first
+    pub history: Vec<String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "divergence on {} at line {}", self.field, self.line)?;
+        for line in self.history.iter() {
+            writeln!(f, "  {}", line)?;
+        }
+        writeln!(f, "- {}", self.expected)?;
+        write!(f, "+ {}", self.actual)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum TraceComparison {
+    // every line of the reference matched, with the number of instructions compared
+    Matched(usize),
+    Diverged(Divergence),
+}
+
+///
+/// trace sink whose lines are read back by the comparison
+///
+#[derive(Clone, Default)]
+struct LineBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for LineBuffer {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl LineBuffer {
+    fn take_line(&self) -> Option<String> {
+        let data = self.0.replace(Vec::new());
+        if data.is_empty() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&data).trim_end().to_owned())
+    }
+}
+
+impl Cpu6502 {
+    ///
+    /// run the cpu from its current state and compare every instruction with the next line
+    /// of `reference`, `context` is the number of matching lines kept for the report,
+    /// the trace sink of the cpu is restored once the comparison ends
+    ///
+    pub fn compare_with_trace<R: BufRead>(&mut self, reference: R, context: usize) -> Result<TraceComparison, EmulatorError> {
+        let buffer = LineBuffer::default();
+        let previous = self.take_trace();
+        self.set_trace(Box::new(buffer.clone()));
+        let result = self.compare_lines(reference, context, &buffer);
+        self.trace = previous;
+        result
+    }
+
+    fn compare_lines<R: BufRead>(&mut self, reference: R, context: usize, buffer: &LineBuffer) -> Result<TraceComparison, EmulatorError> {
+        let mut history = VecDeque::with_capacity(context);
+        let mut compared = 0;
+        for (number, expected) in reference.lines().enumerate() {
+            let expected = expected?;
+            let expected_record = match TraceRecord::parse(&expected) {
+                Some(record) => record,
+                None if expected.trim().is_empty() => continue,
+                None => {
+                    return Err(EmulatorError::InvalidTrace { line: number + 1 });
+                }
+            };
+            // interrupts are not traced, run until the next instruction
+            let actual = loop {
+                let (state, _) = self.execute_instruction()?;
+                if let Some(line) = buffer.take_line() {
+                    break line;
+                }
+                if state != ExecutionState::Running {
+                    return Ok(TraceComparison::Diverged(Divergence {
+                        line: number + 1,
+                        field: "end",
+                        expected,
+                        actual: String::new(),
+                        history: history.into(),
+                    }));
+                }
+            };
+            let actual_record = TraceRecord::parse(&actual).ok_or(EmulatorError::InvalidTrace { line: number + 1 })?;
+            if let Some(field) = actual_record.first_difference(&expected_record) {
+                return Ok(TraceComparison::Diverged(Divergence {
+                    line: number + 1,
+                    field,
+                    expected,
+                    actual,
+                    history: history.into(),
+                }));
+            }
+            if context > 0 {
+                if history.len() == context {
+                    history.pop_front();
+                }
+                history.push_back(expected);
+            }
+            compared += 1;
+        }
+        Ok(TraceComparison::Matched(compared))
+    }
+}
@@ -1,10 +1,11 @@
-///
-/// File: cpu/instruction.rs
-/// The instruction module contains the implementation of the instruction enum and the implementation of the instruction functions.
-///
+//!
+//! File: cpu/instruction.rs
+//! The instruction module contains the implementation of the instruction enum and the implementation of the instruction functions.
+//!
 use crate::cpu::addressing_mode::AddressingMode;
 use crate::cpu::cpu_6502::Cpu6502;
 use crate::cpu::function;
+use crate::cpu::operand::Operand;
 
 #[derive(Clone, Copy)]
 pub struct Instruction<'a> {
@@ -13,7 +14,66 @@ pub struct Instruction<'a> {
     pub addressing_mode: AddressingMode,
     pub cycles: u8,
     pub length: u8,
-    pub execute: fn(&mut Cpu6502, AddressingMode),
+    pub execute: fn(&mut Cpu6502, Operand),
+}
+
+impl Instruction<'_> {
+    ///
+    /// true if the instruction takes one more cycle when its indexed address crosses a page,
+    /// only the instructions reading memory do, stores and read-modify-write always take the worst case
+    /// but for the 65C02 shifts and rotates indexed by X, listed with 6 cycles instead of 7
+    ///
+    pub fn has_page_cross_penalty(&self) -> bool {
+        let cmos_shift = matches!(self.name, "ASL" | "LSR" | "ROL" | "ROR")
+            && self.addressing_mode == AddressingMode::AbsoluteX
+            && self.cycles == 6;
+        cmos_shift
+            || matches!(
+                self.name,
+                "ADC" | "AND" | "BIT" | "CMP" | "EOR" | "LDA" | "LDX" | "LDY" | "ORA" | "SBC" | "NOP" | "LAX" | "LAS"
+            )
+    }
+
+    ///
+    /// true if the opcode is part of the documented instruction set,
+    /// the only documented NOP is $EA and $EB is an undocumented copy of SBC immediate
+    ///
+    pub fn is_documented(&self) -> bool {
+        match self.name {
+            "NOP" => return self.opcode == 0xEA,
+            "SBC" => return self.opcode != 0xEB,
+            _ => {}
+        }
+        DOCUMENTED_MNEMONICS.contains(&self.name) || CMOS_MNEMONICS.iter().any(|name| self.name.starts_with(name))
+    }
+}
+
+const DOCUMENTED_MNEMONICS: [&str; 56] = [
+    "ADC", "AND", "ASL", "BCC", "BCS", "BEQ", "BIT", "BMI", "BNE", "BPL", "BRK", "BVC", "BVS", "CLC",
+    "CLD", "CLI", "CLV", "CMP", "CPX", "CPY", "DEC", "DEX", "DEY", "EOR", "INC", "INX", "INY", "JMP",
+    "JSR", "LDA", "LDX", "LDY", "LSR", "NOP", "ORA", "PHA", "PHP", "PLA", "PLP", "ROL", "ROR", "RTI",
+    "RTS", "SBC", "SEC", "SED", "SEI", "STA", "STX", "STY", "TAX", "TAY", "TSX", "TXA", "TXS", "TYA",
+];
+
+// mnemonics added by the 65C02, the bit instructions are followed by the bit number
+const CMOS_MNEMONICS: [&str; 14] = [
+    "BRA", "PHX", "PHY", "PLX", "PLY", "STZ", "TRB", "TSB", "WAI", "STP", "RMB", "SMB", "BBR", "BBS",
+];
+
+///
+/// the instruction set of the first 6502 revision, the ROR opcodes are not wired yet
+///
+pub const REV_A_INSTRUCTIONS: [Instruction; 256] = rev_a_instructions();
+
+const fn rev_a_instructions() -> [Instruction<'static>; 256] {
+    let mut instructions = INSTRUCTIONS;
+    let opcodes = [0x66, 0x6A, 0x6E, 0x76, 0x7E];
+    let mut i = 0;
+    while i < opcodes.len() {
+        instructions[opcodes[i]].execute = function::ror_rev_a;
+        i += 1;
+    }
+    instructions
 }
 
 pub fn find_instruction_by_name_and_mode<'a>(
@@ -1399,12 +1459,12 @@ pub const INSTRUCTIONS: [Instruction; 256] = [
         execute: function::tax,
     },
     Instruction {
-        name: "LAX",
+        name: "LXA",
         opcode: 0xAB,
         addressing_mode: AddressingMode::Immediate,
         cycles: 2,
         length: 2,
-        execute: function::lax,
+        execute: function::lxa,
     },
     Instruction {
         name: "LDY",
@@ -1855,12 +1915,12 @@ pub const INSTRUCTIONS: [Instruction; 256] = [
         execute: function::isc,
     },
     Instruction {
-        name: "NOP",
+        name: "CPX",
         opcode: 0xE4,
         addressing_mode: AddressingMode::ZeroPage,
         cycles: 3,
         length: 2,
-        execute: function::nop,
+        execute: function::cpx,
     },
     Instruction {
         name: "SBC",
@@ -2079,3 +2139,2058 @@ pub const INSTRUCTIONS: [Instruction; 256] = [
         execute: function::isc,
     },
 ];
+
+///
+/// the instruction set of the WDC 65C02 with the Rockwell bit instructions,
+/// the opcodes left undefined are NOPs of various lengths
+///
+pub const CMOS_INSTRUCTIONS: [Instruction; 256] = [
+    Instruction {
+        name: "BRK",
+        opcode: 0x00,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 7,
+        length: 1,
+        execute: function::brk,
+    },
+    Instruction {
+        name: "ORA",
+        opcode: 0x01,
+        addressing_mode: AddressingMode::IndirectX,
+        cycles: 6,
+        length: 2,
+        execute: function::ora,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x02,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x03,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "TSB",
+        opcode: 0x04,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::tsb,
+    },
+    Instruction {
+        name: "ORA",
+        opcode: 0x05,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::ora,
+    },
+    Instruction {
+        name: "ASL",
+        opcode: 0x06,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::asl,
+    },
+    Instruction {
+        name: "RMB0",
+        opcode: 0x07,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::rmb::<0>,
+    },
+    Instruction {
+        name: "PHP",
+        opcode: 0x08,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 3,
+        length: 1,
+        execute: function::php,
+    },
+    Instruction {
+        name: "ORA",
+        opcode: 0x09,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::ora,
+    },
+    Instruction {
+        name: "ASL",
+        opcode: 0x0A,
+        addressing_mode: AddressingMode::Accumulator,
+        cycles: 2,
+        length: 1,
+        execute: function::asl,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x0B,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "TSB",
+        opcode: 0x0C,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 6,
+        length: 3,
+        execute: function::tsb,
+    },
+    Instruction {
+        name: "ORA",
+        opcode: 0x0D,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::ora,
+    },
+    Instruction {
+        name: "ASL",
+        opcode: 0x0E,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 6,
+        length: 3,
+        execute: function::asl,
+    },
+    Instruction {
+        name: "BBR0",
+        opcode: 0x0F,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbr::<0>,
+    },
+    Instruction {
+        name: "BPL",
+        opcode: 0x10,
+        addressing_mode: AddressingMode::Relative,
+        cycles: 2,
+        length: 2,
+        execute: function::bpl,
+    },
+    Instruction {
+        name: "ORA",
+        opcode: 0x11,
+        addressing_mode: AddressingMode::IndirectY,
+        cycles: 5,
+        length: 2,
+        execute: function::ora,
+    },
+    Instruction {
+        name: "ORA",
+        opcode: 0x12,
+        addressing_mode: AddressingMode::ZeroPageIndirect,
+        cycles: 5,
+        length: 2,
+        execute: function::ora,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x13,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "TRB",
+        opcode: 0x14,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::trb,
+    },
+    Instruction {
+        name: "ORA",
+        opcode: 0x15,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::ora,
+    },
+    Instruction {
+        name: "ASL",
+        opcode: 0x16,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 6,
+        length: 2,
+        execute: function::asl,
+    },
+    Instruction {
+        name: "RMB1",
+        opcode: 0x17,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::rmb::<1>,
+    },
+    Instruction {
+        name: "CLC",
+        opcode: 0x18,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::clc,
+    },
+    Instruction {
+        name: "ORA",
+        opcode: 0x19,
+        addressing_mode: AddressingMode::AbsoluteY,
+        cycles: 4,
+        length: 3,
+        execute: function::ora,
+    },
+    Instruction {
+        name: "INC",
+        opcode: 0x1A,
+        addressing_mode: AddressingMode::Accumulator,
+        cycles: 2,
+        length: 1,
+        execute: function::inc,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x1B,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "TRB",
+        opcode: 0x1C,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 6,
+        length: 3,
+        execute: function::trb,
+    },
+    Instruction {
+        name: "ORA",
+        opcode: 0x1D,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 4,
+        length: 3,
+        execute: function::ora,
+    },
+    Instruction {
+        name: "ASL",
+        opcode: 0x1E,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 6,
+        length: 3,
+        execute: function::asl,
+    },
+    Instruction {
+        name: "BBR1",
+        opcode: 0x1F,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbr::<1>,
+    },
+    Instruction {
+        name: "JSR",
+        opcode: 0x20,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 6,
+        length: 3,
+        execute: function::jsr,
+    },
+    Instruction {
+        name: "AND",
+        opcode: 0x21,
+        addressing_mode: AddressingMode::IndirectX,
+        cycles: 6,
+        length: 2,
+        execute: function::and,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x22,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x23,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "BIT",
+        opcode: 0x24,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::bit,
+    },
+    Instruction {
+        name: "AND",
+        opcode: 0x25,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::and,
+    },
+    Instruction {
+        name: "ROL",
+        opcode: 0x26,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::rol,
+    },
+    Instruction {
+        name: "RMB2",
+        opcode: 0x27,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::rmb::<2>,
+    },
+    Instruction {
+        name: "PLP",
+        opcode: 0x28,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 4,
+        length: 1,
+        execute: function::plp,
+    },
+    Instruction {
+        name: "AND",
+        opcode: 0x29,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::and,
+    },
+    Instruction {
+        name: "ROL",
+        opcode: 0x2A,
+        addressing_mode: AddressingMode::Accumulator,
+        cycles: 2,
+        length: 1,
+        execute: function::rol,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x2B,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "BIT",
+        opcode: 0x2C,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::bit,
+    },
+    Instruction {
+        name: "AND",
+        opcode: 0x2D,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::and,
+    },
+    Instruction {
+        name: "ROL",
+        opcode: 0x2E,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 6,
+        length: 3,
+        execute: function::rol,
+    },
+    Instruction {
+        name: "BBR2",
+        opcode: 0x2F,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbr::<2>,
+    },
+    Instruction {
+        name: "BMI",
+        opcode: 0x30,
+        addressing_mode: AddressingMode::Relative,
+        cycles: 2,
+        length: 2,
+        execute: function::bmi,
+    },
+    Instruction {
+        name: "AND",
+        opcode: 0x31,
+        addressing_mode: AddressingMode::IndirectY,
+        cycles: 5,
+        length: 2,
+        execute: function::and,
+    },
+    Instruction {
+        name: "AND",
+        opcode: 0x32,
+        addressing_mode: AddressingMode::ZeroPageIndirect,
+        cycles: 5,
+        length: 2,
+        execute: function::and,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x33,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "BIT",
+        opcode: 0x34,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::bit,
+    },
+    Instruction {
+        name: "AND",
+        opcode: 0x35,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::and,
+    },
+    Instruction {
+        name: "ROL",
+        opcode: 0x36,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 6,
+        length: 2,
+        execute: function::rol,
+    },
+    Instruction {
+        name: "RMB3",
+        opcode: 0x37,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::rmb::<3>,
+    },
+    Instruction {
+        name: "SEC",
+        opcode: 0x38,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::sec,
+    },
+    Instruction {
+        name: "AND",
+        opcode: 0x39,
+        addressing_mode: AddressingMode::AbsoluteY,
+        cycles: 4,
+        length: 3,
+        execute: function::and,
+    },
+    Instruction {
+        name: "DEC",
+        opcode: 0x3A,
+        addressing_mode: AddressingMode::Accumulator,
+        cycles: 2,
+        length: 1,
+        execute: function::dec,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x3B,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "BIT",
+        opcode: 0x3C,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 4,
+        length: 3,
+        execute: function::bit,
+    },
+    Instruction {
+        name: "AND",
+        opcode: 0x3D,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 4,
+        length: 3,
+        execute: function::and,
+    },
+    Instruction {
+        name: "ROL",
+        opcode: 0x3E,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 6,
+        length: 3,
+        execute: function::rol,
+    },
+    Instruction {
+        name: "BBR3",
+        opcode: 0x3F,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbr::<3>,
+    },
+    Instruction {
+        name: "RTI",
+        opcode: 0x40,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 6,
+        length: 1,
+        execute: function::rti,
+    },
+    Instruction {
+        name: "EOR",
+        opcode: 0x41,
+        addressing_mode: AddressingMode::IndirectX,
+        cycles: 6,
+        length: 2,
+        execute: function::eor,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x42,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x43,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x44,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "EOR",
+        opcode: 0x45,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::eor,
+    },
+    Instruction {
+        name: "LSR",
+        opcode: 0x46,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::lsr,
+    },
+    Instruction {
+        name: "RMB4",
+        opcode: 0x47,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::rmb::<4>,
+    },
+    Instruction {
+        name: "PHA",
+        opcode: 0x48,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 3,
+        length: 1,
+        execute: function::pha,
+    },
+    Instruction {
+        name: "EOR",
+        opcode: 0x49,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::eor,
+    },
+    Instruction {
+        name: "LSR",
+        opcode: 0x4A,
+        addressing_mode: AddressingMode::Accumulator,
+        cycles: 2,
+        length: 1,
+        execute: function::lsr,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x4B,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "JMP",
+        opcode: 0x4C,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 3,
+        length: 3,
+        execute: function::jmp,
+    },
+    Instruction {
+        name: "EOR",
+        opcode: 0x4D,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::eor,
+    },
+    Instruction {
+        name: "LSR",
+        opcode: 0x4E,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 6,
+        length: 3,
+        execute: function::lsr,
+    },
+    Instruction {
+        name: "BBR4",
+        opcode: 0x4F,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbr::<4>,
+    },
+    Instruction {
+        name: "BVC",
+        opcode: 0x50,
+        addressing_mode: AddressingMode::Relative,
+        cycles: 2,
+        length: 2,
+        execute: function::bvc,
+    },
+    Instruction {
+        name: "EOR",
+        opcode: 0x51,
+        addressing_mode: AddressingMode::IndirectY,
+        cycles: 5,
+        length: 2,
+        execute: function::eor,
+    },
+    Instruction {
+        name: "EOR",
+        opcode: 0x52,
+        addressing_mode: AddressingMode::ZeroPageIndirect,
+        cycles: 5,
+        length: 2,
+        execute: function::eor,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x53,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x54,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "EOR",
+        opcode: 0x55,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::eor,
+    },
+    Instruction {
+        name: "LSR",
+        opcode: 0x56,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 6,
+        length: 2,
+        execute: function::lsr,
+    },
+    Instruction {
+        name: "RMB5",
+        opcode: 0x57,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::rmb::<5>,
+    },
+    Instruction {
+        name: "CLI",
+        opcode: 0x58,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::cli,
+    },
+    Instruction {
+        name: "EOR",
+        opcode: 0x59,
+        addressing_mode: AddressingMode::AbsoluteY,
+        cycles: 4,
+        length: 3,
+        execute: function::eor,
+    },
+    Instruction {
+        name: "PHY",
+        opcode: 0x5A,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 3,
+        length: 1,
+        execute: function::phy,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x5B,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x5C,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 8,
+        length: 3,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "EOR",
+        opcode: 0x5D,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 4,
+        length: 3,
+        execute: function::eor,
+    },
+    Instruction {
+        name: "LSR",
+        opcode: 0x5E,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 6,
+        length: 3,
+        execute: function::lsr,
+    },
+    Instruction {
+        name: "BBR5",
+        opcode: 0x5F,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbr::<5>,
+    },
+    Instruction {
+        name: "RTS",
+        opcode: 0x60,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 6,
+        length: 1,
+        execute: function::rts,
+    },
+    Instruction {
+        name: "ADC",
+        opcode: 0x61,
+        addressing_mode: AddressingMode::IndirectX,
+        cycles: 6,
+        length: 2,
+        execute: function::adc,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x62,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x63,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "STZ",
+        opcode: 0x64,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::stz,
+    },
+    Instruction {
+        name: "ADC",
+        opcode: 0x65,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::adc,
+    },
+    Instruction {
+        name: "ROR",
+        opcode: 0x66,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::ror,
+    },
+    Instruction {
+        name: "RMB6",
+        opcode: 0x67,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::rmb::<6>,
+    },
+    Instruction {
+        name: "PLA",
+        opcode: 0x68,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 4,
+        length: 1,
+        execute: function::pla,
+    },
+    Instruction {
+        name: "ADC",
+        opcode: 0x69,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::adc,
+    },
+    Instruction {
+        name: "ROR",
+        opcode: 0x6A,
+        addressing_mode: AddressingMode::Accumulator,
+        cycles: 2,
+        length: 1,
+        execute: function::ror,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x6B,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "JMP",
+        opcode: 0x6C,
+        addressing_mode: AddressingMode::Indirect,
+        cycles: 6,
+        length: 3,
+        execute: function::jmp,
+    },
+    Instruction {
+        name: "ADC",
+        opcode: 0x6D,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::adc,
+    },
+    Instruction {
+        name: "ROR",
+        opcode: 0x6E,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 6,
+        length: 3,
+        execute: function::ror,
+    },
+    Instruction {
+        name: "BBR6",
+        opcode: 0x6F,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbr::<6>,
+    },
+    Instruction {
+        name: "BVS",
+        opcode: 0x70,
+        addressing_mode: AddressingMode::Relative,
+        cycles: 2,
+        length: 2,
+        execute: function::bvs,
+    },
+    Instruction {
+        name: "ADC",
+        opcode: 0x71,
+        addressing_mode: AddressingMode::IndirectY,
+        cycles: 5,
+        length: 2,
+        execute: function::adc,
+    },
+    Instruction {
+        name: "ADC",
+        opcode: 0x72,
+        addressing_mode: AddressingMode::ZeroPageIndirect,
+        cycles: 5,
+        length: 2,
+        execute: function::adc,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x73,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "STZ",
+        opcode: 0x74,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::stz,
+    },
+    Instruction {
+        name: "ADC",
+        opcode: 0x75,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::adc,
+    },
+    Instruction {
+        name: "ROR",
+        opcode: 0x76,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 6,
+        length: 2,
+        execute: function::ror,
+    },
+    Instruction {
+        name: "RMB7",
+        opcode: 0x77,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::rmb::<7>,
+    },
+    Instruction {
+        name: "SEI",
+        opcode: 0x78,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::sei,
+    },
+    Instruction {
+        name: "ADC",
+        opcode: 0x79,
+        addressing_mode: AddressingMode::AbsoluteY,
+        cycles: 4,
+        length: 3,
+        execute: function::adc,
+    },
+    Instruction {
+        name: "PLY",
+        opcode: 0x7A,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 4,
+        length: 1,
+        execute: function::ply,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x7B,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "JMP",
+        opcode: 0x7C,
+        addressing_mode: AddressingMode::AbsoluteIndirectX,
+        cycles: 6,
+        length: 3,
+        execute: function::jmp,
+    },
+    Instruction {
+        name: "ADC",
+        opcode: 0x7D,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 4,
+        length: 3,
+        execute: function::adc,
+    },
+    Instruction {
+        name: "ROR",
+        opcode: 0x7E,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 6,
+        length: 3,
+        execute: function::ror,
+    },
+    Instruction {
+        name: "BBR7",
+        opcode: 0x7F,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbr::<7>,
+    },
+    Instruction {
+        name: "BRA",
+        opcode: 0x80,
+        addressing_mode: AddressingMode::Relative,
+        cycles: 2,
+        length: 2,
+        execute: function::bra,
+    },
+    Instruction {
+        name: "STA",
+        opcode: 0x81,
+        addressing_mode: AddressingMode::IndirectX,
+        cycles: 6,
+        length: 2,
+        execute: function::sta,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x82,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x83,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "STY",
+        opcode: 0x84,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::sty,
+    },
+    Instruction {
+        name: "STA",
+        opcode: 0x85,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::sta,
+    },
+    Instruction {
+        name: "STX",
+        opcode: 0x86,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::stx,
+    },
+    Instruction {
+        name: "SMB0",
+        opcode: 0x87,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::smb::<0>,
+    },
+    Instruction {
+        name: "DEY",
+        opcode: 0x88,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::dey,
+    },
+    Instruction {
+        name: "BIT",
+        opcode: 0x89,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::bit,
+    },
+    Instruction {
+        name: "TXA",
+        opcode: 0x8A,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::txa,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x8B,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "STY",
+        opcode: 0x8C,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::sty,
+    },
+    Instruction {
+        name: "STA",
+        opcode: 0x8D,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::sta,
+    },
+    Instruction {
+        name: "STX",
+        opcode: 0x8E,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::stx,
+    },
+    Instruction {
+        name: "BBS0",
+        opcode: 0x8F,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbs::<0>,
+    },
+    Instruction {
+        name: "BCC",
+        opcode: 0x90,
+        addressing_mode: AddressingMode::Relative,
+        cycles: 2,
+        length: 2,
+        execute: function::bcc,
+    },
+    Instruction {
+        name: "STA",
+        opcode: 0x91,
+        addressing_mode: AddressingMode::IndirectY,
+        cycles: 6,
+        length: 2,
+        execute: function::sta,
+    },
+    Instruction {
+        name: "STA",
+        opcode: 0x92,
+        addressing_mode: AddressingMode::ZeroPageIndirect,
+        cycles: 5,
+        length: 2,
+        execute: function::sta,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x93,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "STY",
+        opcode: 0x94,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::sty,
+    },
+    Instruction {
+        name: "STA",
+        opcode: 0x95,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::sta,
+    },
+    Instruction {
+        name: "STX",
+        opcode: 0x96,
+        addressing_mode: AddressingMode::ZeroPageY,
+        cycles: 4,
+        length: 2,
+        execute: function::stx,
+    },
+    Instruction {
+        name: "SMB1",
+        opcode: 0x97,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::smb::<1>,
+    },
+    Instruction {
+        name: "TYA",
+        opcode: 0x98,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::tya,
+    },
+    Instruction {
+        name: "STA",
+        opcode: 0x99,
+        addressing_mode: AddressingMode::AbsoluteY,
+        cycles: 5,
+        length: 3,
+        execute: function::sta,
+    },
+    Instruction {
+        name: "TXS",
+        opcode: 0x9A,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::txs,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0x9B,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "STZ",
+        opcode: 0x9C,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::stz,
+    },
+    Instruction {
+        name: "STA",
+        opcode: 0x9D,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 5,
+        length: 3,
+        execute: function::sta,
+    },
+    Instruction {
+        name: "STZ",
+        opcode: 0x9E,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 5,
+        length: 3,
+        execute: function::stz,
+    },
+    Instruction {
+        name: "BBS1",
+        opcode: 0x9F,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbs::<1>,
+    },
+    Instruction {
+        name: "LDY",
+        opcode: 0xA0,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::ldy,
+    },
+    Instruction {
+        name: "LDA",
+        opcode: 0xA1,
+        addressing_mode: AddressingMode::IndirectX,
+        cycles: 6,
+        length: 2,
+        execute: function::lda,
+    },
+    Instruction {
+        name: "LDX",
+        opcode: 0xA2,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::ldx,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xA3,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "LDY",
+        opcode: 0xA4,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::ldy,
+    },
+    Instruction {
+        name: "LDA",
+        opcode: 0xA5,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::lda,
+    },
+    Instruction {
+        name: "LDX",
+        opcode: 0xA6,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::ldx,
+    },
+    Instruction {
+        name: "SMB2",
+        opcode: 0xA7,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::smb::<2>,
+    },
+    Instruction {
+        name: "TAY",
+        opcode: 0xA8,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::tay,
+    },
+    Instruction {
+        name: "LDA",
+        opcode: 0xA9,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::lda,
+    },
+    Instruction {
+        name: "TAX",
+        opcode: 0xAA,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::tax,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xAB,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "LDY",
+        opcode: 0xAC,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::ldy,
+    },
+    Instruction {
+        name: "LDA",
+        opcode: 0xAD,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::lda,
+    },
+    Instruction {
+        name: "LDX",
+        opcode: 0xAE,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::ldx,
+    },
+    Instruction {
+        name: "BBS2",
+        opcode: 0xAF,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbs::<2>,
+    },
+    Instruction {
+        name: "BCS",
+        opcode: 0xB0,
+        addressing_mode: AddressingMode::Relative,
+        cycles: 2,
+        length: 2,
+        execute: function::bcs,
+    },
+    Instruction {
+        name: "LDA",
+        opcode: 0xB1,
+        addressing_mode: AddressingMode::IndirectY,
+        cycles: 5,
+        length: 2,
+        execute: function::lda,
+    },
+    Instruction {
+        name: "LDA",
+        opcode: 0xB2,
+        addressing_mode: AddressingMode::ZeroPageIndirect,
+        cycles: 5,
+        length: 2,
+        execute: function::lda,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xB3,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "LDY",
+        opcode: 0xB4,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::ldy,
+    },
+    Instruction {
+        name: "LDA",
+        opcode: 0xB5,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::lda,
+    },
+    Instruction {
+        name: "LDX",
+        opcode: 0xB6,
+        addressing_mode: AddressingMode::ZeroPageY,
+        cycles: 4,
+        length: 2,
+        execute: function::ldx,
+    },
+    Instruction {
+        name: "SMB3",
+        opcode: 0xB7,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::smb::<3>,
+    },
+    Instruction {
+        name: "CLV",
+        opcode: 0xB8,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::clv,
+    },
+    Instruction {
+        name: "LDA",
+        opcode: 0xB9,
+        addressing_mode: AddressingMode::AbsoluteY,
+        cycles: 4,
+        length: 3,
+        execute: function::lda,
+    },
+    Instruction {
+        name: "TSX",
+        opcode: 0xBA,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::tsx,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xBB,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "LDY",
+        opcode: 0xBC,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 4,
+        length: 3,
+        execute: function::ldy,
+    },
+    Instruction {
+        name: "LDA",
+        opcode: 0xBD,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 4,
+        length: 3,
+        execute: function::lda,
+    },
+    Instruction {
+        name: "LDX",
+        opcode: 0xBE,
+        addressing_mode: AddressingMode::AbsoluteY,
+        cycles: 4,
+        length: 3,
+        execute: function::ldx,
+    },
+    Instruction {
+        name: "BBS3",
+        opcode: 0xBF,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbs::<3>,
+    },
+    Instruction {
+        name: "CPY",
+        opcode: 0xC0,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::cpy,
+    },
+    Instruction {
+        name: "CMP",
+        opcode: 0xC1,
+        addressing_mode: AddressingMode::IndirectX,
+        cycles: 6,
+        length: 2,
+        execute: function::cmp,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xC2,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xC3,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "CPY",
+        opcode: 0xC4,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::cpy,
+    },
+    Instruction {
+        name: "CMP",
+        opcode: 0xC5,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::cmp,
+    },
+    Instruction {
+        name: "DEC",
+        opcode: 0xC6,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::dec,
+    },
+    Instruction {
+        name: "SMB4",
+        opcode: 0xC7,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::smb::<4>,
+    },
+    Instruction {
+        name: "INY",
+        opcode: 0xC8,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::iny,
+    },
+    Instruction {
+        name: "CMP",
+        opcode: 0xC9,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::cmp,
+    },
+    Instruction {
+        name: "DEX",
+        opcode: 0xCA,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::dex,
+    },
+    Instruction {
+        name: "WAI",
+        opcode: 0xCB,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 3,
+        length: 1,
+        execute: function::wai,
+    },
+    Instruction {
+        name: "CPY",
+        opcode: 0xCC,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::cpy,
+    },
+    Instruction {
+        name: "CMP",
+        opcode: 0xCD,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::cmp,
+    },
+    Instruction {
+        name: "DEC",
+        opcode: 0xCE,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 6,
+        length: 3,
+        execute: function::dec,
+    },
+    Instruction {
+        name: "BBS4",
+        opcode: 0xCF,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbs::<4>,
+    },
+    Instruction {
+        name: "BNE",
+        opcode: 0xD0,
+        addressing_mode: AddressingMode::Relative,
+        cycles: 2,
+        length: 2,
+        execute: function::bne,
+    },
+    Instruction {
+        name: "CMP",
+        opcode: 0xD1,
+        addressing_mode: AddressingMode::IndirectY,
+        cycles: 5,
+        length: 2,
+        execute: function::cmp,
+    },
+    Instruction {
+        name: "CMP",
+        opcode: 0xD2,
+        addressing_mode: AddressingMode::ZeroPageIndirect,
+        cycles: 5,
+        length: 2,
+        execute: function::cmp,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xD3,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xD4,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "CMP",
+        opcode: 0xD5,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::cmp,
+    },
+    Instruction {
+        name: "DEC",
+        opcode: 0xD6,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 6,
+        length: 2,
+        execute: function::dec,
+    },
+    Instruction {
+        name: "SMB5",
+        opcode: 0xD7,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::smb::<5>,
+    },
+    Instruction {
+        name: "CLD",
+        opcode: 0xD8,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::cld,
+    },
+    Instruction {
+        name: "CMP",
+        opcode: 0xD9,
+        addressing_mode: AddressingMode::AbsoluteY,
+        cycles: 4,
+        length: 3,
+        execute: function::cmp,
+    },
+    Instruction {
+        name: "PHX",
+        opcode: 0xDA,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 3,
+        length: 1,
+        execute: function::phx,
+    },
+    Instruction {
+        name: "STP",
+        opcode: 0xDB,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 3,
+        length: 1,
+        execute: function::stp,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xDC,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "CMP",
+        opcode: 0xDD,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 4,
+        length: 3,
+        execute: function::cmp,
+    },
+    Instruction {
+        name: "DEC",
+        opcode: 0xDE,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 7,
+        length: 3,
+        execute: function::dec,
+    },
+    Instruction {
+        name: "BBS5",
+        opcode: 0xDF,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbs::<5>,
+    },
+    Instruction {
+        name: "CPX",
+        opcode: 0xE0,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::cpx,
+    },
+    Instruction {
+        name: "SBC",
+        opcode: 0xE1,
+        addressing_mode: AddressingMode::IndirectX,
+        cycles: 6,
+        length: 2,
+        execute: function::sbc,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xE2,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xE3,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "CPX",
+        opcode: 0xE4,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::cpx,
+    },
+    Instruction {
+        name: "SBC",
+        opcode: 0xE5,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 3,
+        length: 2,
+        execute: function::sbc,
+    },
+    Instruction {
+        name: "INC",
+        opcode: 0xE6,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::inc,
+    },
+    Instruction {
+        name: "SMB6",
+        opcode: 0xE7,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::smb::<6>,
+    },
+    Instruction {
+        name: "INX",
+        opcode: 0xE8,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::inx,
+    },
+    Instruction {
+        name: "SBC",
+        opcode: 0xE9,
+        addressing_mode: AddressingMode::Immediate,
+        cycles: 2,
+        length: 2,
+        execute: function::sbc,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xEA,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xEB,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "CPX",
+        opcode: 0xEC,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::cpx,
+    },
+    Instruction {
+        name: "SBC",
+        opcode: 0xED,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::sbc,
+    },
+    Instruction {
+        name: "INC",
+        opcode: 0xEE,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 6,
+        length: 3,
+        execute: function::inc,
+    },
+    Instruction {
+        name: "BBS6",
+        opcode: 0xEF,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbs::<6>,
+    },
+    Instruction {
+        name: "BEQ",
+        opcode: 0xF0,
+        addressing_mode: AddressingMode::Relative,
+        cycles: 2,
+        length: 2,
+        execute: function::beq,
+    },
+    Instruction {
+        name: "SBC",
+        opcode: 0xF1,
+        addressing_mode: AddressingMode::IndirectY,
+        cycles: 5,
+        length: 2,
+        execute: function::sbc,
+    },
+    Instruction {
+        name: "SBC",
+        opcode: 0xF2,
+        addressing_mode: AddressingMode::ZeroPageIndirect,
+        cycles: 5,
+        length: 2,
+        execute: function::sbc,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xF3,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xF4,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "SBC",
+        opcode: 0xF5,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 4,
+        length: 2,
+        execute: function::sbc,
+    },
+    Instruction {
+        name: "INC",
+        opcode: 0xF6,
+        addressing_mode: AddressingMode::ZeroPageX,
+        cycles: 6,
+        length: 2,
+        execute: function::inc,
+    },
+    Instruction {
+        name: "SMB7",
+        opcode: 0xF7,
+        addressing_mode: AddressingMode::ZeroPage,
+        cycles: 5,
+        length: 2,
+        execute: function::smb::<7>,
+    },
+    Instruction {
+        name: "SED",
+        opcode: 0xF8,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 2,
+        length: 1,
+        execute: function::sed,
+    },
+    Instruction {
+        name: "SBC",
+        opcode: 0xF9,
+        addressing_mode: AddressingMode::AbsoluteY,
+        cycles: 4,
+        length: 3,
+        execute: function::sbc,
+    },
+    Instruction {
+        name: "PLX",
+        opcode: 0xFA,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 4,
+        length: 1,
+        execute: function::plx,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xFB,
+        addressing_mode: AddressingMode::Implied,
+        cycles: 1,
+        length: 1,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "NOP",
+        opcode: 0xFC,
+        addressing_mode: AddressingMode::Absolute,
+        cycles: 4,
+        length: 3,
+        execute: function::nop,
+    },
+    Instruction {
+        name: "SBC",
+        opcode: 0xFD,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 4,
+        length: 3,
+        execute: function::sbc,
+    },
+    Instruction {
+        name: "INC",
+        opcode: 0xFE,
+        addressing_mode: AddressingMode::AbsoluteX,
+        cycles: 7,
+        length: 3,
+        execute: function::inc,
+    },
+    Instruction {
+        name: "BBS7",
+        opcode: 0xFF,
+        addressing_mode: AddressingMode::ZeroPageRelative,
+        cycles: 5,
+        length: 3,
+        execute: function::bbs::<7>,
+    },
+];
@@ -0,0 +1,507 @@
+//!
+//! File: cpu/micro_op.rs
+//! The micro op module contains the cycle by cycle programs used by `Cpu6502::tick`.
+//! Every instruction is split into micro ops doing exactly one bus access each,
+//! the dummy reads and writes of the real hardware included.
+//!
+//! # See
+//! * [http://www.6502.org/tutorials/6502opcodes.html](http://www.6502.org/tutorials/6502opcodes.html)
+//! * [https://www.nesdev.org/6502_cpu.txt](https://www.nesdev.org/6502_cpu.txt)
+//!
+
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::flag::Flag;
+use crate::cpu::instruction::Instruction;
+use crate::cpu::operand::Operand;
+use crate::cpu::variant::{CpuVariant, IllegalOpcodes};
+use crate::util::constants::{STACK_SIZE, IRQ_ADDRESS_LOW};
+use crate::util::types::{Byte, Address};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum MicroOp {
+    // read the next program byte as the low byte of the address
+    FetchLow,
+    // read the next program byte as the high byte of the address
+    FetchHigh,
+    // read the high byte and add the index to the address, the high byte is fixed one cycle later
+    FetchHighIndexed,
+    // read the immediate operand and run the instruction on it
+    FetchImmediate,
+    // read the branch offset of BBR and BBS
+    FetchOffset,
+    // read the byte following BRK, it is skipped
+    FetchSignature,
+    // dummy read of the zero page address then add the index, wrapping inside the zero page
+    IndexZeroPage,
+    // read the low byte of the zero page pointer
+    ReadPointerLow,
+    // read the high byte of the zero page pointer
+    ReadPointerHigh,
+    // read the high byte of the zero page pointer and add Y
+    ReadPointerHighIndexed,
+    // dummy read at the indexed address before its high byte is fixed
+    Fixup,
+    // same as `Fixup` but only when a page boundary was crossed, for the read instructions
+    FixupIfCrossed,
+    // the data access, the instruction reads or writes its effective address
+    Execute,
+    // first cycle of a read-modify-write, the data is read and the instruction runs
+    // with its write held back
+    ReadModify,
+    // the NMOS parts write the unmodified value back, the 65C02 reads it again
+    DummyWrite,
+    // last cycle of a read-modify-write, the held back write
+    WriteResult,
+    // dummy read of the next program byte then run the instruction
+    Implied,
+    // run a push or pull instruction, its stack access is the bus access of the cycle
+    Stack,
+    DummyReadPc,
+    DummyReadStack,
+    PushPch,
+    PushPcl,
+    PushStatus,
+    PullStatus,
+    PullPcl,
+    PullPch,
+    // last cycle of RTS, dummy read of the pulled address then move past it
+    IncrementPc,
+    // read the high byte of the target and jump, for JMP and JSR
+    JumpHigh,
+    // dummy read while X is added to the pointer of JMP (abs,X)
+    AddX,
+    // read the target of JMP through the pointer
+    ReadIndirectLow,
+    ReadIndirectHigh,
+    // set the interrupt flag and read the vector
+    VectorLow,
+    VectorHigh,
+    // read the offset and run the branch instruction
+    Branch,
+    // dummy read of the next opcode while a taken branch adds the offset
+    BranchTaken,
+    // dummy read while the high byte of a branch target crossing a page is fixed
+    BranchFixup,
+    // dummy read of the program counter until the instruction used the cycles of the table
+    Pad,
+}
+
+///
+/// how an instruction uses its effective address
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Access {
+    Read,
+    Write,
+    ReadModifyWrite,
+}
+
+fn access(name: &str) -> Access {
+    match name {
+        "STA" | "STX" | "STY" | "STZ" | "SAX" | "AHX" | "SHX" | "SHY" | "TAS" => Access::Write,
+        "ASL" | "LSR" | "ROL" | "ROR" | "INC" | "DEC" | "SLO" | "SRE" | "RLA" | "RRA" | "DCP" | "ISC"
+        | "TSB" | "TRB" => Access::ReadModifyWrite,
+        _ if name.starts_with("RMB") || name.starts_with("SMB") => Access::ReadModifyWrite,
+        _ => Access::Read,
+    }
+}
+
+///
+/// the micro ops following the opcode fetch
+///
+fn program(instruction: &Instruction) -> &'static [MicroOp] {
+    use MicroOp::*;
+
+    match instruction.name {
+        "BRK" => return &[FetchSignature, PushPch, PushPcl, PushStatus, VectorLow, VectorHigh],
+        "JSR" => return &[FetchLow, DummyReadStack, PushPch, PushPcl, JumpHigh],
+        "RTS" => return &[DummyReadPc, DummyReadStack, PullPcl, PullPch, IncrementPc],
+        "RTI" => return &[DummyReadPc, DummyReadStack, PullStatus, PullPcl, PullPch],
+        "PHA" | "PHP" | "PHX" | "PHY" => return &[DummyReadPc, Stack],
+        "PLA" | "PLP" | "PLX" | "PLY" => return &[DummyReadPc, DummyReadStack, Stack],
+        "JMP" => {
+            return match instruction.addressing_mode {
+                AddressingMode::Absolute => &[FetchLow, JumpHigh],
+                AddressingMode::AbsoluteIndirectX => &[FetchLow, FetchHigh, AddX, ReadIndirectLow, ReadIndirectHigh],
+                _ => &[FetchLow, FetchHigh, ReadIndirectLow, ReadIndirectHigh, Pad],
+            }
+        }
+        _ => {}
+    }
+
+    let access = access(instruction.name);
+    match instruction.addressing_mode {
+        // the one cycle NOPs of the 65C02 have no cycle for the dummy read
+        AddressingMode::Implied | AddressingMode::Accumulator if instruction.cycles == 1 => &[Pad],
+        AddressingMode::Implied | AddressingMode::Accumulator => &[Implied, Pad],
+        AddressingMode::Immediate => &[FetchImmediate, Pad],
+        AddressingMode::Relative => &[Branch, BranchTaken, BranchFixup],
+        AddressingMode::ZeroPageRelative => &[FetchLow, FetchOffset, Execute, BranchTaken, BranchFixup, Pad],
+        AddressingMode::ZeroPage => match access {
+            Access::ReadModifyWrite => &[FetchLow, ReadModify, DummyWrite, WriteResult, Pad],
+            _ => &[FetchLow, Execute, Pad],
+        },
+        AddressingMode::ZeroPageX | AddressingMode::ZeroPageY => match access {
+            Access::ReadModifyWrite => &[FetchLow, IndexZeroPage, ReadModify, DummyWrite, WriteResult, Pad],
+            _ => &[FetchLow, IndexZeroPage, Execute, Pad],
+        },
+        AddressingMode::Absolute => match access {
+            Access::ReadModifyWrite => &[FetchLow, FetchHigh, ReadModify, DummyWrite, WriteResult, Pad],
+            _ => &[FetchLow, FetchHigh, Execute, Pad],
+        },
+        AddressingMode::AbsoluteX | AddressingMode::AbsoluteY => match access {
+            Access::Read => &[FetchLow, FetchHighIndexed, FixupIfCrossed, Execute, Pad],
+            Access::Write => &[FetchLow, FetchHighIndexed, Fixup, Execute, Pad],
+            // the 65C02 shifts and rotates only fix the high byte when the page is crossed
+            Access::ReadModifyWrite if instruction.has_page_cross_penalty() => {
+                &[FetchLow, FetchHighIndexed, FixupIfCrossed, ReadModify, DummyWrite, WriteResult, Pad]
+            }
+            Access::ReadModifyWrite => &[FetchLow, FetchHighIndexed, Fixup, ReadModify, DummyWrite, WriteResult, Pad],
+        },
+        AddressingMode::IndirectX => match access {
+            Access::ReadModifyWrite => &[FetchLow, IndexZeroPage, ReadPointerLow, ReadPointerHigh, ReadModify, DummyWrite, WriteResult, Pad],
+            _ => &[FetchLow, IndexZeroPage, ReadPointerLow, ReadPointerHigh, Execute, Pad],
+        },
+        AddressingMode::IndirectY => match access {
+            Access::Read => &[FetchLow, ReadPointerLow, ReadPointerHighIndexed, FixupIfCrossed, Execute, Pad],
+            Access::Write => &[FetchLow, ReadPointerLow, ReadPointerHighIndexed, Fixup, Execute, Pad],
+            Access::ReadModifyWrite => &[FetchLow, ReadPointerLow, ReadPointerHighIndexed, Fixup, ReadModify, DummyWrite, WriteResult, Pad],
+        },
+        AddressingMode::ZeroPageIndirect => &[FetchLow, ReadPointerLow, ReadPointerHigh, Execute, Pad],
+        AddressingMode::Indirect | AddressingMode::AbsoluteIndirectX => &[FetchLow, FetchHigh, Execute, Pad],
+    }
+}
+
+///
+/// the bus accesses of an instruction running in a read-modify-write cycle,
+/// the read goes to the bus but the write is held back for the last cycle
+///
+#[derive(Clone, Copy, Default)]
+pub(crate) struct BusCapture {
+    pub(crate) read: Byte,
+    pub(crate) write: Option<(Address, Byte)>,
+}
+
+///
+/// the values an instruction or an interrupt sequence carries from one cycle to the next
+///
+#[derive(Clone, Copy)]
+pub(crate) struct MicroState {
+    // the instruction being run, None for a hardware interrupt
+    instruction: Option<Instruction<'static>>,
+    // false for the undocumented opcodes the variant turns into NOPs
+    execute: bool,
+    program: &'static [MicroOp],
+    step: usize,
+    // cycles used so far, the opcode fetch included
+    cycles: u8,
+    address: Address,
+    // address before indexing, for the dummy reads of the indexed modes
+    base: Address,
+    crossed: bool,
+    target: Address,
+    data: Byte,
+    result: Byte,
+    vector: Address,
+    break_flag: bool,
+}
+
+impl MicroState {
+    ///
+    /// first cycle of an instruction, the opcode fetch
+    ///
+    pub(crate) fn fetch(cpu: &mut Cpu6502) -> MicroState {
+        let opcode = cpu.read_byte(cpu.registers.pc);
+        cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+        cpu.extra_cycles = 0;
+        let instruction = cpu.variant.instructions()[opcode as usize];
+        MicroState {
+            instruction: Some(instruction),
+            execute: instruction.is_documented() || cpu.illegal_opcodes == IllegalOpcodes::Execute,
+            program: program(&instruction),
+            break_flag: instruction.name == "BRK",
+            ..MicroState::new(IRQ_ADDRESS_LOW)
+        }
+    }
+
+    ///
+    /// first cycle of a hardware interrupt, the opcode fetch is turned into a dummy read
+    /// and the program counter does not move
+    ///
+    pub(crate) fn interrupt(cpu: &mut Cpu6502, vector: Address) -> MicroState {
+        use MicroOp::*;
+
+        cpu.read_byte(cpu.registers.pc);
+        MicroState {
+            program: &[DummyReadPc, PushPch, PushPcl, PushStatus, VectorLow, VectorHigh],
+            ..MicroState::new(vector)
+        }
+    }
+
+    fn new(vector: Address) -> MicroState {
+        MicroState {
+            instruction: None,
+            execute: true,
+            program: &[],
+            step: 0,
+            cycles: 1,
+            address: 0,
+            base: 0,
+            crossed: false,
+            target: 0,
+            data: 0,
+            result: 0,
+            vector,
+            break_flag: false,
+        }
+    }
+
+    pub(crate) fn instruction(&self) -> Option<Instruction<'static>> {
+        self.instruction
+    }
+
+    pub(crate) fn executed(&self) -> bool {
+        self.execute
+    }
+
+    ///
+    /// true once every micro op has run
+    ///
+    pub(crate) fn done(&self) -> bool {
+        self.step >= self.program.len()
+    }
+
+    ///
+    /// move past the micro ops that do not apply, like the fixup of an index that did not cross a page
+    ///
+    pub(crate) fn skip(&mut self, cpu: &Cpu6502) {
+        while !self.done() && self.skipped(cpu, self.program[self.step]) {
+            self.step += 1;
+        }
+    }
+
+    fn skipped(&self, cpu: &Cpu6502, op: MicroOp) -> bool {
+        match op {
+            MicroOp::FixupIfCrossed => !self.crossed,
+            MicroOp::BranchTaken => cpu.extra_cycles == 0,
+            MicroOp::BranchFixup => cpu.extra_cycles < 2,
+            MicroOp::Pad => self.cycles >= self.expected_cycles(cpu),
+            _ => false,
+        }
+    }
+
+    ///
+    /// cycles of the instruction according to the table, like `execute_instruction` counts them
+    ///
+    fn expected_cycles(&self, cpu: &Cpu6502) -> u8 {
+        match self.instruction {
+            Some(instruction) => {
+                let penalty = self.crossed && instruction.has_page_cross_penalty();
+                instruction.cycles + cpu.extra_cycles + penalty as u8
+            }
+            None => 7,
+        }
+    }
+
+    ///
+    /// run the next micro op, it does exactly one bus access
+    ///
+    pub(crate) fn run(&mut self, cpu: &mut Cpu6502) {
+        let op = self.program[self.step];
+        self.step += 1;
+        self.cycles += 1;
+
+        match op {
+            MicroOp::FetchLow => {
+                self.address = self.fetch_byte(cpu) as Address;
+            }
+            MicroOp::FetchHigh => {
+                self.address |= (self.fetch_byte(cpu) as Address) << 8;
+            }
+            MicroOp::FetchHighIndexed => {
+                self.base = self.address | (self.fetch_byte(cpu) as Address) << 8;
+                self.index(self.base, self.index_register(cpu));
+            }
+            MicroOp::FetchImmediate => {
+                let value = self.fetch_byte(cpu);
+                self.run_instruction(cpu, Operand::Immediate(value));
+            }
+            MicroOp::FetchOffset => {
+                let offset = self.fetch_byte(cpu) as i8;
+                self.base = cpu.registers.pc;
+                self.target = cpu.registers.pc.wrapping_add(offset as Address);
+            }
+            MicroOp::FetchSignature => {
+                self.fetch_byte(cpu);
+            }
+            MicroOp::IndexZeroPage => {
+                cpu.read_byte(self.address);
+                self.address = (self.address as Byte).wrapping_add(self.index_register(cpu)) as Address;
+            }
+            MicroOp::ReadPointerLow => {
+                self.data = cpu.read_byte(self.address);
+            }
+            MicroOp::ReadPointerHigh => {
+                let high = cpu.read_byte((self.address as Byte).wrapping_add(1) as Address) as Address;
+                self.address = self.data as Address | high << 8;
+            }
+            MicroOp::ReadPointerHighIndexed => {
+                let high = cpu.read_byte((self.address as Byte).wrapping_add(1) as Address) as Address;
+                self.base = self.data as Address | high << 8;
+                self.index(self.base, cpu.registers.y);
+            }
+            MicroOp::Fixup | MicroOp::FixupIfCrossed => {
+                cpu.read_byte((self.base & 0xFF00) | (self.address & 0x00FF));
+            }
+            MicroOp::Execute => {
+                let operand = match self.instruction.map(|instruction| instruction.addressing_mode) {
+                    Some(AddressingMode::ZeroPageRelative) => Operand::ZeroPageRelative(self.address, self.target),
+                    _ => Operand::Address(self.address),
+                };
+                if self.execute && self.instruction.is_some_and(|instruction| instruction.name != "NOP") {
+                    self.run_instruction(cpu, operand);
+                } else {
+                    cpu.read_byte(self.address);
+                }
+            }
+            MicroOp::ReadModify => {
+                if self.execute {
+                    cpu.capture = Some(Default::default());
+                    self.run_instruction(cpu, Operand::Address(self.address));
+                    let capture = cpu.capture.take().unwrap_or_default();
+                    self.data = capture.read;
+                    self.result = capture.write.map_or(capture.read, |(_, value)| value);
+                } else {
+                    self.data = cpu.read_byte(self.address);
+                    self.result = self.data;
+                }
+            }
+            MicroOp::DummyWrite => {
+                if cpu.variant == CpuVariant::Cmos65C02 {
+                    cpu.read_byte(self.address);
+                } else {
+                    cpu.write_byte(self.address, self.data);
+                }
+            }
+            MicroOp::WriteResult => {
+                cpu.write_byte(self.address, self.result);
+            }
+            MicroOp::Implied => {
+                cpu.read_byte(cpu.registers.pc);
+                let accumulator = self.instruction.is_some_and(|instruction| instruction.addressing_mode == AddressingMode::Accumulator);
+                self.run_instruction(cpu, if accumulator { Operand::Accumulator } else { Operand::None });
+            }
+            MicroOp::Stack => {
+                self.run_instruction(cpu, Operand::None);
+            }
+            MicroOp::DummyReadPc => {
+                cpu.read_byte(cpu.registers.pc);
+            }
+            MicroOp::Pad => {
+                cpu.read_byte(cpu.registers.pc);
+                // repeated until it is skipped
+                self.step -= 1;
+            }
+            MicroOp::DummyReadStack => {
+                cpu.read_byte(STACK_SIZE as Address + cpu.registers.sp as Address);
+            }
+            MicroOp::PushPch => {
+                cpu.push_stack((cpu.registers.pc >> 8) as Byte);
+            }
+            MicroOp::PushPcl => {
+                cpu.push_stack(cpu.registers.pc as Byte);
+            }
+            MicroOp::PushStatus => {
+                let mut status = cpu.registers.status | Flag::Unused as Byte;
+                if self.break_flag {
+                    status |= Flag::Break as Byte;
+                } else {
+                    status &= !(Flag::Break as Byte);
+                }
+                cpu.push_stack(status);
+            }
+            MicroOp::PullStatus => {
+                // the break and unused bits only exist on the stack
+                cpu.registers.status = (cpu.pop_stack() & !(Flag::Break as Byte)) | Flag::Unused as Byte;
+            }
+            MicroOp::PullPcl => {
+                self.data = cpu.pop_stack();
+            }
+            MicroOp::PullPch => {
+                cpu.registers.pc = self.data as Address | (cpu.pop_stack() as Address) << 8;
+            }
+            MicroOp::IncrementPc => {
+                self.fetch_byte(cpu);
+            }
+            MicroOp::JumpHigh => {
+                let high = cpu.read_byte(cpu.registers.pc) as Address;
+                cpu.registers.pc = self.address | high << 8;
+            }
+            MicroOp::AddX => {
+                cpu.read_byte(cpu.registers.pc);
+                self.address = self.address.wrapping_add(cpu.registers.x as Address);
+            }
+            MicroOp::ReadIndirectLow => {
+                self.data = cpu.read_byte(self.address);
+            }
+            MicroOp::ReadIndirectHigh => {
+                let indirect = self.instruction.is_some_and(|instruction| instruction.addressing_mode == AddressingMode::Indirect);
+                // the NMOS parts do not carry into the high byte of the pointer
+                let address = if indirect && self.address & 0xFF == 0xFF && cpu.variant.has_jmp_indirect_bug() {
+                    self.address & !0xFF
+                } else {
+                    self.address.wrapping_add(1)
+                };
+                cpu.registers.pc = self.data as Address | (cpu.read_byte(address) as Address) << 8;
+            }
+            MicroOp::VectorLow => {
+                cpu.set_flag(Flag::Interrupt, true);
+                // the 65C02 also leaves decimal mode when it takes an interrupt
+                if cpu.variant == CpuVariant::Cmos65C02 {
+                    cpu.set_flag(Flag::Decimal, false);
+                }
+                self.data = cpu.read_byte(self.vector);
+            }
+            MicroOp::VectorHigh => {
+                cpu.registers.pc = self.data as Address | (cpu.read_byte(self.vector.wrapping_add(1)) as Address) << 8;
+            }
+            MicroOp::Branch => {
+                let offset = self.fetch_byte(cpu) as i8;
+                self.base = cpu.registers.pc;
+                self.target = cpu.registers.pc.wrapping_add(offset as Address);
+                self.run_instruction(cpu, Operand::Address(self.target));
+            }
+            MicroOp::BranchTaken => {
+                cpu.read_byte(self.base);
+            }
+            MicroOp::BranchFixup => {
+                cpu.read_byte((self.base & 0xFF00) | (self.target & 0x00FF));
+            }
+        }
+    }
+
+    fn fetch_byte(&self, cpu: &mut Cpu6502) -> Byte {
+        let value = cpu.read_byte(cpu.registers.pc);
+        cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
+        value
+    }
+
+    fn index(&mut self, base: Address, index: Byte) {
+        self.address = base.wrapping_add(index as Address);
+        self.crossed = base & 0xFF00 != self.address & 0xFF00;
+    }
+
+    fn index_register(&self, cpu: &Cpu6502) -> Byte {
+        match self.instruction.map(|instruction| instruction.addressing_mode) {
+            Some(AddressingMode::ZeroPageY | AddressingMode::AbsoluteY | AddressingMode::IndirectY) => cpu.registers.y,
+            _ => cpu.registers.x,
+        }
+    }
+
+    fn run_instruction(&self, cpu: &mut Cpu6502, operand: Operand) {
+        if let (Some(instruction), true) = (self.instruction, self.execute) {
+            (instruction.execute)(cpu, operand);
+        }
+    }
+}
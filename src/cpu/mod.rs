@@ -1,7 +1,20 @@
 pub mod addressing_mode;
+pub mod breakpoint;
 pub mod cpu_6502;
+pub mod decimal_mode;
+pub mod decoded_instruction;
+pub mod expression;
 pub mod function;
+pub mod golden;
 pub mod instruction;
 pub mod flag;
+pub mod micro_op;
+pub mod operand;
 pub mod register;
+pub mod rewind;
+pub mod run;
+pub mod save_state;
+pub mod single_step;
+pub mod trace;
+pub mod variant;
 pub mod tests;
\ No newline at end of file
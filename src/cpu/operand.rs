@@ -0,0 +1,36 @@
+//!
+//! File: cpu/operand.rs
+//! The operand module contains the operand of a decoded instruction,
+//! the addressing mode is already resolved so the instructions only see
+//! a value or the effective address they work on.
+//!
+
+use crate::util::types::{Byte, Address};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Operand {
+    // implied instructions have no operand
+    None,
+    // the shifts and rotates working on the accumulator
+    Accumulator,
+    // the byte following the opcode
+    Immediate(Byte),
+    // the effective address, also the target of jumps and branches
+    Address(Address),
+    // the zero page address tested by BBR and BBS and their branch target
+    ZeroPageRelative(Address, Address),
+}
+
+impl Operand {
+    ///
+    /// the effective address of the operand,
+    /// the branch target for BBR and BBS and zero for operands without address
+    ///
+    pub fn address(&self) -> Address {
+        match self {
+            Operand::Address(address) => *address,
+            Operand::ZeroPageRelative(_, target) => *target,
+            _ => 0,
+        }
+    }
+}
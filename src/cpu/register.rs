@@ -1,9 +1,9 @@
-///
-/// File: cpu/register.rs
-/// The register module contains the implementation of the register struct and the implementation of the register functions.
-///
+//!
+//! File: cpu/register.rs
+//! The register module contains the implementation of the register struct and the implementation of the register functions.
+//!
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Registers {
     pub a: u8, // Registre accumulateur
     pub x: u8, // Registre X
@@ -24,4 +24,10 @@ impl Registers {
             status: 0,
         }
     }
+}
+
+impl Default for Registers {
+    fn default() -> Self {
+        Self::new()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,157 @@
+//!
+//! File: cpu/rewind.rs
+//! The rewind module contains the journal used to step the cpu backward.
+//! Every instruction records the registers it started from and the previous value
+//! of every byte it wrote, so undoing it never needs a copy of the whole memory.
+//! The journal is bounded, the oldest instructions are forgotten first.
+//! Side effects of reads on memory mapped devices can not be undone.
+//!
+
+use std::collections::VecDeque;
+
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::register::Registers;
+use crate::util::types::{Byte, Address};
+
+#[derive(Clone)]
+struct JournalEntry {
+    registers: Registers,
+    cycles: u64,
+    nmi_pending: bool,
+    waiting: bool,
+    stopped: bool,
+    // address and previous value of every byte written, in the order of the writes
+    writes: Vec<(Address, Byte)>,
+}
+
+pub(crate) struct Journal {
+    // maximum number of instructions kept
+    capacity: usize,
+    entries: VecDeque<JournalEntry>,
+    // instruction in progress
+    current: Option<JournalEntry>,
+}
+
+impl Journal {
+    fn new(capacity: usize) -> Journal {
+        Journal {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+            current: None,
+        }
+    }
+
+    ///
+    /// remember the previous value of a byte about to be written
+    ///
+    pub(crate) fn record_write(&mut self, address: Address, previous: Byte) {
+        if let Some(entry) = self.current.as_mut() {
+            entry.writes.push((address, previous));
+        }
+    }
+}
+
+impl Cpu6502 {
+    ///
+    /// start recording the last `capacity` instructions so they can be undone,
+    /// the history is cleared when the capacity changes
+    ///
+    pub fn enable_rewind(&mut self, capacity: usize) {
+        self.journal = Some(Journal::new(capacity));
+    }
+
+    pub fn disable_rewind(&mut self) {
+        self.journal = None;
+    }
+
+    ///
+    /// number of instructions that can be undone
+    ///
+    pub fn rewind_depth(&self) -> usize {
+        self.journal.as_ref().map_or(0, |journal| journal.entries.len())
+    }
+
+    ///
+    /// undo the last instruction, returns false if the history is empty
+    ///
+    pub fn step_back(&mut self) -> bool {
+        let entry = match self.journal.as_mut().and_then(|journal| journal.entries.pop_back()) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        // undone from the last write so a byte written twice gets its oldest value back
+        for &(address, previous) in entry.writes.iter().rev() {
+            self.memory.write(address, previous);
+        }
+        self.registers = entry.registers;
+        self.cycles = entry.cycles;
+        self.nmi_pending = entry.nmi_pending;
+        self.waiting = entry.waiting;
+        self.stopped = entry.stopped;
+        true
+    }
+
+    ///
+    /// undo whole instructions until at least `cycles` cycles are undone or the history is empty,
+    /// returns the number of cycles undone
+    ///
+    pub fn rewind_cycles(&mut self, cycles: u64) -> u64 {
+        let end = self.cycles;
+        while end - self.cycles < cycles && self.step_back() {}
+        end - self.cycles
+    }
+
+    ///
+    /// open the journal entry of the instruction about to run
+    ///
+    pub(crate) fn journal_begin(&mut self) {
+        let entry = JournalEntry {
+            registers: self.registers,
+            cycles: self.cycles,
+            nmi_pending: self.nmi_pending,
+            waiting: self.waiting,
+            stopped: self.stopped,
+            writes: Vec::new(),
+        };
+        if let Some(journal) = self.journal.as_mut() {
+            journal.current = Some(entry);
+        }
+    }
+
+    ///
+    /// close the journal entry of the instruction that just ran,
+    /// nothing is kept when the cpu did not change
+    ///
+    pub(crate) fn journal_end(&mut self) {
+        let journal = match self.journal.as_mut() {
+            Some(journal) => journal,
+            None => return,
+        };
+        let entry = match journal.current.take() {
+            Some(entry) => entry,
+            None => return,
+        };
+        let unchanged = entry.writes.is_empty()
+            && entry.registers == self.registers
+            && entry.cycles == self.cycles
+            && entry.waiting == self.waiting
+            && entry.stopped == self.stopped;
+        if unchanged || journal.capacity == 0 {
+            return;
+        }
+        if journal.entries.len() == journal.capacity {
+            journal.entries.pop_front();
+        }
+        journal.entries.push_back(entry);
+    }
+
+    ///
+    /// forget the history, after a reset or a restored state
+    ///
+    pub(crate) fn journal_clear(&mut self) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.entries.clear();
+            journal.current = None;
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//!
+//! File: cpu/run.rs
+//! The run module contains the run loops built on top of `Cpu6502::execute_instruction`.
+//! Every loop runs whole instructions and returns the reason it stopped,
+//! so test harnesses and debuggers never have to write their own loop.
+//! The breakpoints and watchpoints of the cpu are checked after every instruction.
+//!
+
+use crate::cpu::cpu_6502::{Cpu6502, ExecutionState};
+use crate::error::EmulatorError;
+use crate::util::types::{Address, Word};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StopReason {
+    // the cycle budget of `run_cycles` is spent
+    CyclesElapsed,
+    // `run_instructions` executed all its instructions
+    InstructionsExecuted,
+    // the predicate of `run_until` returned true
+    Condition,
+    // `step_over` reached the instruction following the one it stepped over
+    Stepped,
+    // `step_out` executed the RTS leaving the current subroutine
+    Returned,
+    // the cpu stopped on STP or KIL, or on BRK with `BrkBehavior::Stop`
+    Stopped,
+    // the cpu waits for an interrupt after WAI and nothing is signaled
+    Waiting,
+    // the breakpoint with this id is reached, the instruction at its address is not executed yet
+    Breakpoint(usize),
+    // the last instruction accessed the range of the watchpoint with this id
+    Watchpoint { id: usize, address: Address, write: bool },
+}
+
+impl Cpu6502 {
+    ///
+    /// run whole instructions until at least `cycles` cycles have elapsed,
+    /// the last instruction may go past the budget,
+    /// waiting for an interrupt uses up the budget one cycle at a time
+    ///
+    pub fn run_cycles(&mut self, cycles: u64) -> Result<StopReason, EmulatorError> {
+        let end = self.cycles + cycles;
+        while self.cycles < end {
+            match self.step()? {
+                None | Some(StopReason::Waiting) => {}
+                Some(reason) => return Ok(reason),
+            }
+        }
+        Ok(StopReason::CyclesElapsed)
+    }
+
+    ///
+    /// run `count` instructions, the entry of a hardware interrupt counts as one instruction
+    ///
+    pub fn run_instructions(&mut self, count: usize) -> Result<StopReason, EmulatorError> {
+        for _ in 0..count {
+            if let Some(reason) = self.step()? {
+                return Ok(reason);
+            }
+        }
+        Ok(StopReason::InstructionsExecuted)
+    }
+
+    ///
+    /// run instructions until `predicate` returns true, it is checked after every instruction
+    ///
+    /// # Example
+    /// ```
+    /// use emul::cpu::cpu_6502::Cpu6502;
+    /// use emul::cpu::run::StopReason;
+    /// use emul::memory::ram::Ram;
+    ///
+    /// let mut cpu = Cpu6502::new(Ram::new());
+    /// // INX ; JMP $0000
+    /// cpu.memory.load(&[0xE8, 0x4C, 0x00, 0x00], 0x0000).unwrap();
+    /// let reason = cpu.run_until(|cpu| cpu.registers.x == 10).unwrap();
+    /// assert_eq!(reason, StopReason::Condition);
+    /// ```
+    pub fn run_until<F: FnMut(&Cpu6502) -> bool>(&mut self, mut predicate: F) -> Result<StopReason, EmulatorError> {
+        loop {
+            if let Some(reason) = self.step()? {
+                return Ok(reason);
+            }
+            if predicate(self) {
+                return Ok(StopReason::Condition);
+            }
+        }
+    }
+
+    ///
+    /// run the instruction at the program counter, a JSR runs until the subroutine returns
+    /// to the following instruction with the stack back where it was
+    ///
+    pub fn step_over(&mut self) -> Result<StopReason, EmulatorError> {
+        let pc = self.registers.pc;
+        let sp = self.registers.sp;
+        let opcode = self.memory.peek(pc);
+        let instruction = self.variant.instructions()[opcode as usize];
+        if let Some(reason) = self.step()? {
+            return Ok(reason);
+        }
+        if instruction.name != "JSR" {
+            return Ok(StopReason::Stepped);
+        }
+        let return_address = pc.wrapping_add(instruction.length as Word);
+        match self.run_until(|cpu| cpu.registers.pc == return_address && cpu.registers.sp == sp)? {
+            StopReason::Condition => Ok(StopReason::Stepped),
+            reason => Ok(reason),
+        }
+    }
+
+    ///
+    /// run until the RTS leaving the current subroutine,
+    /// the RTS of nested subroutines are recognized by the stack pointer staying below its starting value
+    ///
+    pub fn step_out(&mut self) -> Result<StopReason, EmulatorError> {
+        let sp = self.registers.sp;
+        loop {
+            let opcode = self.memory.peek(self.registers.pc);
+            let is_rts = self.variant.instructions()[opcode as usize].name == "RTS";
+            if let Some(reason) = self.step()? {
+                return Ok(reason);
+            }
+            // an interrupt taken instead of the RTS pushes on the stack so it never matches
+            if is_rts && (self.registers.sp.wrapping_sub(sp) as i8) > 0 {
+                return Ok(StopReason::Returned);
+            }
+        }
+    }
+
+    ///
+    /// execute one instruction, returns the reason to stop if the cpu can not go on
+    /// or if a breakpoint fired
+    ///
+    fn step(&mut self) -> Result<Option<StopReason>, EmulatorError> {
+        let address = self.registers.pc;
+        let length = self.variant.instructions()[self.memory.peek(address) as usize].length;
+        match self.execute_instruction()? {
+            (ExecutionState::Running, _) => Ok(self.breakpoints_end(address, length)),
+            (ExecutionState::Waiting, _) => Ok(Some(StopReason::Waiting)),
+            (ExecutionState::Stopped, _) => Ok(Some(StopReason::Stopped)),
+        }
+    }
+}
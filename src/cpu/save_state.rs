@@ -0,0 +1,150 @@
+//!
+//! File: cpu/save_state.rs
+//! The save state module contains the snapshot of the whole machine,
+//! the cpu registers and internal state followed by the state of the bus.
+//! The state starts with a magic and a version number, a state written by
+//! another version of the format is rejected instead of being misread.
+//!
+
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::decimal_mode::DecimalMode;
+use crate::cpu::register::Registers;
+use crate::cpu::variant::CpuVariant;
+use crate::error::EmulatorError;
+use crate::util::state::{invalid_state, StateReader, StateWriter};
+use crate::util::types::Byte;
+
+// first bytes of every save state
+const MAGIC: &[Byte; 4] = b"E65S";
+
+// version of the format, to be incremented whenever the layout changes
+pub const SAVE_STATE_VERSION: u16 = 1;
+
+impl Cpu6502 {
+    ///
+    /// snapshot the cpu and the bus, the state can only be taken between two instructions
+    ///
+    pub fn save_state(&self) -> Result<Vec<Byte>, EmulatorError> {
+        if self.in_instruction() {
+            return Err(invalid_state("an instruction is in progress"));
+        }
+        let mut writer = StateWriter::new();
+        writer.write_bytes(MAGIC);
+        writer.write_word(SAVE_STATE_VERSION);
+
+        writer.write_byte(variant_to_byte(self.variant));
+        writer.write_byte(decimal_mode_to_byte(self.decimal_mode));
+        writer.write_byte(self.magic_constant);
+        writer.write_byte(self.registers.a);
+        writer.write_byte(self.registers.x);
+        writer.write_byte(self.registers.y);
+        writer.write_byte(self.registers.sp);
+        writer.write_word(self.registers.pc);
+        writer.write_byte(self.registers.status);
+        writer.write_u64(self.cycles);
+        writer.write_bool(self.irq_line);
+        writer.write_bool(self.nmi_pending);
+        writer.write_bool(self.waiting);
+        writer.write_bool(self.stopped);
+
+        self.memory.save_state(&mut writer);
+        Ok(writer.into_bytes())
+    }
+
+    ///
+    /// restore a snapshot taken by `save_state`,
+    /// the bus must be built with the same components as the one that was saved,
+    /// the cpu and the bus are left untouched when the state is rejected
+    ///
+    pub fn load_state(&mut self, data: &[Byte]) -> Result<(), EmulatorError> {
+        let mut reader = StateReader::new(data);
+        if reader.read_bytes(MAGIC.len())? != MAGIC {
+            return Err(invalid_state("not a save state"));
+        }
+        let version = reader.read_word()?;
+        if version != SAVE_STATE_VERSION {
+            return Err(EmulatorError::SaveStateVersion { version });
+        }
+
+        let variant = variant_from_byte(reader.read_byte()?)?;
+        let decimal_mode = decimal_mode_from_byte(reader.read_byte()?)?;
+        let magic_constant = reader.read_byte()?;
+        let registers = Registers {
+            a: reader.read_byte()?,
+            x: reader.read_byte()?,
+            y: reader.read_byte()?,
+            sp: reader.read_byte()?,
+            pc: reader.read_word()?,
+            status: reader.read_byte()?,
+        };
+        let cycles = reader.read_u64()?;
+        let irq_line = reader.read_bool()?;
+        let nmi_pending = reader.read_bool()?;
+        let waiting = reader.read_bool()?;
+        let stopped = reader.read_bool()?;
+
+        // the components restore themselves, their previous state is put back on failure
+        let mut backup = StateWriter::new();
+        self.memory.save_state(&mut backup);
+        let loaded = self.memory.load_state(&mut reader).and_then(|()| {
+            if reader.is_empty() {
+                Ok(())
+            } else {
+                Err(invalid_state("unexpected data after the bus state"))
+            }
+        });
+        if let Err(error) = loaded {
+            self.memory.load_state(&mut StateReader::new(&backup.into_bytes()))?;
+            return Err(error);
+        }
+
+        self.variant = variant;
+        self.decimal_mode = decimal_mode;
+        self.magic_constant = magic_constant;
+        self.registers = registers;
+        self.cycles = cycles;
+        self.irq_line = irq_line;
+        self.nmi_pending = nmi_pending;
+        self.waiting = waiting;
+        self.stopped = stopped;
+        self.extra_cycles = 0;
+        self.journal_clear();
+        Ok(())
+    }
+}
+
+fn variant_to_byte(variant: CpuVariant) -> Byte {
+    match variant {
+        CpuVariant::Nmos6502 => 0,
+        CpuVariant::Cmos65C02 => 1,
+        CpuVariant::Ricoh2A03 => 2,
+        CpuVariant::RevA => 3,
+    }
+}
+
+fn variant_from_byte(value: Byte) -> Result<CpuVariant, EmulatorError> {
+    match value {
+        0 => Ok(CpuVariant::Nmos6502),
+        1 => Ok(CpuVariant::Cmos65C02),
+        2 => Ok(CpuVariant::Ricoh2A03),
+        3 => Ok(CpuVariant::RevA),
+        _ => Err(invalid_state(&format!("unknown cpu variant {}", value))),
+    }
+}
+
+fn decimal_mode_to_byte(mode: DecimalMode) -> Byte {
+    match mode {
+        DecimalMode::Nmos => 0,
+        DecimalMode::Cmos => 1,
+        DecimalMode::Disabled => 2,
+    }
+}
+
+fn decimal_mode_from_byte(value: Byte) -> Result<DecimalMode, EmulatorError> {
+    match value {
+        0 => Ok(DecimalMode::Nmos),
+        1 => Ok(DecimalMode::Cmos),
+        2 => Ok(DecimalMode::Disabled),
+        _ => Err(invalid_state(&format!("unknown decimal mode {}", value))),
+    }
+}
@@ -0,0 +1,348 @@
+//!
+//! File: cpu/single_step.rs
+//! The single step module contains the runner of the per-opcode JSON test vectors
+//! published by the community. A file holds the vectors of one opcode, named after it
+//! in lower case hex (`a9.json`), and every vector gives the registers and the RAM
+//! before and after one instruction, plus an optional log of the bus accesses.
+//! Each vector runs on a fresh cpu one cycle at a time, so the log is compared
+//! access by access, and the differences are reported grouped by opcode and field.
+//!
+//! # See
+//! * [https://github.com/SingleStepTests/ProcessorTests](https://github.com/SingleStepTests/ProcessorTests)
+//!
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::cpu::cpu_6502::{Cpu6502, ExecutionState};
+use crate::cpu::variant::CpuVariant;
+use crate::error::EmulatorError;
+use crate::memory::bus::Bus;
+use crate::memory::ram::Ram;
+use crate::util::json::{self, JsonValue};
+use crate::util::types::{Byte, Address};
+
+// no instruction or interrupt takes more cycles, a vector running longer is a failure
+const MAX_TICKS: usize = 16;
+
+///
+/// registers and RAM content on one side of a test vector
+///
+#[derive(Clone, PartialEq, Debug)]
+pub struct VectorState {
+    pub pc: Address,
+    pub s: Byte,
+    pub a: Byte,
+    pub x: Byte,
+    pub y: Byte,
+    pub p: Byte,
+    pub ram: Vec<(Address, Byte)>,
+}
+
+///
+/// one access of the bus log
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BusCycle {
+    pub address: Address,
+    pub data: Byte,
+    pub write: bool,
+}
+
+impl fmt::Display for BusCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.write { "write" } else { "read" };
+        write!(f, "{} ${:04X} ${:02X}", kind, self.address, self.data)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TestVector {
+    pub name: String,
+    pub initial: VectorState,
+    pub expected: VectorState,
+    // None when the vector has no bus log, the accesses are not compared then
+    pub cycles: Option<Vec<BusCycle>>,
+}
+
+///
+/// one field of a vector that does not match
+///
+#[derive(Clone, PartialEq, Debug)]
+pub struct VectorFailure {
+    pub name: String,
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+///
+/// result of a run, the failures are grouped by opcode then by field
+///
+#[derive(Default, Debug)]
+pub struct VectorReport {
+    pub passed: usize,
+    pub failed: usize,
+    // opcodes of the instruction table without a vector file
+    pub missing: Vec<Byte>,
+    pub failures: BTreeMap<Byte, BTreeMap<String, Vec<VectorFailure>>>,
+}
+
+impl VectorReport {
+    pub fn is_success(&self) -> bool {
+        self.failed == 0
+    }
+
+    ///
+    /// run the vectors of one opcode and add their results to the report
+    ///
+    pub fn check_opcode(&mut self, variant: CpuVariant, opcode: Byte, vectors: &[TestVector]) {
+        for vector in vectors {
+            let failures = run_vector(variant, vector);
+            if failures.is_empty() {
+                self.passed += 1;
+                continue;
+            }
+            self.failed += 1;
+            let fields = self.failures.entry(opcode).or_default();
+            for failure in failures {
+                fields.entry(failure.field.clone()).or_default().push(failure);
+            }
+        }
+    }
+}
+
+impl fmt::Display for VectorReport {
+    ///
+    /// one line per opcode and field with the number of failures and the first of them
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} passed, {} failed", self.passed, self.failed)?;
+        if !self.missing.is_empty() {
+            let missing: Vec<String> = self.missing.iter().map(|opcode| format!("{:02X}", opcode)).collect();
+            writeln!(f, "no vectors for {}", missing.join(" "))?;
+        }
+        for (opcode, fields) in self.failures.iter() {
+            for (field, failures) in fields.iter() {
+                let first = &failures[0];
+                writeln!(
+                    f,
+                    "{:02X} {}: {} failures, first \"{}\" expected {} got {}",
+                    opcode,
+                    field,
+                    failures.len(),
+                    first.name,
+                    first.expected,
+                    first.actual
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// parse the content of a vector file, an array of vectors
+///
+pub fn parse_vectors(text: &str) -> Result<Vec<TestVector>, EmulatorError> {
+    let document = json::parse(text)?;
+    let vectors = document.as_array().ok_or_else(|| invalid_vector("the file is not an array"))?;
+    vectors.iter().map(parse_vector).collect()
+}
+
+fn invalid_vector(message: &str) -> EmulatorError {
+    EmulatorError::InvalidTestVector { message: message.to_owned() }
+}
+
+fn parse_vector(value: &JsonValue) -> Result<TestVector, EmulatorError> {
+    let name = value
+        .get("name")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| invalid_vector("missing name"))?
+        .to_owned();
+    let initial = parse_state(value.get("initial"), &name)?;
+    let expected = parse_state(value.get("final"), &name)?;
+    let cycles = match value.get("cycles") {
+        Some(cycles) => Some(parse_cycles(cycles, &name)?),
+        None => None,
+    };
+    Ok(TestVector { name, initial, expected, cycles })
+}
+
+fn number<T: TryFrom<u64>>(value: Option<&JsonValue>, name: &str, field: &str) -> Result<T, EmulatorError> {
+    value
+        .and_then(JsonValue::as_u64)
+        .and_then(|number| T::try_from(number).ok())
+        .ok_or_else(|| invalid_vector(&format!("{}: bad or missing {}", name, field)))
+}
+
+fn parse_state(value: Option<&JsonValue>, name: &str) -> Result<VectorState, EmulatorError> {
+    let value = value.ok_or_else(|| invalid_vector(&format!("{}: missing state", name)))?;
+    let ram = value
+        .get("ram")
+        .and_then(JsonValue::as_array)
+        .ok_or_else(|| invalid_vector(&format!("{}: missing ram", name)))?
+        .iter()
+        .map(|entry| {
+            let pair = entry.as_array().unwrap_or_default();
+            Ok((number(pair.first(), name, "ram address")?, number(pair.get(1), name, "ram value")?))
+        })
+        .collect::<Result<_, EmulatorError>>()?;
+    Ok(VectorState {
+        pc: number(value.get("pc"), name, "pc")?,
+        s: number(value.get("s"), name, "s")?,
+        a: number(value.get("a"), name, "a")?,
+        x: number(value.get("x"), name, "x")?,
+        y: number(value.get("y"), name, "y")?,
+        p: number(value.get("p"), name, "p")?,
+        ram,
+    })
+}
+
+fn parse_cycles(value: &JsonValue, name: &str) -> Result<Vec<BusCycle>, EmulatorError> {
+    let cycles = value.as_array().ok_or_else(|| invalid_vector(&format!("{}: bad cycles", name)))?;
+    cycles
+        .iter()
+        .map(|cycle| {
+            let cycle = cycle.as_array().unwrap_or_default();
+            let write = match cycle.get(2).and_then(JsonValue::as_str) {
+                Some("read") => false,
+                Some("write") => true,
+                _ => return Err(invalid_vector(&format!("{}: bad cycle kind", name))),
+            };
+            Ok(BusCycle {
+                address: number(cycle.first(), name, "cycle address")?,
+                data: number(cycle.get(1), name, "cycle value")?,
+                write,
+            })
+        })
+        .collect()
+}
+
+///
+/// RAM logging every access of the cpu
+///
+struct LoggingRam {
+    ram: Ram,
+    log: Rc<RefCell<Vec<BusCycle>>>,
+}
+
+impl Bus for LoggingRam {
+    fn read(&mut self, address: Address) -> Byte {
+        let data = self.ram.read(address);
+        self.log.borrow_mut().push(BusCycle { address, data, write: false });
+        data
+    }
+
+    fn write(&mut self, address: Address, data: Byte) {
+        self.ram.write(address, data);
+        self.log.borrow_mut().push(BusCycle { address, data, write: true });
+    }
+
+    fn peek(&self, address: Address) -> Byte {
+        self.ram.peek(address)
+    }
+}
+
+///
+/// run one vector on a fresh cpu, returns the fields that differ from the final state
+///
+pub fn run_vector(variant: CpuVariant, vector: &TestVector) -> Vec<VectorFailure> {
+    let mut ram = Ram::new();
+    for &(address, data) in vector.initial.ram.iter() {
+        ram.write(address, data);
+    }
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut cpu = Cpu6502::with_variant(LoggingRam { ram, log: log.clone() }, variant);
+    cpu.registers.pc = vector.initial.pc;
+    cpu.registers.sp = vector.initial.s;
+    cpu.registers.a = vector.initial.a;
+    cpu.registers.x = vector.initial.x;
+    cpu.registers.y = vector.initial.y;
+    cpu.registers.status = vector.initial.p;
+
+    let mut failures = Vec::new();
+    let mut fail = |field: &str, expected: String, actual: String| {
+        failures.push(VectorFailure {
+            name: vector.name.clone(),
+            field: field.to_owned(),
+            expected,
+            actual,
+        });
+    };
+
+    for _ in 0..MAX_TICKS {
+        match cpu.tick() {
+            Ok(ExecutionState::Stopped) => break,
+            Ok(_) if !cpu.in_instruction() => break,
+            Ok(_) => {}
+            Err(error) => {
+                fail("error", "none".to_owned(), error.to_string());
+                break;
+            }
+        }
+    }
+    if cpu.in_instruction() {
+        fail("cycles", format!("at most {} cycles", MAX_TICKS), "more".to_owned());
+    }
+
+    let expected = &vector.expected;
+    let registers = [
+        ("pc", expected.pc, cpu.registers.pc),
+        ("s", expected.s as Address, cpu.registers.sp as Address),
+        ("a", expected.a as Address, cpu.registers.a as Address),
+        ("x", expected.x as Address, cpu.registers.x as Address),
+        ("y", expected.y as Address, cpu.registers.y as Address),
+        ("p", expected.p as Address, cpu.registers.status as Address),
+    ];
+    for (field, expected, actual) in registers {
+        if expected != actual {
+            fail(field, format!("${:02X}", expected), format!("${:02X}", actual));
+        }
+    }
+    for &(address, data) in expected.ram.iter() {
+        let actual = cpu.memory.peek(address);
+        if actual != data {
+            fail("ram", format!("${:04X}=${:02X}", address, data), format!("${:04X}=${:02X}", address, actual));
+        }
+    }
+
+    if let Some(cycles) = &vector.cycles {
+        let log = log.borrow();
+        if cycles.len() != log.len() {
+            fail("cycles", cycles.len().to_string(), log.len().to_string());
+        }
+        if let Some((index, (expected, actual))) =
+            cycles.iter().zip(log.iter()).enumerate().find(|(_, (expected, actual))| expected != actual)
+        {
+            fail("bus", format!("{}: {}", index, expected), format!("{}: {}", index, actual));
+        }
+    }
+    failures
+}
+
+///
+/// run the vector files of a directory for every opcode of the instruction table of the variant,
+/// the opcodes without a file are listed as missing in the report
+///
+pub fn run_directory(directory: &Path, variant: CpuVariant) -> Result<VectorReport, EmulatorError> {
+    let mut report = VectorReport::default();
+    for instruction in variant.instructions().iter() {
+        let path = directory.join(format!("{:02x}.json", instruction.opcode));
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                report.missing.push(instruction.opcode);
+                continue;
+            }
+            Err(error) => return Err(error.into()),
+        };
+        report.check_opcode(variant, instruction.opcode, &parse_vectors(&text)?);
+    }
+    Ok(report)
+}
@@ -1,4 +1,6 @@
 use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::decimal_mode::DecimalMode;
 use crate::cpu::flag::Flag;
 use crate::cpu::function::adc;
 use super::*;
@@ -11,7 +13,7 @@ fn test_adc_simple() {
     cpu.registers.a = 0x01;
     cpu.memory.write(0x0001, 0x01);
     cpu.set_flag(Flag::Carry, true);
-    adc(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, adc, AddressingMode::Immediate);
     assert_eq!(cpu.registers.a, 0x03);
     assert_eq!(cpu.get_flag(Flag::Carry), false);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
@@ -26,7 +28,7 @@ fn test_adc_overflow() {
     cpu.registers.a = 0x7F;
     cpu.memory.write(0x0001, 0x01);
     cpu.set_flag(Flag::Carry, true);
-    adc(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, adc, AddressingMode::Immediate);
     assert_eq!(cpu.registers.a, 0x81);
     assert_eq!(cpu.get_flag(Flag::Carry), false);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
@@ -41,7 +43,7 @@ fn test_adc_carry() {
     cpu.registers.a = 0xFF;
     cpu.memory.write(0x0001, 0x01);
     cpu.set_flag(Flag::Carry, true);
-    adc(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, adc, AddressingMode::Immediate);
     assert_eq!(cpu.registers.a, 0x01);
     assert_eq!(cpu.get_flag(Flag::Carry), true);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
@@ -49,6 +51,21 @@ fn test_adc_carry() {
     assert_eq!(cpu.get_flag(Flag::Overflow), false);
 }
 
+#[test]
+fn test_adc_carry_with_carry_in() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0xFF;
+    cpu.memory.write(0x0001, 0xFF);
+    cpu.set_flag(Flag::Carry, true);
+    run(&mut cpu, adc, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.a, 0xFF);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+    assert_eq!(cpu.get_flag(Flag::Overflow), false);
+}
+
 #[test]
 fn test_adc_zero() {
     let mut cpu = get_cpu();
@@ -56,7 +73,7 @@ fn test_adc_zero() {
     cpu.registers.a = 0x00;
     cpu.memory.write(0x0001, 0x00);
     cpu.set_flag(Flag::Carry, true);
-    adc(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, adc, AddressingMode::Immediate);
     assert_eq!(cpu.registers.a, 0x01);
     assert_eq!(cpu.get_flag(Flag::Carry), false);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
@@ -71,7 +88,7 @@ fn test_adc_negative() {
     cpu.registers.a = 0x80;
     cpu.memory.write(0x0001, 0x01);
     cpu.set_flag(Flag::Carry, true);
-    adc(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, adc, AddressingMode::Immediate);
     assert_eq!(cpu.registers.a, 0x82);
     assert_eq!(cpu.get_flag(Flag::Carry), false);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
@@ -91,13 +108,84 @@ fn test_adc_random() {
         cpu.registers.a = a;
         cpu.memory.write(0x0001, b);
         cpu.set_flag(Flag::Carry, c);
-        adc(&mut cpu, AddressingMode::Immediate);
+        run(&mut cpu, adc, AddressingMode::Immediate);
 
         let expected = a.wrapping_add(b).wrapping_add(c as u8);
         assert_eq!(cpu.registers.a, expected);
-        assert_eq!(cpu.get_flag(Flag::Carry), expected < a || expected < b);
+        assert_eq!(cpu.get_flag(Flag::Carry), a as u16 + b as u16 + c as u16 > 0xFF);
         assert_eq!(cpu.get_flag(Flag::Zero), expected == 0);
         assert_eq!(cpu.get_flag(Flag::Negative), expected & 0x80 != 0);
         assert_eq!(cpu.get_flag(Flag::Overflow), (a ^ b) & 0x80 == 0 && (a ^ expected) & 0x80 != 0);
     }
-}
\ No newline at end of file
+}
+
+fn adc_decimal(decimal_mode: DecimalMode, a: u8, b: u8, carry: bool) -> Cpu6502 {
+    let mut cpu = get_cpu();
+
+    cpu.decimal_mode = decimal_mode;
+    cpu.registers.a = a;
+    cpu.memory.write(0x0001, b);
+    cpu.set_flag(Flag::Decimal, true);
+    cpu.set_flag(Flag::Carry, carry);
+    run(&mut cpu, adc, AddressingMode::Immediate);
+    cpu
+}
+
+#[test]
+fn test_adc_decimal_simple() {
+    let cpu = adc_decimal(DecimalMode::Nmos, 0x12, 0x34, false);
+    assert_eq!(cpu.registers.a, 0x46);
+    assert_eq!(cpu.get_flag(Flag::Carry), false);
+}
+
+#[test]
+fn test_adc_decimal_carry() {
+    let cpu = adc_decimal(DecimalMode::Nmos, 0x58, 0x46, true);
+    assert_eq!(cpu.registers.a, 0x05);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+}
+
+#[test]
+fn test_adc_decimal_overflow() {
+    let cpu = adc_decimal(DecimalMode::Nmos, 0x81, 0x92, false);
+    assert_eq!(cpu.registers.a, 0x73);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Overflow), true);
+}
+
+#[test]
+fn test_adc_decimal_nmos_flags() {
+    // 99 + 01 = 00, the NMOS flags come from the binary and intermediate results
+    let cpu = adc_decimal(DecimalMode::Nmos, 0x99, 0x01, false);
+    assert_eq!(cpu.registers.a, 0x00);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+}
+
+#[test]
+fn test_adc_decimal_cmos_flags() {
+    let cpu = adc_decimal(DecimalMode::Cmos, 0x99, 0x01, false);
+    assert_eq!(cpu.registers.a, 0x00);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), true);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+}
+
+#[test]
+fn test_adc_decimal_disabled() {
+    let cpu = adc_decimal(DecimalMode::Disabled, 0x09, 0x01, false);
+    assert_eq!(cpu.registers.a, 0x0A);
+}
+
+#[test]
+fn test_adc_decimal_cmos_cycles() {
+    let mut cpu = get_cpu();
+
+    cpu.decimal_mode = DecimalMode::Cmos;
+    cpu.memory.write(0x0000, 0x69);
+    cpu.memory.write(0x0001, 0x01);
+    cpu.set_flag(Flag::Decimal, true);
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cycles, 3);
+}
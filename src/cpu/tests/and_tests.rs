@@ -1,7 +1,7 @@
 use crate::cpu::addressing_mode::AddressingMode;
 use crate::cpu::flag::Flag;
 use crate::cpu::function::and;
-use crate::cpu::tests::get_cpu;
+use crate::cpu::tests::{get_cpu, run};
 
 #[test]
 fn test_and_immediate() {
@@ -10,7 +10,7 @@ fn test_and_immediate() {
     let value = 0b1010_1101;
 
     cpu.memory.write(0x0001, value);
-    and(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, and, AddressingMode::Immediate);
 
     let expected = 0b0100_1010 & 0b1010_1101;
     assert_eq!(cpu.registers.a, expected);
@@ -26,7 +26,7 @@ fn test_and_zero_page() {
 
     cpu.memory.write(0x0020, value);
     cpu.memory.write(0x0001, 0x20);
-    and(&mut cpu, AddressingMode::ZeroPage);
+    run(&mut cpu, and, AddressingMode::ZeroPage);
 
     let expected = 0b1100_1010 & 0b1010_1101;
     assert_eq!(cpu.registers.a, expected);
@@ -43,7 +43,7 @@ fn test_and_zero_page_x() {
 
     cpu.memory.write(0x0024, value);
     cpu.memory.write(0x0001, 0x20);
-    and(&mut cpu, AddressingMode::ZeroPageX);
+    run(&mut cpu, and, AddressingMode::ZeroPageX);
 
     let expected = 0b1100_1010 & 0b1010_1101;
     assert_eq!(cpu.registers.a, expected);
@@ -60,7 +60,7 @@ fn test_and_absolute() {
     cpu.memory.write(0x1000, value);
     cpu.memory.write(0x0001, 0x00);
     cpu.memory.write(0x0002, 0x10);
-    and(&mut cpu, AddressingMode::Absolute);
+    run(&mut cpu, and, AddressingMode::Absolute);
 
     let expected = 0b1100_1010 & 0b0010_1101;
     assert_eq!(cpu.registers.a, expected);
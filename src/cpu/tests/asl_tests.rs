@@ -1,6 +1,6 @@
 use crate::cpu::addressing_mode::AddressingMode;
 use crate::cpu::function::asl;
-use crate::cpu::tests::get_cpu;
+use crate::cpu::tests::{get_cpu, run};
 use crate::cpu::flag::Flag;
 
 #[test]
@@ -8,7 +8,7 @@ fn test_asl_accumulator() {
     let mut cpu = get_cpu();
     cpu.registers.a = 0b0101_1010;
 
-    asl(&mut cpu, AddressingMode::Accumulator);
+    run(&mut cpu, asl, AddressingMode::Accumulator);
 
     let expected = 0b1011_0100;
     assert_eq!(cpu.registers.a, expected);
@@ -24,7 +24,7 @@ fn test_asl_zero_page() {
 
     cpu.memory.write(0x0020, value);
     cpu.memory.write(0x0001, 0x20);
-    asl(&mut cpu, AddressingMode::ZeroPage);
+    run(&mut cpu, asl, AddressingMode::ZeroPage);
 
     let expected = 0b1011_0100;
     assert_eq!(cpu.read_byte(0x0020), expected);
@@ -41,7 +41,7 @@ fn test_asl_zero_page_x() {
 
     cpu.memory.write(0x0024, value);
     cpu.memory.write(0x0001, 0x20);
-    asl(&mut cpu, AddressingMode::ZeroPageX);
+    run(&mut cpu, asl, AddressingMode::ZeroPageX);
 
     let expected = 0b1011_0100;
     assert_eq!(cpu.read_byte(0x0024), expected);
@@ -58,7 +58,7 @@ fn test_asl_absolute() {
     cpu.memory.write(0x1000, value);
     cpu.memory.write(0x0001, 0x00);
     cpu.memory.write(0x0002, 0x10);
-    asl(&mut cpu, AddressingMode::Absolute);
+    run(&mut cpu, asl, AddressingMode::Absolute);
 
     let expected = 0b1011_0100;
     assert_eq!(cpu.read_byte(0x1000), expected);
@@ -0,0 +1,53 @@
+use crate::cpu::flag::Flag;
+use super::*;
+
+#[test]
+fn test_bne_not_taken() {
+    let mut cpu = get_cpu();
+
+    cpu.memory.write(0x0000, 0xD0);
+    cpu.memory.write(0x0001, 0x02);
+    cpu.set_flag(Flag::Zero, true);
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0002);
+    assert_eq!(cycles, 2);
+}
+
+#[test]
+fn test_bne_taken() {
+    let mut cpu = get_cpu();
+
+    cpu.memory.write(0x0000, 0xD0);
+    cpu.memory.write(0x0001, 0x02);
+    cpu.set_flag(Flag::Zero, false);
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0004);
+    assert_eq!(cycles, 3);
+}
+
+#[test]
+fn test_bne_taken_backward() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.pc = 0x0010;
+    cpu.memory.write(0x0010, 0xD0);
+    cpu.memory.write(0x0011, 0xFE);
+    cpu.set_flag(Flag::Zero, false);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0010);
+}
+
+#[test]
+fn test_bne_taken_page_crossed() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.pc = 0x00F0;
+    cpu.memory.write(0x00F0, 0xD0);
+    cpu.memory.write(0x00F1, 0x20);
+    cpu.set_flag(Flag::Zero, false);
+    let before = cpu.cycles;
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0112);
+    assert_eq!(cycles, 4);
+    assert_eq!(cpu.cycles - before, 4);
+}
@@ -0,0 +1,176 @@
+use crate::cpu::breakpoint::{Access, BreakpointKind};
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::expression::Expression;
+use crate::cpu::run::StopReason;
+use super::*;
+
+// LDX #$00 ; INX ; STX $10 ; LDA $20 ; CPX #$05 ; BNE $0202 ; KIL
+const PROGRAM: [u8; 14] = [0xA2, 0x00, 0xE8, 0x86, 0x10, 0xA5, 0x20, 0xE0, 0x05, 0xD0, 0xF7, 0x02, 0x00, 0x00];
+
+fn get_breakpoint_cpu() -> Cpu6502 {
+    let mut cpu = get_cpu();
+    cpu.memory.load(&PROGRAM, 0x0200).unwrap();
+    cpu.registers.pc = 0x0200;
+    cpu
+}
+
+#[test]
+fn test_breakpoint_stops_before_the_instruction() {
+    let mut cpu = get_breakpoint_cpu();
+    let id = cpu.add_breakpoint(BreakpointKind::Execution(0x0203));
+
+    assert_eq!(cpu.run_cycles(1000).unwrap(), StopReason::Breakpoint(id));
+    assert_eq!(cpu.registers.pc, 0x0203);
+    assert_eq!(cpu.registers.x, 1);
+    assert_eq!(cpu.memory.read(0x0010), 0);
+    // resuming from the breakpoint executes its instruction
+    assert_eq!(cpu.run_cycles(1000).unwrap(), StopReason::Breakpoint(id));
+    assert_eq!(cpu.registers.x, 2);
+    assert_eq!(cpu.breakpoint(id).unwrap().hits, 2);
+}
+
+#[test]
+fn test_breakpoint_condition() {
+    let mut cpu = get_breakpoint_cpu();
+    let id = cpu.add_breakpoint(BreakpointKind::Execution(0x0203));
+    cpu.breakpoint_mut(id).unwrap().condition = Some(Expression::parse("X == 3").unwrap());
+
+    assert_eq!(cpu.run_instructions(100).unwrap(), StopReason::Breakpoint(id));
+    assert_eq!(cpu.registers.x, 3);
+    assert_eq!(cpu.breakpoint(id).unwrap().hits, 1);
+}
+
+#[test]
+fn test_breakpoint_ignore_count() {
+    let mut cpu = get_breakpoint_cpu();
+    let id = cpu.add_breakpoint(BreakpointKind::Execution(0x0203));
+    cpu.breakpoint_mut(id).unwrap().ignore_count = 3;
+
+    assert_eq!(cpu.run_instructions(100).unwrap(), StopReason::Breakpoint(id));
+    assert_eq!(cpu.registers.x, 4);
+    assert_eq!(cpu.breakpoint(id).unwrap().hits, 4);
+}
+
+#[test]
+fn test_temporary_breakpoint() {
+    let mut cpu = get_breakpoint_cpu();
+    let id = cpu.add_breakpoint(BreakpointKind::Execution(0x0203));
+    cpu.breakpoint_mut(id).unwrap().temporary = true;
+
+    assert_eq!(cpu.run_instructions(100).unwrap(), StopReason::Breakpoint(id));
+    assert_eq!(cpu.breakpoint(id), None);
+    assert_eq!(cpu.run_instructions(100).unwrap(), StopReason::Stopped);
+    assert_eq!(cpu.registers.x, 5);
+}
+
+#[test]
+fn test_add_breakpoint_with() {
+    let mut cpu = get_breakpoint_cpu();
+    let condition = Expression::parse("X == 2").unwrap();
+    let id = cpu.add_breakpoint_with(BreakpointKind::Execution(0x0203), Some(condition), true);
+
+    assert_eq!(cpu.run_instructions(100).unwrap(), StopReason::Breakpoint(id));
+    assert_eq!(cpu.registers.x, 2);
+    assert_eq!(cpu.breakpoint(id), None);
+}
+
+#[test]
+fn test_disabled_and_removed_breakpoints() {
+    let mut cpu = get_breakpoint_cpu();
+    let disabled = cpu.add_breakpoint(BreakpointKind::Execution(0x0203));
+    let removed = cpu.add_breakpoint(BreakpointKind::Execution(0x0205));
+    cpu.breakpoint_mut(disabled).unwrap().enabled = false;
+    assert_eq!(cpu.remove_breakpoint(removed), true);
+    assert_eq!(cpu.remove_breakpoint(removed), false);
+
+    assert_eq!(cpu.run_instructions(100).unwrap(), StopReason::Stopped);
+    assert_eq!(cpu.breakpoints().len(), 1);
+}
+
+#[test]
+fn test_write_watchpoint() {
+    let mut cpu = get_breakpoint_cpu();
+    let id = cpu.add_breakpoint(BreakpointKind::Watch { start: 0x0010, end: 0x001F, access: Access::Write });
+    cpu.breakpoint_mut(id).unwrap().condition = Some(Expression::parse("[$10] == 2").unwrap());
+
+    let reason = cpu.run_instructions(100).unwrap();
+    assert_eq!(reason, StopReason::Watchpoint { id, address: 0x0010, write: true });
+    // the run stops after the store
+    assert_eq!(cpu.registers.pc, 0x0205);
+    assert_eq!(cpu.memory.read(0x0010), 2);
+}
+
+#[test]
+fn test_read_watchpoint() {
+    let mut cpu = get_breakpoint_cpu();
+    let read = cpu.add_breakpoint(BreakpointKind::Watch { start: 0x0020, end: 0x0020, access: Access::Read });
+    cpu.add_breakpoint(BreakpointKind::Watch { start: 0x0010, end: 0x0010, access: Access::Read });
+
+    assert_eq!(cpu.run_instructions(100).unwrap(), StopReason::Watchpoint { id: read, address: 0x0020, write: false });
+    assert_eq!(cpu.registers.pc, 0x0207);
+}
+
+#[test]
+fn test_stack_read_watchpoint() {
+    let mut cpu = get_cpu();
+    // PHA ; PLA ; KIL
+    cpu.memory.load(&[0x48, 0x68, 0x02], 0x0200).unwrap();
+    cpu.registers.pc = 0x0200;
+    let stack = 0x0100 + cpu.registers.sp as u16;
+    let id = cpu.add_breakpoint(BreakpointKind::Watch { start: stack, end: stack, access: Access::Read });
+
+    assert_eq!(cpu.run_instructions(100).unwrap(), StopReason::Watchpoint { id, address: stack, write: false });
+    // the run stops after the pull, the push does not read the stack
+    assert_eq!(cpu.registers.pc, 0x0202);
+}
+
+#[test]
+fn test_watchpoint_ignores_the_fetch() {
+    let mut cpu = get_breakpoint_cpu();
+    let id = cpu.add_breakpoint(BreakpointKind::Watch { start: 0x0200, end: 0x02FF, access: Access::ReadWrite });
+
+    assert_eq!(cpu.run_instructions(100).unwrap(), StopReason::Stopped);
+    assert_eq!(cpu.breakpoint(id).unwrap().hits, 0);
+}
+
+#[test]
+fn test_breakpoint_inside_step_over() {
+    let mut cpu = get_cpu();
+    // JSR $0210 ; NOP ... $0210: INX ; RTS
+    cpu.memory.load(&[0x20, 0x10, 0x02, 0xEA], 0x0200).unwrap();
+    cpu.memory.load(&[0xE8, 0x60], 0x0210).unwrap();
+    cpu.registers.pc = 0x0200;
+    let id = cpu.add_breakpoint(BreakpointKind::Execution(0x0211));
+
+    assert_eq!(cpu.step_over().unwrap(), StopReason::Breakpoint(id));
+    assert_eq!(cpu.registers.pc, 0x0211);
+}
+
+#[test]
+fn test_watch_accesses_of_one_instruction() {
+    let mut cpu = get_breakpoint_cpu();
+    cpu.add_breakpoint(BreakpointKind::Watch { start: 0x0010, end: 0x0010, access: Access::Write });
+
+    // outside of the run loops, only the accesses of the last instruction are kept
+    cpu.execute_instruction().unwrap();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.breakpoints.accesses, vec![(0x0202, false)]);
+}
+
+#[test]
+fn test_watch_accesses_of_one_tick_instruction() {
+    let mut cpu = get_cpu();
+    // INC $10
+    cpu.memory.load(&[0xE6, 0x10], 0x0200).unwrap();
+    cpu.registers.pc = 0x0200;
+    cpu.add_breakpoint(BreakpointKind::Watch { start: 0x0010, end: 0x0010, access: Access::Write });
+
+    cpu.tick().unwrap();
+    while cpu.in_instruction() {
+        cpu.tick().unwrap();
+    }
+    // the dummy write of the unchanged value, then the result written once
+    let writes: Vec<_> = cpu.breakpoints.accesses.iter().filter(|&&(_, write)| write).collect();
+    assert_eq!(writes, vec![&(0x0010, true), &(0x0010, true)]);
+    assert_eq!(cpu.breakpoints.accesses.len(), 5);
+}
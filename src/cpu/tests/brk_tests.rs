@@ -11,11 +11,12 @@ fn test_brk() {
     cpu.registers.status = 0b11111111;
     cpu.memory.write(0xFFFE, 0x10);
     cpu.memory.write(0xFFFF, 0x10);
-    brk(&mut cpu, AddressingMode::Implied);
+    run(&mut cpu, brk, AddressingMode::Implied);
     assert_eq!(cpu.registers.pc, 0x1010);
     assert_eq!(cpu.get_flag(Flag::Break), true);
     assert_eq!(cpu.get_flag(Flag::Interrupt), true);
-    assert_eq!(cpu.memory.read(0x01FF), 0x10); // Changed this line
-    assert_eq!(cpu.memory.read(0x01FE), 0x00); // Changed this line
-    assert_eq!(cpu.memory.read(0x01FD), 0b11111111); // Changed this line
+    assert_eq!(cpu.memory.read(0x01FD), 0x10); // Changed this line
+    // the return address skips the byte following the opcode
+    assert_eq!(cpu.memory.read(0x01FC), 0x02); // Changed this line
+    assert_eq!(cpu.memory.read(0x01FB), 0b11111111); // Changed this line
 }
\ No newline at end of file
@@ -0,0 +1,229 @@
+use crate::cpu::cpu_6502::{Cpu6502, ExecutionState};
+use crate::cpu::flag::Flag;
+use crate::cpu::variant::CpuVariant;
+use crate::memory::ram::Ram;
+
+fn get_cmos_cpu(program: &[u8]) -> Cpu6502 {
+    let mut cpu = Cpu6502::with_variant(Ram::new(), CpuVariant::Cmos65C02);
+    cpu.reset();
+    cpu.memory.load(program, 0x0200).unwrap();
+    cpu.registers.pc = 0x0200;
+    cpu
+}
+
+#[test]
+fn test_bra() {
+    let mut cpu = get_cmos_cpu(&[0x80, 0x10]);
+
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0212);
+    assert_eq!(cycles, 3);
+}
+
+#[test]
+fn test_phx_ply() {
+    let mut cpu = get_cmos_cpu(&[0xDA, 0x7A]);
+
+    cpu.registers.x = 0x80;
+    cpu.execute_instruction().unwrap();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.y, 0x80);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+    assert_eq!(cpu.registers.pc, 0x0202);
+}
+
+#[test]
+fn test_phy_plx() {
+    let mut cpu = get_cmos_cpu(&[0x5A, 0xFA]);
+
+    cpu.registers.y = 0x00;
+    cpu.registers.x = 0x12;
+    cpu.execute_instruction().unwrap();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.x, 0x00);
+    assert_eq!(cpu.get_flag(Flag::Zero), true);
+}
+
+#[test]
+fn test_stz() {
+    let mut cpu = get_cmos_cpu(&[0x9C, 0x00, 0x30, 0x64, 0x10]);
+
+    cpu.memory.write(0x3000, 0xFF);
+    cpu.memory.write(0x0010, 0xFF);
+    cpu.execute_instruction().unwrap();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.memory.read(0x3000), 0x00);
+    assert_eq!(cpu.memory.read(0x0010), 0x00);
+    assert_eq!(cpu.registers.pc, 0x0205);
+}
+
+#[test]
+fn test_tsb_trb() {
+    let mut cpu = get_cmos_cpu(&[0x04, 0x10, 0x14, 0x10]);
+
+    cpu.registers.a = 0b0000_1111;
+    cpu.memory.write(0x0010, 0b1111_0000);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.memory.read(0x0010), 0b1111_1111);
+    assert_eq!(cpu.get_flag(Flag::Zero), true);
+
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.memory.read(0x0010), 0b1111_0000);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+    assert_eq!(cpu.registers.pc, 0x0204);
+}
+
+#[test]
+fn test_inc_dec_accumulator() {
+    let mut cpu = get_cmos_cpu(&[0x1A, 0x3A, 0x3A]);
+
+    cpu.registers.a = 0xFF;
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.a, 0x00);
+    assert_eq!(cpu.get_flag(Flag::Zero), true);
+    cpu.execute_instruction().unwrap();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.a, 0xFE);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+    assert_eq!(cpu.registers.pc, 0x0203);
+}
+
+#[test]
+fn test_bit_immediate() {
+    let mut cpu = get_cmos_cpu(&[0x89, 0xC0]);
+
+    cpu.registers.a = 0x01;
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.get_flag(Flag::Zero), true);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+    assert_eq!(cpu.get_flag(Flag::Overflow), false);
+}
+
+#[test]
+fn test_lda_zero_page_indirect() {
+    let mut cpu = get_cmos_cpu(&[0xB2, 0x20]);
+
+    cpu.memory.write(0x0020, 0x00);
+    cpu.memory.write(0x0021, 0x30);
+    cpu.memory.write(0x3000, 0x42);
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.a, 0x42);
+    assert_eq!(cpu.registers.pc, 0x0202);
+    assert_eq!(cycles, 5);
+}
+
+#[test]
+fn test_jmp_absolute_indirect_x() {
+    let mut cpu = get_cmos_cpu(&[0x7C, 0x00, 0x30]);
+
+    cpu.registers.x = 0x02;
+    cpu.memory.write(0x3002, 0x34);
+    cpu.memory.write(0x3003, 0x12);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x1234);
+}
+
+#[test]
+fn test_rmb_smb() {
+    let mut cpu = get_cmos_cpu(&[0x37, 0x10, 0xB7, 0x11]);
+
+    cpu.memory.write(0x0010, 0xFF);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.memory.read(0x0010), 0b1111_0111);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.memory.read(0x0011), 0b0000_1000);
+    assert_eq!(cpu.registers.pc, 0x0204);
+}
+
+#[test]
+fn test_bbr_bbs() {
+    // BBR0 $10 not taken, BBS0 $10 taken
+    let mut cpu = get_cmos_cpu(&[0x0F, 0x10, 0x10, 0x8F, 0x10, 0x10]);
+
+    cpu.memory.write(0x0010, 0x01);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0203);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0216);
+}
+
+#[test]
+fn test_reserved_nop() {
+    let mut cpu = get_cmos_cpu(&[0x02, 0xFF, 0x03]);
+
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0202);
+    assert_eq!(cycles, 2);
+    let (state, cycles) = cpu.execute_instruction().unwrap();
+    assert!(state == ExecutionState::Running);
+    assert_eq!(cpu.registers.pc, 0x0203);
+    assert_eq!(cycles, 1);
+}
+
+#[test]
+fn test_eb_is_nop() {
+    let mut cpu = get_cmos_cpu(&[0xEB, 0xEA]);
+
+    cpu.registers.a = 0x10;
+    cpu.set_flag(Flag::Carry, true);
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0201);
+    assert_eq!(cycles, 1);
+    assert_eq!(cpu.registers.a, 0x10);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+}
+
+#[test]
+fn test_shift_absolute_x_cycles() {
+    // ASL $1200,X ; ROL $1200,X ; LSR $1200,X ; ROR $1200,X
+    for opcode in [0x1E, 0x3E, 0x5E, 0x7E] {
+        let mut cpu = get_cmos_cpu(&[opcode, 0x00, 0x12, opcode, 0xF0, 0x12]);
+
+        cpu.registers.x = 0x10;
+        let (_, cycles) = cpu.execute_instruction().unwrap();
+        assert_eq!(cycles, 6, "opcode {:02X}", opcode);
+        // one more cycle when the indexed address crosses a page
+        let (_, cycles) = cpu.execute_instruction().unwrap();
+        assert_eq!(cycles, 7, "opcode {:02X}", opcode);
+    }
+}
+
+#[test]
+fn test_wai() {
+    let mut cpu = get_cmos_cpu(&[0xCB, 0xEA]);
+
+    cpu.set_flag(Flag::Interrupt, true);
+    cpu.execute_instruction().unwrap();
+    let (state, _) = cpu.execute_instruction().unwrap();
+    assert!(state == ExecutionState::Waiting);
+    assert_eq!(cpu.registers.pc, 0x0201);
+
+    // a masked IRQ resumes execution without taking the interrupt
+    cpu.assert_irq();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0202);
+}
+
+#[test]
+fn test_stp() {
+    let mut cpu = get_cmos_cpu(&[0xDB, 0xEA]);
+
+    cpu.execute_instruction().unwrap();
+    let (state, _) = cpu.execute_instruction().unwrap();
+    assert!(state == ExecutionState::Stopped);
+    assert_eq!(cpu.registers.pc, 0x0201);
+
+    cpu.reset();
+    cpu.registers.pc = 0x0201;
+    let (state, _) = cpu.execute_instruction().unwrap();
+    assert!(state == ExecutionState::Running);
+}
+
+#[test]
+fn test_brk_clears_decimal() {
+    let mut cpu = get_cmos_cpu(&[0x00]);
+
+    cpu.set_flag(Flag::Decimal, true);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.get_flag(Flag::Decimal), false);
+}
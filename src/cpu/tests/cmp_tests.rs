@@ -9,7 +9,7 @@ fn test_cmp_equal() {
 
     cpu.registers.a = 0x01;
     cpu.memory.write(0x0001, 0x01);
-    cmp(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, cmp, AddressingMode::Immediate);
     assert_eq!(cpu.get_flag(Flag::Carry), true);
     assert_eq!(cpu.get_flag(Flag::Zero), true);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -21,7 +21,7 @@ fn test_cmp_upper() {
 
     cpu.registers.a = 0x02;
     cpu.memory.write(0x0001, 0x01);
-    cmp(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, cmp, AddressingMode::Immediate);
     assert_eq!(cpu.get_flag(Flag::Carry), true);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -33,7 +33,7 @@ fn test_cmp_lower() {
 
     cpu.registers.a = 0x01;
     cpu.memory.write(0x0001, 0x02);
-    cmp(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, cmp, AddressingMode::Immediate);
     assert_eq!(cpu.get_flag(Flag::Carry), false);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), true);
@@ -45,7 +45,7 @@ fn test_cmp_zero() {
 
     cpu.registers.a = 0x00;
     cpu.memory.write(0x0001, 0x00);
-    cmp(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, cmp, AddressingMode::Immediate);
     assert_eq!(cpu.get_flag(Flag::Carry), true);
     assert_eq!(cpu.get_flag(Flag::Zero), true);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -57,7 +57,7 @@ fn test_cmp_negative() {
 
     cpu.registers.a = 0x80;
     cpu.memory.write(0x0001, 0x00);
-    cmp(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, cmp, AddressingMode::Immediate);
     assert_eq!(cpu.get_flag(Flag::Carry), true);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), true);
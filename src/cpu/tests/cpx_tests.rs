@@ -0,0 +1,23 @@
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::flag::Flag;
+use crate::cpu::variant::CpuVariant;
+use super::*;
+
+#[test]
+fn test_cpx_zero_page_opcode() {
+    for variant in [CpuVariant::Nmos6502, CpuVariant::Cmos65C02] {
+        let instruction = variant.instructions()[0xE4];
+        assert_eq!(instruction.name, "CPX");
+        assert_eq!(instruction.addressing_mode, AddressingMode::ZeroPage);
+        assert_eq!(instruction.cycles, 3);
+
+        let mut cpu = get_cpu();
+        cpu.registers.x = 0x10;
+        cpu.memory.write(0x0001, 0x20);
+        cpu.memory.write(0x0020, 0x10);
+        run(&mut cpu, instruction.execute, instruction.addressing_mode);
+        assert_eq!(cpu.get_flag(Flag::Carry), true);
+        assert_eq!(cpu.get_flag(Flag::Zero), true);
+        assert_eq!(cpu.get_flag(Flag::Negative), false);
+    }
+}
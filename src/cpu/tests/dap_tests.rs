@@ -0,0 +1,233 @@
+use std::fs;
+use std::io::Cursor;
+
+use crate::dap::DapServer;
+use crate::util::json::{parse, JsonValue};
+use super::*;
+
+// assembled at $0200: LDX $0200, JSR $0202, CPX $0205, BNE $0207, KIL $0209,
+// INX $020A, STX $020B, RTS $020D
+const PROGRAM: &str = "start:
+LDX immediate $00
+loop:
+JSR absolute count
+CPX immediate $03
+BNE relative loop
+KIL implied
+count:
+INX implied
+STX zero_page $10
+RTS implied
+";
+
+fn write_source(name: &str, source: &str) -> String {
+    let path = std::env::temp_dir().join(format!("emul_dap_{}_{}.s", name, std::process::id()));
+    fs::write(&path, source).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+///
+/// run a session with the requests, each a command and its arguments in JSON,
+/// and return the messages of the server, the session ends with the input
+/// once the program stopped, a disconnect would end it while the program runs
+///
+fn session(requests: &[(&str, String)]) -> Vec<JsonValue> {
+    let mut input = String::new();
+    for (seq, (command, arguments)) in requests.iter().enumerate() {
+        let body = format!(r#"{{"seq":{},"type":"request","command":"{}","arguments":{}}}"#, seq + 1, command, arguments);
+        input.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+    }
+    let mut output = Vec::new();
+    DapServer::new(get_cpu()).run(Cursor::new(input.into_bytes()), &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let mut messages = Vec::new();
+    let mut rest = output.as_str();
+    while let Some((header, body)) = rest.split_once("\r\n\r\n") {
+        let length: usize = header.trim_start_matches("Content-Length: ").parse().unwrap();
+        messages.push(parse(&body[..length]).unwrap());
+        rest = &body[length..];
+    }
+    messages
+}
+
+fn launch(path: &str, stop_on_entry: bool) -> (&'static str, String) {
+    ("launch", format!(r#"{{"program":"{}","origin":512,"stopOnEntry":{}}}"#, path, stop_on_entry))
+}
+
+fn request(command: &'static str, arguments: &str) -> (&'static str, String) {
+    (command, arguments.to_owned())
+}
+
+fn set_breakpoints(path: &str, breakpoints: &str) -> (&'static str, String) {
+    ("setBreakpoints", format!(r#"{{"source":{{"path":"{}"}},"breakpoints":{}}}"#, path, breakpoints))
+}
+
+///
+/// the bodies of the responses to `command`, in order
+///
+fn responses<'a>(messages: &'a [JsonValue], command: &str) -> Vec<&'a JsonValue> {
+    messages
+        .iter()
+        .filter(|message| message.get("type").and_then(JsonValue::as_str) == Some("response"))
+        .filter(|message| message.get("command").and_then(JsonValue::as_str) == Some(command))
+        .map(|message| message.get("body").unwrap_or(&JsonValue::Null))
+        .collect()
+}
+
+///
+/// the names of the events and the reasons of the stops, in order
+///
+fn events(messages: &[JsonValue]) -> Vec<String> {
+    messages
+        .iter()
+        .filter(|message| message.get("type").and_then(JsonValue::as_str) == Some("event"))
+        .map(|message| {
+            let event = message.get("event").and_then(JsonValue::as_str).unwrap().to_owned();
+            match message.get("body").and_then(|body| body.get("reason")).and_then(JsonValue::as_str) {
+                Some(reason) => format!("{} {}", event, reason),
+                None => event,
+            }
+        })
+        .collect()
+}
+
+///
+/// the name and the line of the frames of a stack trace
+///
+fn frames(body: &JsonValue) -> Vec<(String, u64)> {
+    body.get("stackFrames")
+        .and_then(JsonValue::as_array)
+        .unwrap()
+        .iter()
+        .map(|frame| {
+            (frame.get("name").and_then(JsonValue::as_str).unwrap().to_owned(), frame.get("line").and_then(JsonValue::as_u64).unwrap())
+        })
+        .collect()
+}
+
+fn variable<'a>(body: &'a JsonValue, name: &str) -> &'a str {
+    let variables = body.get("variables").and_then(JsonValue::as_array).unwrap();
+    let variable = variables.iter().find(|variable| variable.get("name").and_then(JsonValue::as_str) == Some(name)).unwrap();
+    variable.get("value").and_then(JsonValue::as_str).unwrap()
+}
+
+#[test]
+fn test_dap_breakpoint_and_call_stack() {
+    let path = write_source("breakpoint", PROGRAM);
+    let messages = session(&[
+        request("initialize", r#"{"adapterID":"emul"}"#),
+        launch(&path, false),
+        set_breakpoints(&path, r#"[{"line":9},{"line":8},{"line":20}]"#),
+        request("configurationDone", "{}"),
+        request("stackTrace", r#"{"threadId":1}"#),
+        request("variables", r#"{"variablesReference":1}"#),
+        request("next", r#"{"threadId":1}"#),
+        request("stackTrace", r#"{"threadId":1}"#),
+        request("stepOut", r#"{"threadId":1}"#),
+        request("stackTrace", r#"{"threadId":1}"#),
+        request("continue", r#"{"threadId":1}"#),
+        request("variables", r#"{"variablesReference":1}"#),
+    ]);
+    fs::remove_file(&path).unwrap();
+    let capabilities = responses(&messages, "initialize")[0];
+    assert_eq!(capabilities.get("supportsConfigurationDoneRequest"), Some(&JsonValue::Bool(true)));
+    let breakpoints = responses(&messages, "setBreakpoints")[0].get("breakpoints").and_then(JsonValue::as_array).unwrap();
+    assert_eq!(breakpoints[0].get("verified"), Some(&JsonValue::Bool(true)));
+    // the label line moves to the instruction after it
+    assert_eq!(breakpoints[1].get("line"), Some(&JsonValue::Number(9.0)));
+    assert_eq!(breakpoints[2].get("verified"), Some(&JsonValue::Bool(false)));
+    assert_eq!(
+        events(&messages),
+        ["initialized", "stopped breakpoint", "stopped step", "stopped step", "stopped breakpoint"]
+    );
+    let traces = responses(&messages, "stackTrace");
+    assert_eq!(frames(traces[0]), [("count".to_owned(), 9), ("start".to_owned(), 4)]);
+    assert_eq!(frames(traces[1]), [("count".to_owned(), 10), ("start".to_owned(), 4)]);
+    assert_eq!(frames(traces[2]), [("start".to_owned(), 5)]);
+    let variables = responses(&messages, "variables");
+    assert_eq!(variable(variables[0], "X"), "$00");
+    assert_eq!(variable(variables[0], "PC"), "$020A");
+    assert_eq!(variable(variables[1], "X"), "$01");
+}
+
+#[test]
+fn test_dap_conditions() {
+    let path = write_source("conditions", PROGRAM);
+    let messages = session(&[
+        request("initialize", "{}"),
+        launch(&path, false),
+        set_breakpoints(&path, r#"[{"line":10,"condition":"X == 2"},{"line":5,"hitCondition":"3"},{"line":2,"condition":"X =="}]"#),
+        request("configurationDone", "{}"),
+        request("variables", r#"{"variablesReference":1}"#),
+        request("continue", r#"{"threadId":1}"#),
+        request("variables", r#"{"variablesReference":1}"#),
+        request("continue", r#"{"threadId":1}"#),
+    ]);
+    fs::remove_file(&path).unwrap();
+    let breakpoints = responses(&messages, "setBreakpoints")[0].get("breakpoints").and_then(JsonValue::as_array).unwrap();
+    assert_eq!(breakpoints[2].get("verified"), Some(&JsonValue::Bool(false)));
+    assert_eq!(
+        events(&messages),
+        ["initialized", "stopped breakpoint", "stopped breakpoint", "exited", "terminated"]
+    );
+    let variables = responses(&messages, "variables");
+    assert_eq!(variable(variables[0], "X"), "$02");
+    assert_eq!(variable(variables[0], "PC"), "$020B");
+    assert_eq!(variable(variables[1], "X"), "$03");
+    assert_eq!(variable(variables[1], "PC"), "$0205");
+}
+
+#[test]
+fn test_dap_variables_and_memory() {
+    let path = write_source("memory", PROGRAM);
+    let messages = session(&[
+        request("initialize", "{}"),
+        launch(&path, true),
+        request("configurationDone", "{}"),
+        request("next", r#"{"threadId":1}"#),
+        request("next", r#"{"threadId":1}"#),
+        request("stackTrace", r#"{"threadId":1}"#),
+        request("evaluate", r#"{"expression":"X + [$10]"}"#),
+        request("setVariable", r#"{"variablesReference":1,"name":"A","value":"$42"}"#),
+        request("setVariable", r#"{"variablesReference":2,"name":"C","value":"1"}"#),
+        request("setVariable", r#"{"variablesReference":1,"name":"A","value":"$142"}"#),
+        request("variables", r#"{"variablesReference":2}"#),
+        request("writeMemory", r#"{"memoryReference":"0x0010","data":"qrs="}"#),
+        request("readMemory", r#"{"memoryReference":"0x0010","offset":-1,"count":3}"#),
+        request("readMemory", r#"{"memoryReference":"0xFFFF","count":2}"#),
+        request("scopes", r#"{"frameId":0}"#),
+        request("goto", "{}"),
+    ]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(events(&messages), ["initialized", "stopped entry", "stopped step", "stopped step"]);
+    assert_eq!(frames(responses(&messages, "stackTrace")[0]), [("start".to_owned(), 5)]);
+    assert_eq!(responses(&messages, "evaluate")[0].get("result").and_then(JsonValue::as_str), Some("2 ($2)"));
+    let values = responses(&messages, "setVariable");
+    assert_eq!(values[0].get("value").and_then(JsonValue::as_str), Some("$42"));
+    assert_eq!(values[1].get("value").and_then(JsonValue::as_str), Some("1"));
+    assert_eq!(values[2], &JsonValue::Null);
+    assert_eq!(variable(responses(&messages, "variables")[0], "C"), "1");
+    assert_eq!(responses(&messages, "writeMemory")[0].get("bytesWritten"), Some(&JsonValue::Number(2.0)));
+    let reads = responses(&messages, "readMemory");
+    assert_eq!(reads[0].get("address").and_then(JsonValue::as_str), Some("0x000F"));
+    assert_eq!(reads[0].get("data").and_then(JsonValue::as_str), Some("AKq7"));
+    assert_eq!(reads[1].get("unreadableBytes"), Some(&JsonValue::Number(1.0)));
+    let unsupported = messages.iter().find(|message| message.get("command").and_then(JsonValue::as_str) == Some("goto")).unwrap();
+    assert_eq!(unsupported.get("success"), Some(&JsonValue::Bool(false)));
+}
+
+#[test]
+fn test_dap_pause() {
+    let path = write_source("pause", "loop:\nJMP absolute loop\n");
+    let messages = session(&[
+        request("initialize", "{}"),
+        launch(&path, false),
+        request("configurationDone", "{}"),
+        request("pause", r#"{"threadId":1}"#),
+        request("stackTrace", r#"{"threadId":1}"#),
+        request("disconnect", "{}"),
+    ]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(events(&messages), ["initialized", "stopped pause"]);
+    assert_eq!(frames(responses(&messages, "stackTrace")[0]), [("loop".to_owned(), 2)]);
+}
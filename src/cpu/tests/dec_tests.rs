@@ -8,7 +8,7 @@ fn test_dec() {
     let mut cpu = get_cpu();
 
     cpu.memory.write(0x0001, 0x01);
-    dec(&mut cpu, AddressingMode::ZeroPage);
+    run(&mut cpu, dec, AddressingMode::ZeroPage);
     assert_eq!(cpu.memory.read(0x0001), 0x00);
     assert_eq!(cpu.get_flag(Flag::Zero), true);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -0,0 +1,92 @@
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::operand::Operand;
+use crate::memory::ram::Ram;
+
+fn get_program_cpu(program: &[u8]) -> Cpu6502 {
+    let mut cpu = Cpu6502::new(Ram::new());
+    cpu.reset();
+    cpu.memory.load(program, 0x0200).unwrap();
+    cpu.registers.pc = 0x0200;
+    cpu
+}
+
+#[test]
+fn test_decode_immediate() {
+    let mut cpu = get_program_cpu(&[0xA9, 0x42]);
+
+    let decoded = cpu.decode(0x0200);
+    assert_eq!(decoded.opcode, 0xA9);
+    assert_eq!(decoded.operand, Operand::Immediate(0x42));
+    assert_eq!(decoded.next_pc, 0x0202);
+    assert_eq!(cpu.registers.pc, 0x0200);
+}
+
+#[test]
+fn test_decode_indirect_y_page_crossed() {
+    let mut cpu = get_program_cpu(&[0xB1, 0x10]);
+
+    cpu.memory.write(0x0010, 0xF0);
+    cpu.memory.write(0x0011, 0x12);
+    cpu.registers.y = 0x20;
+    let decoded = cpu.decode(0x0200);
+    assert_eq!(decoded.instruction.addressing_mode, AddressingMode::IndirectY);
+    assert_eq!(decoded.operand, Operand::Address(0x1310));
+    assert_eq!(decoded.page_crossed, true);
+}
+
+#[test]
+fn test_decode_indirect_x_zero_page_wrap() {
+    let mut cpu = get_program_cpu(&[0xA1, 0xFE]);
+
+    cpu.registers.x = 0x01;
+    cpu.memory.write(0x00FF, 0x34);
+    cpu.memory.write(0x0000, 0x12);
+    let decoded = cpu.decode(0x0200);
+    assert_eq!(decoded.operand, Operand::Address(0x1234));
+}
+
+#[test]
+fn test_pc_advances_by_length() {
+    // LDA #$01, ASL A, INX, STA $1234
+    let mut cpu = get_program_cpu(&[0xA9, 0x01, 0x0A, 0xE8, 0x8D, 0x34, 0x12]);
+
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0202);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0203);
+    assert_eq!(cpu.registers.a, 0x02);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0204);
+    assert_eq!(cpu.registers.x, 0x01);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0207);
+    assert_eq!(cpu.memory.read(0x1234), 0x02);
+}
+
+#[test]
+fn test_jsr_rts() {
+    // JSR $0210, then RTS at $0210
+    let mut cpu = get_program_cpu(&[0x20, 0x10, 0x02]);
+
+    cpu.memory.write(0x0210, 0x60);
+    let sp = cpu.registers.sp;
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0210);
+    // the pushed return address is the last byte of JSR
+    assert_eq!(cpu.memory.read(0x0100 + sp as u16), 0x02);
+    assert_eq!(cpu.memory.read(0x0100 + sp.wrapping_sub(1) as u16), 0x02);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0203);
+    assert_eq!(cpu.registers.sp, sp);
+}
+
+#[test]
+fn test_data_reads_leave_pc() {
+    let mut cpu = get_program_cpu(&[]);
+
+    cpu.read_byte(0x1234);
+    cpu.read_word(0x1234);
+    cpu.write_byte(0x1234, 0x42);
+    assert_eq!(cpu.registers.pc, 0x0200);
+}
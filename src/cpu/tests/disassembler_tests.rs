@@ -0,0 +1,156 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::assembler::assemble_listing;
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::variant::CpuVariant;
+use crate::disassembler::{disassemble, disassemble_source, disassemble_with, DisassembledLine};
+use crate::util::types::Address;
+
+fn operands(lines: &[DisassembledLine]) -> Vec<String> {
+    lines.iter().map(|line| format!("{} {}", line.mnemonic, line.operand).trim_end().to_owned()).collect()
+}
+
+#[test]
+fn test_disassemble_addressing_modes() {
+    let code = [
+        0xEA, // NOP
+        0x0A, // ASL A
+        0xA9, 0x12, // LDA #$12
+        0xA5, 0x12, // LDA $12
+        0xB5, 0x12, // LDA $12,X
+        0xB6, 0x12, // LDX $12,Y
+        0xAD, 0x34, 0x12, // LDA $1234
+        0xBD, 0x34, 0x12, // LDA $1234,X
+        0xB9, 0x34, 0x12, // LDA $1234,Y
+        0x6C, 0x34, 0x12, // JMP ($1234)
+        0xA1, 0x12, // LDA ($12,X)
+        0xB1, 0x12, // LDA ($12),Y
+    ];
+    assert_eq!(
+        operands(&disassemble(&code, 0x0200)),
+        [
+            "NOP",
+            "ASL A",
+            "LDA #$12",
+            "LDA $12",
+            "LDA $12,X",
+            "LDX $12,Y",
+            "LDA $1234",
+            "LDA $1234,X",
+            "LDA $1234,Y",
+            "JMP ($1234)",
+            "LDA ($12,X)",
+            "LDA ($12),Y",
+        ]
+    );
+}
+
+#[test]
+fn test_disassemble_targets() {
+    // BNE +2 ; BEQ -4 ; JSR $1234 ; JMP $0200 ; JMP ($1234) ; LDA $1234
+    let code = [0xD0, 0x02, 0xF0, 0xFC, 0x20, 0x34, 0x12, 0x4C, 0x00, 0x02, 0x6C, 0x34, 0x12, 0xAD, 0x34, 0x12];
+    let lines = disassemble(&code, 0x0200);
+    assert_eq!(operands(&lines[..2]), ["BNE $0204", "BEQ $0200"]);
+    let targets: Vec<Option<u16>> = lines.iter().map(|line| line.target).collect();
+    assert_eq!(targets, [Some(0x0204), Some(0x0200), Some(0x1234), Some(0x0200), None, None]);
+    assert_eq!(lines[0].addressing_mode, AddressingMode::Relative);
+    // a branch wraps around the address space
+    assert_eq!(disassemble(&[0xD0, 0xFD], 0x0000)[0].target, Some(0xFFFF));
+}
+
+#[test]
+fn test_disassemble_display() {
+    let lines = disassemble(&[0xA2, 0x00, 0xE8, 0x8E, 0x00, 0x10], 0xC000);
+    let text: Vec<String> = lines.iter().map(DisassembledLine::to_string).collect();
+    assert_eq!(text, ["C000  A2 00     LDX #$00", "C002  E8        INX", "C003  8E 00 10  STX $1000"]);
+    assert_eq!(lines[2].bytes, [0x8E, 0x00, 0x10]);
+    assert_eq!(lines[2].address, 0xC003);
+}
+
+#[test]
+fn test_disassemble_truncated() {
+    // INX ; LDA absolute cut after its first operand byte
+    let lines = disassemble(&[0xE8, 0xAD, 0x34], 0x0200);
+    assert_eq!(operands(&lines), ["INX", ".byte $AD", ".byte $34"]);
+    assert_eq!(lines[2].address, 0x0202);
+}
+
+#[test]
+fn test_disassemble_cmos() {
+    // BBR0 $12,+1 ; LDA ($12) ; STZ $12 ; BRA -9
+    let code = [0x0F, 0x12, 0x01, 0xB2, 0x12, 0x64, 0x12, 0x80, 0xF7];
+    let lines = disassemble_with(CpuVariant::Cmos65C02.instructions(), &code, 0x0200);
+    assert_eq!(operands(&lines), ["BBR0 $12,$0204", "LDA ($12)", "STZ $12", "BRA $0200"]);
+    assert_eq!(lines[0].target, Some(0x0204));
+}
+
+#[test]
+fn test_disassemble_source() {
+    let bytes = [
+        0x20, 0x0B, 0x02, // JSR $020B
+        0xA2, 0x00, // LDX #$00
+        0xCA, // DEX
+        0xD0, 0xFD, // BNE $0205
+        0x6C, 0x12, 0x02, // JMP ($0212)
+        0xB0, 0x80, // BCS $018D, outside of the bytes
+        0x0A, // ASL A
+        0x60, // RTS
+        0x02, 0xEA, 0x1A, 0x00, 0x03, // data
+    ];
+    let source = disassemble_source(&bytes, 0x0200, &[0x0200]);
+    assert_eq!(
+        source.lines().collect::<Vec<_>>(),
+        [
+            "L_0200:",
+            "JSR absolute L_020B",
+            "LDX immediate $00",
+            "L_0205:",
+            "DEX implied",
+            "BNE relative L_0205",
+            "JMP indirect $0212",
+            "L_020B:",
+            "BCS relative $80",
+            "ASL accumulator",
+            "RTS implied",
+            ".byte $02,$EA,$1A,$00,$03",
+        ]
+    );
+    assert_eq!(assemble_listing(&source, 0x0200).unwrap().machine_code, bytes);
+}
+
+#[test]
+fn test_disassemble_source_vectors() {
+    // INX ; JMP $FFF0 ; RTI, then the NMI, reset and IRQ vectors
+    let mut rom = vec![0xE8, 0x4C, 0xF0, 0xFF, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00];
+    rom.extend([0xF4, 0xFF, 0xF0, 0xFF, 0xF4, 0xFF]);
+    let source = disassemble_source(&rom, 0xFFF0, &[]);
+    assert_eq!(
+        source.lines().collect::<Vec<_>>(),
+        [
+            "L_FFF0:",
+            "INX implied",
+            "JMP absolute L_FFF0",
+            "L_FFF4:",
+            "RTI implied",
+            ".byte $00,$00,$00,$00,$00,$F4,$FF,$F0",
+            ".byte $FF,$F4,$FF",
+        ]
+    );
+    assert_eq!(assemble_listing(&source, 0xFFF0).unwrap().machine_code, rom);
+}
+
+#[test]
+fn test_disassemble_source_round_trip() {
+    let mut rng = StdRng::seed_from_u64(6502);
+    for _ in 0..200 {
+        let length = rng.gen_range(1..=512);
+        let origin: Address = rng.gen_range(0..=(0x10000 - length)) as Address;
+        let bytes: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+        let entries: Vec<Address> =
+            (0..4).map(|_| origin.wrapping_add(rng.gen_range(0..length) as Address)).collect();
+        let source = disassemble_source(&bytes, origin, &entries);
+        let listing = assemble_listing(&source, origin).unwrap_or_else(|error| panic!("{}\n{}", error, source));
+        assert_eq!(listing.machine_code, bytes, "origin ${:04X}\n{}", origin, source);
+    }
+}
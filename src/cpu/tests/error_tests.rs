@@ -0,0 +1,102 @@
+use crate::assembler::assemble;
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::error::EmulatorError;
+use crate::memory::address_decoder::AddressDecoder;
+use crate::memory::bus::Bus;
+use crate::memory::ram::Ram;
+use super::*;
+
+#[test]
+fn test_load_too_large() {
+    let mut ram = Ram::new();
+
+    assert_eq!(ram.load(&[0xEA; 0x100], 0xFF00), Ok(()));
+    assert_eq!(
+        ram.load(&[0xEA; 0x101], 0xFF00),
+        Err(EmulatorError::LoadTooLarge { offset: 0xFF00, len: 0x101 })
+    );
+    // nothing is written when the data does not fit
+    assert_eq!(ram.peek(0x0000), 0x00);
+}
+
+#[test]
+fn test_pc_overflow() {
+    let mut cpu = get_cpu();
+
+    // LDA $1234 with its operand past the end of memory
+    cpu.memory.write(0xFFFE, 0xAD);
+    cpu.registers.pc = 0xFFFE;
+    assert_eq!(cpu.execute_instruction(), Err(EmulatorError::PcOverflow { pc: 0xFFFE }));
+    assert_eq!(cpu.registers.pc, 0xFFFE);
+    assert_eq!(cpu.tick(), Err(EmulatorError::PcOverflow { pc: 0xFFFE }));
+}
+
+#[test]
+fn test_bus_fault() {
+    let mut decoder = AddressDecoder::new();
+    decoder.map(0x0000, 0x7FFF, Ram::new());
+    decoder.set_fault_unmapped(true);
+    let mut cpu = Cpu6502::new(decoder);
+
+    // LDA $8000
+    cpu.memory.load(&[0xAD, 0x00, 0x80], 0x0200).unwrap();
+    cpu.registers.pc = 0x0200;
+    assert_eq!(cpu.execute_instruction(), Err(EmulatorError::BusFault { address: 0x8000 }));
+
+    // the fault is only reported once
+    cpu.registers.pc = 0x0200;
+    cpu.memory.load(&[0xAD, 0x00, 0x10], 0x0200).unwrap();
+    assert!(cpu.execute_instruction().is_ok());
+}
+
+#[test]
+fn test_unmapped_without_fault() {
+    let mut decoder = AddressDecoder::new();
+    decoder.map(0x0000, 0x7FFF, Ram::new());
+    let mut cpu = Cpu6502::new(decoder);
+
+    cpu.memory.load(&[0xAD, 0x00, 0x80], 0x0200).unwrap();
+    cpu.registers.pc = 0x0200;
+    assert!(cpu.execute_instruction().is_ok());
+    assert_eq!(cpu.registers.a, 0x00);
+}
+
+#[test]
+fn test_assembler_errors() {
+    assert_eq!(assemble("LDA immediate $10\nFOO immediate $10"), Err(EmulatorError::Assembly {
+        line: 2,
+        message: "unsupported instruction: FOO immediate".to_owned(),
+    }));
+    assert_eq!(assemble("LDA"), Err(EmulatorError::Assembly {
+        line: 1,
+        message: "missing addressing mode after LDA".to_owned(),
+    }));
+    assert_eq!(assemble("LDA immediate $XY"), Err(EmulatorError::Assembly {
+        line: 1,
+        message: "invalid operand: $XY".to_owned(),
+    }));
+    assert_eq!(assemble("LDA immediate"), Err(EmulatorError::Assembly {
+        line: 1,
+        message: "missing operand".to_owned(),
+    }));
+    assert_eq!(assemble("ASL accumulator A"), Err(EmulatorError::Assembly {
+        line: 1,
+        message: "unexpected operand: A".to_owned(),
+    }));
+    assert_eq!(assemble("BBR0 zero_page_relative $12"), Err(EmulatorError::Assembly {
+        line: 1,
+        message: "missing operand".to_owned(),
+    }));
+}
+
+#[test]
+fn test_assembler_backward_branch() {
+    let code = assemble("loop:\nINX implied\nBNE relative loop").unwrap();
+    assert_eq!(code, vec![0xE8, 0xD0, 0xFD]);
+}
+
+#[test]
+fn test_assembler_zero_page_relative() {
+    let code = assemble("loop:\nBBS7 zero_page_relative $12 loop\nBBR0 zero_page_relative $34 $05\nASL accumulator").unwrap();
+    assert_eq!(code, vec![0xFF, 0x12, 0xFD, 0x0F, 0x34, 0x05, 0x0A]);
+}
@@ -0,0 +1,56 @@
+use crate::cpu::expression::Expression;
+use crate::error::EmulatorError;
+use super::*;
+
+fn evaluate(text: &str) -> i64 {
+    let mut cpu = get_cpu();
+    cpu.registers.a = 0xFF;
+    cpu.registers.x = 0x10;
+    cpu.registers.y = 0x02;
+    cpu.registers.pc = 0x1234;
+    cpu.registers.status = 0x25;
+    cpu.memory.write(0x0010, 4);
+    cpu.memory.write(0x0012, 0x80);
+    Expression::parse(text).unwrap().evaluate(&cpu)
+}
+
+#[test]
+fn test_expression_values() {
+    assert_eq!(evaluate("A"), 0xFF);
+    assert_eq!(evaluate("pc"), 0x1234);
+    assert_eq!(evaluate("SP"), 0xFD);
+    assert_eq!(evaluate("$1F + 10 - %11"), 0x1F + 10 - 3);
+    assert_eq!(evaluate("[X]"), 4);
+    assert_eq!(evaluate("[X + Y]"), 0x80);
+    assert_eq!(evaluate("-1"), -1);
+}
+
+#[test]
+fn test_expression_conditions() {
+    assert_eq!(evaluate("A == $FF && [$10] > 3"), 1);
+    assert_eq!(evaluate("A == $FF && [$10] > 4"), 0);
+    assert_eq!(evaluate("X != $10 || Y <= 2"), 1);
+    assert_eq!(evaluate("!(X >= $11)"), 1);
+}
+
+#[test]
+fn test_expression_precedence() {
+    // the bit operators bind tighter than the comparisons like in Rust
+    assert_eq!(evaluate("P & 1 == 1"), 1);
+    assert_eq!(evaluate("P & $0F | $30 ^ $10"), 0x25);
+    assert_eq!(evaluate("1 + 2 == 3 && 0 || 1"), 1);
+}
+
+#[test]
+fn test_expression_errors() {
+    assert!(matches!(Expression::parse("A =="), Err(EmulatorError::InvalidExpression { offset: 4, .. })));
+    assert!(matches!(Expression::parse("Q > 1"), Err(EmulatorError::InvalidExpression { offset: 0, .. })));
+    assert!(matches!(Expression::parse("[$10"), Err(EmulatorError::InvalidExpression { offset: 4, .. })));
+    assert!(matches!(Expression::parse("$GG"), Err(EmulatorError::InvalidExpression { offset: 1, .. })));
+    assert!(matches!(Expression::parse("A 1"), Err(EmulatorError::InvalidExpression { offset: 2, .. })));
+}
+
+#[test]
+fn test_expression_display() {
+    assert_eq!(Expression::parse("  A == $FF ").unwrap().to_string(), "A == $FF");
+}
@@ -0,0 +1,198 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread::{self, JoinHandle};
+
+use crate::error::EmulatorError;
+use crate::gdb::GdbStub;
+use super::*;
+
+///
+/// debugger side of the connection, acknowledging every packet until the no-ack mode
+///
+struct Client {
+    stream: TcpStream,
+    ack: bool,
+}
+
+impl Client {
+    fn read_byte(&mut self) -> u8 {
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+
+    fn send_raw(&mut self, data: &[u8]) {
+        self.stream.write_all(data).unwrap();
+    }
+
+    fn packet(data: &str) -> String {
+        let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        format!("${}#{:02x}", data, checksum)
+    }
+
+    fn receive(&mut self) -> String {
+        while self.read_byte() != b'$' {}
+        let mut data = Vec::new();
+        loop {
+            match self.read_byte() {
+                b'#' => break,
+                byte => data.push(byte),
+            }
+        }
+        self.read_byte();
+        self.read_byte();
+        if self.ack {
+            self.send_raw(b"+");
+        }
+        String::from_utf8(data).unwrap()
+    }
+
+    ///
+    /// send a packet and return the reply
+    ///
+    fn send(&mut self, data: &str) -> String {
+        self.send_raw(Client::packet(data).as_bytes());
+        if self.ack {
+            assert_eq!(self.read_byte(), b'+');
+        }
+        self.receive()
+    }
+
+    ///
+    /// kill the target, the stub acknowledges the packet and closes the connection
+    ///
+    fn kill(&mut self) {
+        self.send_raw(Client::packet("k").as_bytes());
+        assert_eq!(self.read_byte(), b'+');
+    }
+}
+
+///
+/// serve a stub on a loopback socket, the cpu runs the program at $0200
+///
+fn connect(program: &'static [u8]) -> (Client, JoinHandle<Result<(), EmulatorError>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let mut cpu = get_cpu();
+        cpu.memory.load(program, 0x0200).unwrap();
+        cpu.registers.pc = 0x0200;
+        GdbStub::new(cpu).listen(&listener)
+    });
+    let stream = TcpStream::connect(address).unwrap();
+    (Client { stream, ack: true }, server)
+}
+
+// LDX #$00 ; INX ; STX $10 ; CPX #$05 ; BNE $0202 ; KIL
+const COUNT: &[u8] = &[0xA2, 0x00, 0xE8, 0x86, 0x10, 0xE0, 0x05, 0xD0, 0xF9, 0x02];
+
+#[test]
+fn test_gdb_registers() {
+    let (mut client, server) = connect(COUNT);
+    assert_eq!(client.send("?"), "S05");
+    assert_eq!(client.send("g"), "000000fd000224");
+    assert_eq!(client.send("G0102037f3412a5"), "OK");
+    assert_eq!(client.send("g"), "0102037f3412a5");
+    assert_eq!(client.send("p4"), "3412");
+    assert_eq!(client.send("P0=ff"), "OK");
+    assert_eq!(client.send("P4=0002"), "OK");
+    assert_eq!(client.send("p0"), "ff");
+    assert_eq!(client.send("p4"), "0002");
+    assert_eq!(client.send("p6"), "E01");
+    assert_eq!(client.send("P1=0102"), "E01");
+    assert_eq!(client.send("D"), "OK");
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_gdb_memory() {
+    let (mut client, server) = connect(COUNT);
+    assert_eq!(client.send("m200,4"), "a200e886");
+    assert_eq!(client.send("M10,3:aabbcc"), "OK");
+    assert_eq!(client.send("m10,3"), "aabbcc");
+    // the reads stop at the end of the address space
+    assert_eq!(client.send("mffff,4"), "00");
+    assert_eq!(client.send("Mffff,2:0102"), "E01");
+    assert_eq!(client.send("M10,2:01"), "E01");
+    assert_eq!(client.send("m10000,1"), "E01");
+    client.kill();
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_gdb_checksum() {
+    let (mut client, server) = connect(COUNT);
+    client.send_raw(b"$g#00");
+    assert_eq!(client.read_byte(), b'-');
+    assert_eq!(client.send("g"), "000000fd000224");
+    client.kill();
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_gdb_breakpoint_and_step() {
+    let (mut client, server) = connect(COUNT);
+    assert_eq!(client.send("Z0,207,1"), "OK");
+    assert_eq!(client.send("c"), "T05swbreak:;");
+    assert_eq!(client.send("p1"), "01");
+    assert_eq!(client.send("p4"), "0702");
+    assert_eq!(client.send("c"), "T05swbreak:;");
+    assert_eq!(client.send("p1"), "02");
+    assert_eq!(client.send("s"), "S05");
+    assert_eq!(client.send("p4"), "0202");
+    assert_eq!(client.send("vCont?"), "vCont;c;C;s;S");
+    assert_eq!(client.send("vCont;s:1"), "S05");
+    assert_eq!(client.send("p4"), "0302");
+    assert_eq!(client.send("z0,207,1"), "OK");
+    // without the breakpoint the program runs until the KIL stops it
+    assert_eq!(client.send("vCont;c"), "S05");
+    assert_eq!(client.send("m10,1"), "05");
+    client.kill();
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_gdb_watchpoint() {
+    let (mut client, server) = connect(COUNT);
+    assert_eq!(client.send("Z2,10,1"), "OK");
+    assert_eq!(client.send("c"), "T05watch:0010;");
+    assert_eq!(client.send("p4"), "0502");
+    assert_eq!(client.send("z2,10,1"), "OK");
+    assert_eq!(client.send("Z3,10,1"), "OK");
+    assert_eq!(client.send("c"), "S05");
+    // hardware breakpoints are not supported
+    assert_eq!(client.send("Z1,200,1"), "");
+    client.kill();
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_gdb_queries() {
+    let (mut client, server) = connect(COUNT);
+    let supported = client.send("qSupported:multiprocess+;swbreak+");
+    assert_eq!(supported.contains("qXfer:features:read+"), true);
+    let target = client.send("qXfer:features:read:target.xml:0,fff");
+    assert_eq!(target.starts_with("l<?xml"), true);
+    assert_eq!(target.contains(r#"<reg name="pc" bitsize="16" type="code_ptr"/>"#), true);
+    assert_eq!(client.send("qXfer:features:read:target.xml:0,5"), "m<?xml");
+    assert_eq!(client.send("qAttached"), "1");
+    assert_eq!(client.send("vMustReplyEmpty"), "");
+    assert_eq!(client.send("QStartNoAckMode"), "OK");
+    client.ack = false;
+    assert_eq!(client.send("p5"), "24");
+    assert_eq!(client.send("D"), "OK");
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_gdb_interrupt() {
+    // JMP $0200
+    let (mut client, server) = connect(&[0x4C, 0x00, 0x02]);
+    client.send_raw(Client::packet("c").as_bytes());
+    assert_eq!(client.read_byte(), b'+');
+    client.send_raw(&[0x03]);
+    assert_eq!(client.receive(), "S02");
+    assert_eq!(client.send("p4"), "0002");
+    client.kill();
+    server.join().unwrap().unwrap();
+}
@@ -0,0 +1,123 @@
+use std::io::Cursor;
+
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::golden::{TraceComparison, TraceRecord};
+use crate::error::EmulatorError;
+use crate::memory::ram::Ram;
+
+// LDX #$00 ; INX ; STX $10 ; CPX #$03 ; BNE $C002 ; KIL
+const PROGRAM: [u8; 10] = [0xA2, 0x00, 0xE8, 0x86, 0x10, 0xE0, 0x03, 0xD0, 0xF9, 0x02];
+
+const REFERENCE: &str = "\
+C000  A2 00     LDX #$00                        A:00 X:00 Y:00 P:24 SP:FD CYC:7
+C002  E8        INX                             A:00 X:00 Y:00 P:26 SP:FD CYC:9
+C003  86 10     STX $10 = 00                    A:00 X:01 Y:00 P:24 SP:FD CYC:11
+C005  E0 03     CPX #$03                        A:00 X:01 Y:00 P:24 SP:FD CYC:14
+C007  D0 F9     BNE $C002                       A:00 X:01 Y:00 P:A4 SP:FD CYC:16
+C002  E8        INX                             A:00 X:01 Y:00 P:A4 SP:FD CYC:19
+";
+
+fn get_golden_cpu() -> Cpu6502 {
+    let mut cpu = Cpu6502::new(Ram::new());
+    cpu.memory.load(&PROGRAM, 0xC000).unwrap();
+    cpu.registers.pc = 0xC000;
+    cpu.registers.sp = 0xFD;
+    cpu.registers.status = 0x24;
+    cpu.cycles = 7;
+    cpu
+}
+
+#[test]
+fn test_parse_nestest_line() {
+    let record = TraceRecord::parse(
+        "C6BD  04 A9    *NOP $A9 = 00                    A:AA X:97 Y:4E P:EF SP:F5 PPU: 77,  0 CYC:8788",
+    ).unwrap();
+    assert_eq!(record.pc, 0xC6BD);
+    assert_eq!(record.bytes, vec![0x04, 0xA9]);
+    assert_eq!(record.mnemonic, "NOP");
+    assert_eq!(record.a, Some(0xAA));
+    assert_eq!(record.p, Some(0xEF));
+    assert_eq!(record.sp, Some(0xF5));
+    assert_eq!(record.cycles, Some(8788));
+    assert_eq!(TraceRecord::parse("not a trace"), None);
+}
+
+#[test]
+fn test_golden_match() {
+    let mut cpu = get_golden_cpu();
+
+    assert_eq!(cpu.compare_with_trace(Cursor::new(REFERENCE), 2), Ok(TraceComparison::Matched(6)));
+    assert_eq!(cpu.registers.pc, 0xC003);
+}
+
+#[test]
+fn test_golden_divergence() {
+    let mut cpu = get_golden_cpu();
+    let reference = REFERENCE.replace("C005  E0 03     CPX #$03                        A:00 X:01 Y:00 P:24", "C005  E0 03     CPX #$03                        A:00 X:01 Y:00 P:25");
+
+    let divergence = match cpu.compare_with_trace(Cursor::new(reference), 2).unwrap() {
+        TraceComparison::Diverged(divergence) => divergence,
+        comparison => panic!("unexpected {:?}", comparison),
+    };
+    assert_eq!(divergence.line, 4);
+    assert_eq!(divergence.field, "P");
+    assert_eq!(divergence.history.len(), 2);
+    assert!(divergence.history[1].starts_with("C003"));
+    assert!(divergence.actual.contains("P:24"));
+    assert!(divergence.to_string().starts_with("divergence on P at line 4\n"));
+}
+
+#[test]
+fn test_golden_without_cycles() {
+    let mut cpu = get_golden_cpu();
+    // another emulator without the cycle count and a different spacing
+    let reference = "C000 A2 00 LDX #$00 A:00 X:00 Y:00 P:24 SP:FD\nC002 E8 INX A:00 X:00 Y:00 P:26 SP:FD\n";
+
+    assert_eq!(cpu.compare_with_trace(Cursor::new(reference), 0), Ok(TraceComparison::Matched(2)));
+}
+
+#[test]
+fn test_golden_end() {
+    let mut cpu = get_golden_cpu();
+    cpu.registers.pc = 0xC009;
+    // the reference goes on after the KIL that stops the cpu
+    let reference = "\
+C009  02       *KIL                             A:00 X:00 Y:00 P:24 SP:FD CYC:7
+C00A  00        BRK                             A:00 X:00 Y:00 P:24 SP:FD CYC:7
+";
+
+    match cpu.compare_with_trace(Cursor::new(reference), 1).unwrap() {
+        TraceComparison::Diverged(divergence) => {
+            assert_eq!(divergence.line, 2);
+            assert_eq!(divergence.field, "end");
+            assert_eq!(divergence.actual, "");
+            assert_eq!(divergence.history.len(), 1);
+        }
+        comparison => panic!("unexpected {:?}", comparison),
+    }
+}
+
+#[test]
+fn test_golden_invalid_reference() {
+    let mut cpu = get_golden_cpu();
+
+    assert_eq!(
+        cpu.compare_with_trace(Cursor::new("C000  A2 00  LDX #$00\nhello\n"), 0),
+        Err(EmulatorError::InvalidTrace { line: 2 })
+    );
+}
+
+#[test]
+fn test_golden_plp_with_break() {
+    let mut cpu = get_golden_cpu();
+    // LDA #$FF ; PHA ; PLP ; NOP, the break bit pulled from the stack is dropped
+    cpu.memory.load(&[0xA9, 0xFF, 0x48, 0x28, 0xEA], 0xC000).unwrap();
+    let reference = "\
+C000  A9 FF     LDA #$FF                        A:00 X:00 Y:00 P:24 SP:FD CYC:7
+C002  48        PHA                             A:FF X:00 Y:00 P:A4 SP:FD CYC:9
+C003  28        PLP                             A:FF X:00 Y:00 P:A4 SP:FC CYC:12
+C004  EA        NOP                             A:FF X:00 Y:00 P:EF SP:FD CYC:16
+";
+
+    assert_eq!(cpu.compare_with_trace(Cursor::new(reference), 0), Ok(TraceComparison::Matched(4)));
+}
@@ -0,0 +1,373 @@
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::flag::Flag;
+use crate::cpu::function::{
+    ahx, anc, arr, asr, axs, dcp, isc, las, lax, lxa, rla, rra, sax, shx, shy, slo, sre, tas, xaa,
+};
+use super::*;
+
+#[test]
+fn test_lax_zero_page() {
+    let mut cpu = get_cpu();
+
+    cpu.memory.write(0x0001, 0x10);
+    cpu.memory.write(0x0010, 0x85);
+    run(&mut cpu, lax, AddressingMode::ZeroPage);
+    assert_eq!(cpu.registers.a, 0x85);
+    assert_eq!(cpu.registers.x, 0x85);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+}
+
+#[test]
+fn test_sax_zero_page() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0xF0;
+    cpu.registers.x = 0x3C;
+    cpu.memory.write(0x0001, 0x10);
+    run(&mut cpu, sax, AddressingMode::ZeroPage);
+    assert_eq!(cpu.memory.read(0x0010), 0x30);
+}
+
+#[test]
+fn test_slo_zero_page() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0x02;
+    cpu.memory.write(0x0001, 0x10);
+    cpu.memory.write(0x0010, 0x81);
+    run(&mut cpu, slo, AddressingMode::ZeroPage);
+    assert_eq!(cpu.memory.read(0x0010), 0x02);
+    assert_eq!(cpu.registers.a, 0x02);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+}
+
+#[test]
+fn test_rla_zero_page() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0xFF;
+    cpu.set_flag(Flag::Carry, true);
+    cpu.memory.write(0x0001, 0x10);
+    cpu.memory.write(0x0010, 0x81);
+    run(&mut cpu, rla, AddressingMode::ZeroPage);
+    assert_eq!(cpu.memory.read(0x0010), 0x03);
+    assert_eq!(cpu.registers.a, 0x03);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+}
+
+#[test]
+fn test_sre_zero_page() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0x01;
+    cpu.memory.write(0x0001, 0x10);
+    cpu.memory.write(0x0010, 0x03);
+    run(&mut cpu, sre, AddressingMode::ZeroPage);
+    assert_eq!(cpu.memory.read(0x0010), 0x01);
+    assert_eq!(cpu.registers.a, 0x00);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), true);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+}
+
+#[test]
+fn test_rra_zero_page() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0x10;
+    cpu.set_flag(Flag::Carry, false);
+    cpu.memory.write(0x0001, 0x10);
+    cpu.memory.write(0x0010, 0x03);
+    run(&mut cpu, rra, AddressingMode::ZeroPage);
+    assert_eq!(cpu.memory.read(0x0010), 0x01);
+    // the bit rotated out is the carry of the addition
+    assert_eq!(cpu.registers.a, 0x12);
+    assert_eq!(cpu.get_flag(Flag::Carry), false);
+    assert_eq!(cpu.get_flag(Flag::Overflow), false);
+}
+
+#[test]
+fn test_dcp_zero_page() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0x10;
+    cpu.memory.write(0x0001, 0x10);
+    cpu.memory.write(0x0010, 0x11);
+    run(&mut cpu, dcp, AddressingMode::ZeroPage);
+    assert_eq!(cpu.memory.read(0x0010), 0x10);
+    assert_eq!(cpu.registers.a, 0x10);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), true);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+}
+
+#[test]
+fn test_isc_zero_page() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0x20;
+    cpu.set_flag(Flag::Carry, true);
+    cpu.memory.write(0x0001, 0x10);
+    cpu.memory.write(0x0010, 0x0F);
+    run(&mut cpu, isc, AddressingMode::ZeroPage);
+    assert_eq!(cpu.memory.read(0x0010), 0x10);
+    assert_eq!(cpu.registers.a, 0x10);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+}
+
+#[test]
+fn test_anc_immediate() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0xF0;
+    cpu.memory.write(0x0001, 0x80);
+    run(&mut cpu, anc, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.a, 0x80);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+}
+
+#[test]
+fn test_asr_immediate() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0xFF;
+    cpu.memory.write(0x0001, 0x03);
+    run(&mut cpu, asr, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.a, 0x01);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+}
+
+#[test]
+fn test_arr_binary() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0x80;
+    cpu.set_flag(Flag::Carry, false);
+    cpu.memory.write(0x0001, 0xFF);
+    run(&mut cpu, arr, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.a, 0x40);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Overflow), true);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+
+    cpu.registers.pc = 0x0000;
+    cpu.registers.a = 0xFF;
+    cpu.set_flag(Flag::Carry, true);
+    run(&mut cpu, arr, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.a, 0xFF);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Overflow), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+}
+
+#[test]
+fn test_arr_decimal() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0x99;
+    cpu.set_flag(Flag::Carry, false);
+    cpu.set_flag(Flag::Decimal, true);
+    cpu.memory.write(0x0001, 0xFF);
+    run(&mut cpu, arr, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.a, 0xA2);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Overflow), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+}
+
+#[test]
+fn test_xaa_magic_constant() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0x00;
+    cpu.registers.x = 0xFF;
+    cpu.memory.write(0x0001, 0xFF);
+    run(&mut cpu, xaa, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.a, 0xEE);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+
+    cpu.registers.pc = 0x0000;
+    cpu.magic_constant = 0xFF;
+    cpu.registers.a = 0x00;
+    cpu.registers.x = 0x3C;
+    run(&mut cpu, xaa, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.a, 0x3C);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+}
+
+#[test]
+fn test_lxa_magic_constant() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0x01;
+    cpu.memory.write(0x0001, 0x0F);
+    run(&mut cpu, lxa, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.a, 0x0F);
+    assert_eq!(cpu.registers.x, 0x0F);
+
+    cpu.registers.pc = 0x0000;
+    cpu.magic_constant = 0x00;
+    cpu.registers.a = 0x01;
+    run(&mut cpu, lxa, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.a, 0x01);
+    assert_eq!(cpu.registers.x, 0x01);
+}
+
+#[test]
+fn test_lxa_opcode() {
+    let mut cpu = get_cpu();
+
+    cpu.magic_constant = 0xFF;
+    cpu.memory.write(0x0000, 0xAB);
+    cpu.memory.write(0x0001, 0x5A);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.a, 0x5A);
+    assert_eq!(cpu.registers.x, 0x5A);
+    assert_eq!(cpu.registers.pc, 0x0002);
+}
+
+#[test]
+fn test_axs_immediate() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0xF0;
+    cpu.registers.x = 0x3C;
+    cpu.memory.write(0x0001, 0x10);
+    run(&mut cpu, axs, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.x, 0x20);
+    assert_eq!(cpu.registers.a, 0xF0);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+}
+
+#[test]
+fn test_axs_borrow() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0xF0;
+    cpu.registers.x = 0x3C;
+    cpu.set_flag(Flag::Decimal, true);
+    cpu.memory.write(0x0001, 0x40);
+    run(&mut cpu, axs, AddressingMode::Immediate);
+    assert_eq!(cpu.registers.x, 0xF0);
+    assert_eq!(cpu.get_flag(Flag::Carry), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+}
+
+#[test]
+fn test_shx_same_page() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.x = 0xFF;
+    cpu.registers.y = 0x10;
+    cpu.memory.write(0x0001, 0x00);
+    cpu.memory.write(0x0002, 0x12);
+    run(&mut cpu, shx, AddressingMode::AbsoluteY);
+    assert_eq!(cpu.memory.read(0x1210), 0x13);
+}
+
+#[test]
+fn test_shx_page_cross() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.x = 0x05;
+    cpu.registers.y = 0x20;
+    cpu.memory.write(0x0001, 0xF0);
+    cpu.memory.write(0x0002, 0x12);
+    run(&mut cpu, shx, AddressingMode::AbsoluteY);
+    // the stored value replaces the high byte of the address
+    assert_eq!(cpu.memory.read(0x1310), 0x00);
+    assert_eq!(cpu.memory.read(0x0110), 0x01);
+}
+
+#[test]
+fn test_shy_same_page() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.y = 0xFF;
+    cpu.registers.x = 0x01;
+    cpu.memory.write(0x0001, 0x00);
+    cpu.memory.write(0x0002, 0x12);
+    run(&mut cpu, shy, AddressingMode::AbsoluteX);
+    assert_eq!(cpu.memory.read(0x1201), 0x13);
+}
+
+#[test]
+fn test_shy_page_cross() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.y = 0xFF;
+    cpu.registers.x = 0x20;
+    cpu.memory.write(0x0001, 0xF0);
+    cpu.memory.write(0x0002, 0x12);
+    run(&mut cpu, shy, AddressingMode::AbsoluteX);
+    assert_eq!(cpu.memory.read(0x1310), 0x13);
+}
+
+#[test]
+fn test_ahx_absolute_y() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0xF0;
+    cpu.registers.x = 0x3F;
+    cpu.registers.y = 0x10;
+    cpu.memory.write(0x0001, 0x00);
+    cpu.memory.write(0x0002, 0x12);
+    run(&mut cpu, ahx, AddressingMode::AbsoluteY);
+    assert_eq!(cpu.memory.read(0x1210), 0x10);
+}
+
+#[test]
+fn test_ahx_indirect_y() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0xFF;
+    cpu.registers.x = 0xFF;
+    cpu.registers.y = 0x10;
+    cpu.memory.write(0x0001, 0x20);
+    cpu.memory.write(0x0020, 0x00);
+    cpu.memory.write(0x0021, 0x12);
+    run(&mut cpu, ahx, AddressingMode::IndirectY);
+    assert_eq!(cpu.memory.read(0x1210), 0x13);
+}
+
+#[test]
+fn test_tas_absolute_y() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = 0xF0;
+    cpu.registers.x = 0x3F;
+    cpu.registers.y = 0x10;
+    cpu.memory.write(0x0001, 0x00);
+    cpu.memory.write(0x0002, 0x12);
+    run(&mut cpu, tas, AddressingMode::AbsoluteY);
+    assert_eq!(cpu.registers.sp, 0x30);
+    assert_eq!(cpu.memory.read(0x1210), 0x10);
+}
+
+#[test]
+fn test_las_absolute_y() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.sp = 0x3F;
+    cpu.registers.y = 0x10;
+    cpu.memory.write(0x0001, 0x00);
+    cpu.memory.write(0x0002, 0x12);
+    cpu.memory.write(0x1210, 0xF3);
+    run(&mut cpu, las, AddressingMode::AbsoluteY);
+    assert_eq!(cpu.registers.a, 0x33);
+    assert_eq!(cpu.registers.x, 0x33);
+    assert_eq!(cpu.registers.sp, 0x33);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), false);
+}
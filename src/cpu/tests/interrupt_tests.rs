@@ -0,0 +1,115 @@
+use crate::cpu::flag::Flag;
+use super::*;
+
+fn set_vectors(cpu: &mut crate::cpu::cpu_6502::Cpu6502) {
+    // NMI -> $9000, RESET -> $A000, IRQ -> $8000
+    cpu.memory.write(0xFFFA, 0x00);
+    cpu.memory.write(0xFFFB, 0x90);
+    cpu.memory.write(0xFFFC, 0x00);
+    cpu.memory.write(0xFFFD, 0xA0);
+    cpu.memory.write(0xFFFE, 0x00);
+    cpu.memory.write(0xFFFF, 0x80);
+}
+
+#[test]
+fn test_reset() {
+    let mut cpu = get_cpu();
+
+    set_vectors(&mut cpu);
+    cpu.registers.sp = 0x00;
+    cpu.registers.status = 0x00;
+    let before = cpu.cycles;
+    cpu.reset();
+    assert_eq!(cpu.registers.pc, 0xA000);
+    assert_eq!(cpu.registers.sp, 0xFD);
+    assert_eq!(cpu.get_flag(Flag::Interrupt), true);
+    assert_eq!(cpu.cycles - before, 7);
+    assert_eq!(cpu.memory.read(0x0100), 0x00);
+}
+
+#[test]
+fn test_irq() {
+    let mut cpu = get_cpu();
+
+    set_vectors(&mut cpu);
+    cpu.registers.pc = 0x1234;
+    cpu.registers.sp = 0xFF;
+    cpu.registers.status = Flag::Carry as u8;
+    cpu.assert_irq();
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cycles, 7);
+    assert_eq!(cpu.registers.pc, 0x8000);
+    assert_eq!(cpu.get_flag(Flag::Interrupt), true);
+    assert_eq!(cpu.memory.read(0x01FF), 0x12);
+    assert_eq!(cpu.memory.read(0x01FE), 0x34);
+    // break clear, unused set
+    assert_eq!(cpu.memory.read(0x01FD), 0b0010_0001);
+}
+
+#[test]
+fn test_irq_masked() {
+    let mut cpu = get_cpu();
+
+    set_vectors(&mut cpu);
+    cpu.registers.pc = 0x1234;
+    cpu.memory.write(0x1234, 0xEA);
+    cpu.set_flag(Flag::Interrupt, true);
+    cpu.assert_irq();
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cycles, 2);
+    assert_eq!(cpu.registers.pc, 0x1235);
+}
+
+#[test]
+fn test_irq_level_triggered() {
+    let mut cpu = get_cpu();
+
+    set_vectors(&mut cpu);
+    cpu.memory.write(0x8000, 0x58); // CLI
+    cpu.memory.write(0x8001, 0xEA); // NOP
+    cpu.set_flag(Flag::Interrupt, false);
+    cpu.assert_irq();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x8000);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x8001);
+    // the line is still asserted, the interrupt is taken again
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x8000);
+
+    cpu.release_irq();
+    cpu.execute_instruction().unwrap();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x8002);
+}
+
+#[test]
+fn test_nmi() {
+    let mut cpu = get_cpu();
+
+    set_vectors(&mut cpu);
+    cpu.registers.pc = 0x1234;
+    cpu.registers.sp = 0xFF;
+    cpu.memory.write(0x9000, 0xEA);
+    cpu.set_flag(Flag::Interrupt, true);
+    cpu.trigger_nmi();
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cycles, 7);
+    assert_eq!(cpu.registers.pc, 0x9000);
+    assert_eq!(cpu.memory.read(0x01FD) & Flag::Break as u8, 0);
+    // edge triggered, taken only once
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x9001);
+}
+
+#[test]
+fn test_nmi_priority() {
+    let mut cpu = get_cpu();
+
+    set_vectors(&mut cpu);
+    cpu.set_flag(Flag::Interrupt, false);
+    cpu.assert_irq();
+    cpu.trigger_nmi();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x9000);
+}
@@ -8,7 +8,7 @@ fn test_lda_immediate() {
     let mut cpu = get_cpu();
 
     cpu.memory.write(0x0001, 0x01);
-    lda(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, lda, AddressingMode::Immediate);
     assert_eq!(cpu.registers.a, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -20,7 +20,7 @@ fn test_lda_zero_page() {
 
     cpu.memory.write(0x0001, 0x01);
     cpu.memory.write(0x0002, 0x01);
-    lda(&mut cpu, AddressingMode::ZeroPage);
+    run(&mut cpu, lda, AddressingMode::ZeroPage);
     assert_eq!(cpu.registers.a, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -33,7 +33,7 @@ fn test_lda_zero_page_x() {
     cpu.memory.write(0x0001, 0x01);
     cpu.memory.write(0x0002, 0x01);
     cpu.registers.x = 0x01;
-    lda(&mut cpu, AddressingMode::ZeroPageX);
+    run(&mut cpu, lda, AddressingMode::ZeroPageX);
     assert_eq!(cpu.registers.a, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -46,7 +46,7 @@ fn test_lda_absolute() {
     cpu.memory.write(0x0001, 0x01);
     cpu.memory.write(0x0002, 0x01);
     cpu.memory.write(0x0101, 0x01);
-    lda(&mut cpu, AddressingMode::Absolute);
+    run(&mut cpu, lda, AddressingMode::Absolute);
     assert_eq!(cpu.registers.a, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -60,7 +60,7 @@ fn test_lda_absolute_x() {
     cpu.memory.write(0x0002, 0x01);
     cpu.memory.write(0x0102, 0x01);
     cpu.registers.x = 0x01;
-    lda(&mut cpu, AddressingMode::AbsoluteX);
+    run(&mut cpu, lda, AddressingMode::AbsoluteX);
     assert_eq!(cpu.registers.a, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -74,7 +74,7 @@ fn test_lda_absolute_y() {
     cpu.memory.write(0x0002, 0x01);
     cpu.memory.write(0x0102, 0x01);
     cpu.registers.y = 0x01;
-    lda(&mut cpu, AddressingMode::AbsoluteY);
+    run(&mut cpu, lda, AddressingMode::AbsoluteY);
     assert_eq!(cpu.registers.a, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -90,7 +90,7 @@ fn test_lda_indirect_x() {
     cpu.memory.write(0x0004, 0x01);
     cpu.memory.write(0x0101, 0x01);
     cpu.registers.x = 0x01;
-    lda(&mut cpu, AddressingMode::IndirectX);
+    run(&mut cpu, lda, AddressingMode::IndirectX);
     assert_eq!(cpu.registers.a, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -106,7 +106,7 @@ fn test_lda_indirect_y() {
     cpu.memory.write(0x0004, 0x01);
     cpu.memory.write(0x0102, 0x01);
     cpu.registers.y = 0x01;
-    lda(&mut cpu, AddressingMode::IndirectY);
+    run(&mut cpu, lda, AddressingMode::IndirectY);
     assert_eq!(cpu.registers.a, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -117,7 +117,7 @@ fn test_lda_negative() {
     let mut cpu = get_cpu();
 
     cpu.memory.write(0x0001, 0x80);
-    lda(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, lda, AddressingMode::Immediate);
     assert_eq!(cpu.registers.a, 0x80);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), true);
@@ -128,8 +128,34 @@ fn test_lda_zero() {
     let mut cpu = get_cpu();
 
     cpu.memory.write(0x0001, 0x00);
-    lda(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, lda, AddressingMode::Immediate);
     assert_eq!(cpu.registers.a, 0x00);
     assert_eq!(cpu.get_flag(Flag::Zero), true);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_lda_absolute_x_cycles() {
+    let mut cpu = get_cpu();
+
+    cpu.memory.write(0x0000, 0xBD);
+    cpu.memory.write(0x0001, 0x80);
+    cpu.memory.write(0x0002, 0x00);
+    cpu.registers.x = 0x01;
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cycles, 4);
+}
+
+#[test]
+fn test_lda_absolute_x_page_crossed_cycles() {
+    let mut cpu = get_cpu();
+
+    cpu.memory.write(0x0000, 0xBD);
+    cpu.memory.write(0x0001, 0xFF);
+    cpu.memory.write(0x0002, 0x00);
+    cpu.memory.write(0x0100, 0x42);
+    cpu.registers.x = 0x01;
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.a, 0x42);
+    assert_eq!(cycles, 5);
+}
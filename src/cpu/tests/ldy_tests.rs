@@ -8,7 +8,7 @@ fn test_ldy_immediate() {
     let mut cpu = get_cpu();
 
     cpu.memory.write(0x0001, 0x01);
-    ldy(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, ldy, AddressingMode::Immediate);
     assert_eq!(cpu.registers.y, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -20,7 +20,7 @@ fn test_ldy_zero_page() {
 
     cpu.memory.write(0x0001, 0x01);
     cpu.memory.write(0x0002, 0x01);
-    ldy(&mut cpu, AddressingMode::ZeroPage);
+    run(&mut cpu, ldy, AddressingMode::ZeroPage);
     assert_eq!(cpu.registers.y, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -33,7 +33,7 @@ fn test_ldy_zero_page_x() {
     cpu.memory.write(0x0001, 0x01);
     cpu.memory.write(0x0002, 0x01);
     cpu.registers.x = 0x01;
-    ldy(&mut cpu, AddressingMode::ZeroPageX);
+    run(&mut cpu, ldy, AddressingMode::ZeroPageX);
     assert_eq!(cpu.registers.y, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -46,7 +46,7 @@ fn test_ldy_zero_page_y() {
     cpu.memory.write(0x0001, 0x01);
     cpu.memory.write(0x0002, 0x01);
     cpu.registers.y = 0x01;
-    ldy(&mut cpu, AddressingMode::ZeroPageY);
+    run(&mut cpu, ldy, AddressingMode::ZeroPageY);
     assert_eq!(cpu.registers.y, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -59,7 +59,7 @@ fn test_ldy_absolute() {
     cpu.memory.write(0x0001, 0x01);
     cpu.memory.write(0x0002, 0x01);
     cpu.memory.write(0x0101, 0x01);
-    ldy(&mut cpu, AddressingMode::Absolute);
+    run(&mut cpu, ldy, AddressingMode::Absolute);
     assert_eq!(cpu.registers.y, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -73,7 +73,7 @@ fn test_ldy_absolute_x() {
     cpu.memory.write(0x0002, 0x01);
     cpu.memory.write(0x0102, 0x01);
     cpu.registers.x = 0x01;
-    ldy(&mut cpu, AddressingMode::AbsoluteX);
+    run(&mut cpu, ldy, AddressingMode::AbsoluteX);
     assert_eq!(cpu.registers.y, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -87,7 +87,7 @@ fn test_ldy_absolute_y() {
     cpu.memory.write(0x0002, 0x01);
     cpu.memory.write(0x0102, 0x01);
     cpu.registers.y = 0x01;
-    ldy(&mut cpu, AddressingMode::AbsoluteY);
+    run(&mut cpu, ldy, AddressingMode::AbsoluteY);
     assert_eq!(cpu.registers.y, 0x01);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -98,7 +98,7 @@ fn test_ldy_zero() {
     let mut cpu = get_cpu();
 
     cpu.memory.write(0x0001, 0x00);
-    ldy(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, ldy, AddressingMode::Immediate);
     assert_eq!(cpu.registers.y, 0x00);
     assert_eq!(cpu.get_flag(Flag::Zero), true);
     assert_eq!(cpu.get_flag(Flag::Negative), false);
@@ -109,7 +109,7 @@ fn test_ldy_negative() {
     let mut cpu = get_cpu();
 
     cpu.memory.write(0x0001, 0x80);
-    ldy(&mut cpu, AddressingMode::Immediate);
+    run(&mut cpu, ldy, AddressingMode::Immediate);
     assert_eq!(cpu.registers.y, 0x80);
     assert_eq!(cpu.get_flag(Flag::Zero), false);
     assert_eq!(cpu.get_flag(Flag::Negative), true);
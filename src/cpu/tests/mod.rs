@@ -1,4 +1,8 @@
+#![allow(clippy::bool_assert_comparison)]
+
+use crate::cpu::addressing_mode::AddressingMode;
 use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::operand::Operand;
 use crate::memory::ram::Ram;
 
 ///
@@ -12,6 +16,16 @@ pub fn get_cpu() -> Cpu6502 {
     cpu
 }
 
+///
+/// run a single instruction function like the execute stage does,
+/// the operand is decoded from the bytes following the program counter
+///
+pub fn run(cpu: &mut Cpu6502, execute: fn(&mut Cpu6502, Operand), mode: AddressingMode) {
+    let (operand, _) = mode.resolve(cpu, cpu.registers.pc.wrapping_add(1));
+    cpu.registers.pc = cpu.registers.pc.wrapping_add(mode.length() as u16);
+    execute(cpu, operand);
+}
+
 ///
 /// # adc
 /// Test the ADC instruction
@@ -82,6 +96,13 @@ mod bne_tests;
 #[cfg(test)]
 mod bpl_tests;
 
+///
+/// # breakpoint
+/// Test the breakpoints and the watchpoints
+///
+#[cfg(test)]
+mod breakpoint_tests;
+
 ///
 /// # brk
 /// Test the BRK instruction
@@ -131,6 +152,13 @@ mod cli_tests;
 #[cfg(test)]
 mod clv_tests;
 
+///
+/// # 65C02
+/// Test the instructions added by the 65C02
+///
+#[cfg(test)]
+mod cmos_tests;
+
 ///
 /// # CMP
 /// Test the CMP instruction
@@ -159,6 +187,13 @@ mod cpx_tests;
 #[cfg(test)]
 mod cpy_tests;
 
+///
+/// # dap
+/// Test the Debug Adapter Protocol server
+///
+#[cfg(test)]
+mod dap_tests;
+
 ///
 /// # DEC
 /// Test the DEC instruction
@@ -180,6 +215,13 @@ mod dex_tests;
 #[cfg(test)]
 mod dey_tests;
 
+///
+/// # disassembler
+/// Test the disassembly of machine code
+///
+#[cfg(test)]
+mod disassembler_tests;
+
 ///
 /// # EOR
 /// Test the EOR instruction
@@ -187,6 +229,41 @@ mod dey_tests;
 #[cfg(test)]
 mod eor_tests;
 
+///
+/// # errors
+/// Test the errors returned by the cpu, the memory and the assembler
+///
+#[cfg(test)]
+mod error_tests;
+
+///
+/// # expression
+/// Test the conditions of the breakpoints
+///
+#[cfg(test)]
+mod expression_tests;
+
+///
+/// # gdb
+/// Test the GDB remote protocol stub over a loopback socket
+///
+#[cfg(test)]
+mod gdb_tests;
+
+///
+/// # golden
+/// Test the comparison with a reference trace
+///
+#[cfg(test)]
+mod golden_tests;
+
+///
+/// # illegal
+/// Test the undocumented NMOS instructions
+///
+#[cfg(test)]
+mod illegal_tests;
+
 ///
 /// # INC
 /// Test the INC instruction
@@ -194,6 +271,20 @@ mod eor_tests;
 #[cfg(test)]
 mod inc_tests;
 
+///
+/// # decode
+/// Test the decode stage and the program counter
+///
+#[cfg(test)]
+mod decode_tests;
+
+///
+/// # interrupts
+/// Test the IRQ, NMI and RESET lines
+///
+#[cfg(test)]
+mod interrupt_tests;
+
 ///
 /// # INX
 /// Test the INX instruction
@@ -250,6 +341,13 @@ mod ldy_tests;
 #[cfg(test)]
 mod lsr_tests;
 
+///
+/// # monitor
+/// Test the commands of the machine language monitor
+///
+#[cfg(test)]
+mod monitor_tests;
+
 ///
 /// # NOP
 /// Test the NOP instruction
@@ -299,6 +397,13 @@ mod plp_tests;
 #[cfg(test)]
 mod pol_tests;
 
+///
+/// # rewind
+/// Test the reverse stepping
+///
+#[cfg(test)]
+mod rewind_tests;
+
 ///
 /// # ROL
 /// Test the ROL instruction
@@ -327,6 +432,20 @@ mod rti_tests;
 #[cfg(test)]
 mod rts_tests;
 
+///
+/// # run
+/// Test the run loops and the stepping
+///
+#[cfg(test)]
+mod run_tests;
+
+///
+/// # save state
+/// Test the snapshot and restore of the machine
+///
+#[cfg(test)]
+mod save_state_tests;
+
 ///
 /// # SBC
 /// Test the SBC instruction
@@ -355,6 +474,13 @@ mod sed_tests;
 #[cfg(test)]
 mod sei_tests;
 
+///
+/// # single step
+/// Test the runner of the per-opcode JSON test vectors
+///
+#[cfg(test)]
+mod single_step_tests;
+
 ///
 /// # STA
 /// Test the STA instruction
@@ -376,6 +502,13 @@ mod stx_tests;
 #[cfg(test)]
 mod sty_tests;
 
+///
+/// # strict
+/// Test the policies for undocumented and KIL opcodes
+///
+#[cfg(test)]
+mod strict_tests;
+
 ///
 /// # TAX
 /// Test the TAX instruction
@@ -390,6 +523,13 @@ mod tax_tests;
 #[cfg(test)]
 mod tay_tests;
 
+///
+/// # trace
+/// Test the execution trace
+///
+#[cfg(test)]
+mod trace_tests;
+
 ///
 /// # TSX
 /// Test the TSX instruction
@@ -411,9 +551,23 @@ mod txa_tests;
 #[cfg(test)]
 mod txs_tests;
 
+///
+/// # tick
+/// Test the cycle by cycle execution
+///
+#[cfg(test)]
+mod tick_tests;
+
 ///
 /// # TYA
 /// Test the TYA instruction
 ///
 #[cfg(test)]
-mod tya_tests;
\ No newline at end of file
+mod tya_tests;
+
+///
+/// # variants
+/// Test the differences between the cpu variants
+///
+#[cfg(test)]
+mod variant_tests;
\ No newline at end of file
@@ -0,0 +1,166 @@
+use std::fs;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::error::EmulatorError;
+use crate::monitor::{parse_number, Monitor};
+use super::*;
+
+fn get_monitor() -> Monitor {
+    let mut cpu = get_cpu();
+    // LDX #$00 ; INX ; STX $10 ; CPX #$05 ; BNE $0202 ; KIL
+    cpu.memory.load(&[0xA2, 0x00, 0xE8, 0x86, 0x10, 0xE0, 0x05, 0xD0, 0xF9, 0x02], 0x0200).unwrap();
+    cpu.registers.pc = 0x0200;
+    Monitor::new(cpu)
+}
+
+///
+/// run the commands and return everything the monitor answered
+///
+fn execute(monitor: &mut Monitor, commands: &[&str]) -> String {
+    let mut output = Vec::new();
+    for command in commands {
+        assert_eq!(monitor.execute(command, &mut output).unwrap(), true);
+    }
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_parse_number() {
+    assert_eq!(parse_number("$FFFF"), Ok(0xFFFF));
+    assert_eq!(parse_number("10"), Ok(0x10));
+    assert_eq!(parse_number("%11111111"), Ok(0xFF));
+    assert_eq!(parse_number("+255"), Ok(0xFF));
+    assert!(matches!(parse_number("$10000"), Err(EmulatorError::InvalidCommand { .. })));
+    assert!(matches!(parse_number("%2"), Err(EmulatorError::InvalidCommand { .. })));
+}
+
+#[test]
+fn test_monitor_registers() {
+    let mut monitor = get_monitor();
+    let output = execute(&mut monitor, &["r a=$FF x=%101 y=+20 pc=1234 p=A5"]);
+    assert_eq!(output, "PC:1234 A:FF X:05 Y:14 SP:FD P:A5 N.-..I.C CYC:7\n");
+    let output = execute(&mut monitor, &["r a=100", "r q=1"]);
+    assert_eq!(output, "error: not a byte: $100\nerror: unknown register: Q\n");
+}
+
+#[test]
+fn test_monitor_step() {
+    let mut monitor = get_monitor();
+    let output = execute(&mut monitor, &["z 2"]);
+    assert!(output.starts_with("0202  E8        INX"));
+    assert!(output.lines().nth(1).unwrap().starts_with("0203  86 10     STX $10 = 00"));
+    assert_eq!(monitor.cpu.registers.x, 1);
+}
+
+#[test]
+fn test_monitor_examine_and_deposit() {
+    let mut monitor = get_monitor();
+    let output = execute(&mut monitor, &["> $10 41 42 %1000011", "m 10 12"]);
+    assert_eq!(output, "0010  41 42 43                                         ABC\n");
+    assert_eq!(execute(&mut monitor, &["> 10"]), "error: missing bytes\n");
+    assert_eq!(execute(&mut monitor, &["> 10 1FF"]), "error: not a byte: 1FF\n");
+    // m goes on after the last line shown
+    assert!(execute(&mut monitor, &["m"]).starts_with("0013  00"));
+}
+
+#[test]
+fn test_monitor_disassemble() {
+    let mut monitor = get_monitor();
+    let output = execute(&mut monitor, &["d 200 207"]);
+    assert_eq!(
+        output,
+        "0200  A2 00     LDX #$00\n\
+         0202  E8        INX\n\
+         0203  86 10     STX $10\n\
+         0205  E0 05     CPX #$05\n\
+         0207  D0 F9     BNE $0202\n"
+    );
+    assert_eq!(execute(&mut monitor, &["d"]).lines().next(), Some("0209  02        KIL"));
+}
+
+#[test]
+fn test_monitor_go_limit_and_break() {
+    let mut cpu = get_cpu();
+    // JMP $0200
+    cpu.memory.load(&[0x4C, 0x00, 0x02], 0x0200).unwrap();
+    let mut monitor = Monitor::new(cpu);
+    let cycles = monitor.cpu.cycles;
+    let output = execute(&mut monitor, &["g 200 +25000"]);
+    assert!(output.starts_with("0200  4C 00 02  JMP $0200"));
+    assert_eq!(monitor.cpu.cycles - cycles, 25000 * 3);
+
+    // the endless loop runs until the break request
+    let request = monitor.break_request();
+    let breaker = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        request.store(true, Ordering::Relaxed);
+    });
+    let output = execute(&mut monitor, &["g"]);
+    breaker.join().unwrap();
+    assert!(output.starts_with("break\n0200  4C 00 02  JMP $0200"));
+}
+
+#[test]
+fn test_monitor_breakpoints() {
+    let mut monitor = get_monitor();
+    let output = execute(&mut monitor, &["break 205 if X == 3", "watch w 10 1f", "break"]);
+    assert_eq!(
+        output,
+        "breakpoint 0 at $0205\n\
+         watchpoint 1 on $0010-$001F\n\
+         0: break $0205, 0 hits, if X == 3\n\
+         1: watch w $0010-$001F, 0 hits\n"
+    );
+    let output = execute(&mut monitor, &["g"]);
+    assert!(output.starts_with("watchpoint 1, write $0010\n0205"));
+    let output = execute(&mut monitor, &["disable 1", "g"]);
+    assert!(output.starts_with("breakpoint 0\n0205"));
+    assert_eq!(monitor.cpu.registers.x, 3);
+    let output = execute(&mut monitor, &["delete 0", "g", "delete 0"]);
+    assert!(output.starts_with("stopped\n"));
+    assert!(output.ends_with("error: no breakpoint 0\n"));
+}
+
+#[test]
+fn test_monitor_watchpoint_range() {
+    let mut monitor = get_monitor();
+    assert_eq!(execute(&mut monitor, &["watch rw 1f 10"]), "error: the end is before the start\n");
+    assert_eq!(monitor.cpu.breakpoints().len(), 0);
+}
+
+#[test]
+fn test_monitor_temporary_breakpoint() {
+    let mut monitor = get_monitor();
+    execute(&mut monitor, &["tbreak 202", "g"]);
+    assert_eq!(monitor.cpu.registers.pc, 0x0202);
+    assert_eq!(monitor.cpu.breakpoints().len(), 0);
+}
+
+#[test]
+fn test_monitor_load_and_save() {
+    let path = std::env::temp_dir().join(format!("emul_monitor_{}.bin", std::process::id()));
+    let path = path.to_str().unwrap();
+    let mut monitor = get_monitor();
+    let output = execute(&mut monitor, &[&format!("s {} 200 209", path)]);
+    assert_eq!(output, "saved $000A bytes from $0200\n");
+
+    let mut monitor = Monitor::new(Cpu6502::new(Ram::new()));
+    let output = execute(&mut monitor, &[&format!("l {} $1000", path), "m 1000 1001"]);
+    fs::remove_file(path).unwrap();
+    assert_eq!(output, "loaded $000A bytes at $1000\n1000  A2 00                                            ..\n");
+}
+
+#[test]
+fn test_monitor_commands() {
+    let mut monitor = get_monitor();
+    assert_eq!(execute(&mut monitor, &["", "foo"]), "error: unknown command: foo, try help\n");
+    assert!(execute(&mut monitor, &["help"]).contains("set a breakpoint"));
+    assert_eq!(monitor.execute("x", &mut Vec::new()).unwrap(), false);
+
+    let mut output = Vec::new();
+    monitor.run("z\nx\nz\n".as_bytes(), &mut output).unwrap();
+    assert_eq!(monitor.cpu.registers.pc, 0x0202);
+}
@@ -0,0 +1,12 @@
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::function::php;
+use super::*;
+
+#[test]
+fn test_php_sets_break_and_unused() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.status = 0x01;
+    run(&mut cpu, php, AddressingMode::Implied);
+    assert_eq!(cpu.memory.read(0x01FD), 0x31);
+}
@@ -0,0 +1,13 @@
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::function::plp;
+use super::*;
+
+#[test]
+fn test_plp_clears_break_and_sets_unused() {
+    let mut cpu = get_cpu();
+
+    cpu.memory.write(0x01FE, 0xDF);
+    run(&mut cpu, plp, AddressingMode::Implied);
+    assert_eq!(cpu.registers.status, 0xEF);
+    assert_eq!(cpu.registers.sp, 0xFE);
+}
@@ -0,0 +1,113 @@
+use crate::cpu::cpu_6502::{Cpu6502, ExecutionState};
+use crate::memory::ram::Ram;
+
+// LDA #$01 ; STA $10 ; INC $10 ; JSR $0300 ; KIL
+// $0300: DEC $10 ; RTS
+fn get_program_cpu() -> Cpu6502 {
+    let mut cpu = Cpu6502::new(Ram::new());
+    cpu.reset();
+    cpu.memory.load(&[0xA9, 0x01, 0x85, 0x10, 0xE6, 0x10, 0x20, 0x00, 0x03, 0x02], 0x0200).unwrap();
+    cpu.memory.load(&[0xC6, 0x10, 0x60], 0x0300).unwrap();
+    cpu.registers.pc = 0x0200;
+    cpu.enable_rewind(16);
+    cpu
+}
+
+#[test]
+fn test_step_back() {
+    let mut cpu = get_program_cpu();
+    let sp = cpu.registers.sp;
+    let cycles = cpu.cycles;
+
+    cpu.run_instructions(5).unwrap();
+    assert_eq!(cpu.registers.pc, 0x0302);
+    assert_eq!(cpu.memory.read(0x0010), 0x01);
+    assert_eq!(cpu.rewind_depth(), 5);
+
+    // DEC $10
+    assert_eq!(cpu.step_back(), true);
+    assert_eq!(cpu.registers.pc, 0x0300);
+    assert_eq!(cpu.memory.read(0x0010), 0x02);
+    // JSR $0300, the return address is removed from the stack
+    assert_eq!(cpu.step_back(), true);
+    assert_eq!(cpu.registers.pc, 0x0206);
+    assert_eq!(cpu.registers.sp, sp);
+    assert_eq!(cpu.memory.read(0x01FF), 0x00);
+    assert_eq!(cpu.memory.read(0x01FE), 0x00);
+
+    cpu.step_back();
+    cpu.step_back();
+    assert_eq!(cpu.memory.read(0x0010), 0x00);
+    cpu.step_back();
+    assert_eq!(cpu.registers.a, 0x00);
+    assert_eq!(cpu.registers.pc, 0x0200);
+    assert_eq!(cpu.cycles, cycles);
+    assert_eq!(cpu.step_back(), false);
+}
+
+#[test]
+fn test_step_back_after_stop() {
+    let mut cpu = get_program_cpu();
+
+    cpu.run_until(|_| false).unwrap();
+    // running a stopped cpu changes nothing so it is not recorded
+    assert_eq!(cpu.execute_instruction().unwrap().0, ExecutionState::Stopped);
+    assert_eq!(cpu.step_back(), true);
+    assert_eq!(cpu.registers.pc, 0x0209);
+    // the cpu runs again once the KIL is undone
+    assert_eq!(cpu.step_back(), true);
+    assert_eq!(cpu.registers.pc, 0x0302);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.pc, 0x0209);
+}
+
+#[test]
+fn test_rewind_capacity() {
+    let mut cpu = get_program_cpu();
+
+    cpu.enable_rewind(3);
+    cpu.run_instructions(6).unwrap();
+    assert_eq!(cpu.rewind_depth(), 3);
+    cpu.step_back();
+    cpu.step_back();
+    cpu.step_back();
+    assert_eq!(cpu.registers.pc, 0x0206);
+    assert_eq!(cpu.step_back(), false);
+}
+
+#[test]
+fn test_rewind_cycles() {
+    let mut cpu = get_program_cpu();
+
+    cpu.run_instructions(5).unwrap();
+    // DEC $10 takes 5 cycles and JSR 6, so 7 cycles undo both
+    assert_eq!(cpu.rewind_cycles(7), 11);
+    assert_eq!(cpu.registers.pc, 0x0206);
+    assert_eq!(cpu.rewind_cycles(100), 10);
+    assert_eq!(cpu.registers.pc, 0x0200);
+}
+
+#[test]
+fn test_rewind_tick() {
+    let mut cpu = get_program_cpu();
+
+    for _ in 0..10 {
+        cpu.tick().unwrap();
+    }
+    // LDA, STA and INC
+    assert_eq!(cpu.rewind_depth(), 3);
+    assert_eq!(cpu.memory.read(0x0010), 0x02);
+    cpu.step_back();
+    assert_eq!(cpu.memory.read(0x0010), 0x01);
+    assert_eq!(cpu.registers.pc, 0x0204);
+}
+
+#[test]
+fn test_rewind_disabled() {
+    let mut cpu = get_program_cpu();
+
+    cpu.disable_rewind();
+    cpu.run_instructions(2).unwrap();
+    assert_eq!(cpu.rewind_depth(), 0);
+    assert_eq!(cpu.step_back(), false);
+}
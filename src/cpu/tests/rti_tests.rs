@@ -0,0 +1,19 @@
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::flag::Flag;
+use crate::cpu::function::rti;
+use super::*;
+
+#[test]
+fn test_rti() {
+    let mut cpu = get_cpu();
+
+    cpu.registers.sp = 0xFC;
+    cpu.memory.write(0x01FD, 0b1101_0011);
+    cpu.memory.write(0x01FE, 0x34);
+    cpu.memory.write(0x01FF, 0x12);
+    run(&mut cpu, rti, AddressingMode::Implied);
+    assert_eq!(cpu.registers.pc, 0x1234);
+    assert_eq!(cpu.registers.sp, 0xFF);
+    assert_eq!(cpu.registers.status, 0b1110_0011);
+    assert_eq!(cpu.get_flag(Flag::Break), false);
+}
@@ -0,0 +1,105 @@
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::run::StopReason;
+use crate::cpu::variant::{BrkBehavior, CpuVariant};
+use crate::memory::ram::Ram;
+
+// JSR $0300 ; INX ; KIL
+// $0300: INY ; JSR $0310 ; RTS
+// $0310: INY ; RTS
+fn get_program_cpu(variant: CpuVariant) -> Cpu6502 {
+    let mut cpu = Cpu6502::with_variant(Ram::new(), variant);
+    cpu.reset();
+    cpu.memory.load(&[0x20, 0x00, 0x03, 0xE8, 0x02], 0x0200).unwrap();
+    cpu.memory.load(&[0xC8, 0x20, 0x10, 0x03, 0x60], 0x0300).unwrap();
+    cpu.memory.load(&[0xC8, 0x60], 0x0310).unwrap();
+    cpu.registers.pc = 0x0200;
+    cpu
+}
+
+#[test]
+fn test_run_instructions() {
+    let mut cpu = get_program_cpu(CpuVariant::Nmos6502);
+
+    assert_eq!(cpu.run_instructions(2), Ok(StopReason::InstructionsExecuted));
+    assert_eq!(cpu.registers.pc, 0x0301);
+    assert_eq!(cpu.registers.y, 0x01);
+}
+
+#[test]
+fn test_run_cycles() {
+    let mut cpu = get_program_cpu(CpuVariant::Nmos6502);
+    let cycles = cpu.cycles;
+
+    // JSR, INY and JSR take 14 cycles, the last one goes past the budget
+    assert_eq!(cpu.run_cycles(10), Ok(StopReason::CyclesElapsed));
+    assert_eq!(cpu.cycles - cycles, 14);
+    assert_eq!(cpu.registers.pc, 0x0310);
+}
+
+#[test]
+fn test_run_until() {
+    let mut cpu = get_program_cpu(CpuVariant::Nmos6502);
+
+    assert_eq!(cpu.run_until(|cpu| cpu.registers.y == 2), Ok(StopReason::Condition));
+    assert_eq!(cpu.registers.pc, 0x0311);
+    assert_eq!(cpu.run_until(|_| false), Ok(StopReason::Stopped));
+    assert_eq!(cpu.registers.x, 0x01);
+}
+
+#[test]
+fn test_step_over() {
+    let mut cpu = get_program_cpu(CpuVariant::Nmos6502);
+    let sp = cpu.registers.sp;
+
+    assert_eq!(cpu.step_over(), Ok(StopReason::Stepped));
+    assert_eq!(cpu.registers.pc, 0x0203);
+    assert_eq!(cpu.registers.sp, sp);
+    assert_eq!(cpu.registers.y, 0x02);
+    assert_eq!(cpu.step_over(), Ok(StopReason::Stepped));
+    assert_eq!(cpu.registers.pc, 0x0204);
+    assert_eq!(cpu.step_over(), Ok(StopReason::Stopped));
+}
+
+#[test]
+fn test_step_out() {
+    let mut cpu = get_program_cpu(CpuVariant::Nmos6502);
+
+    cpu.run_instructions(2).unwrap();
+    // the RTS of the nested subroutine does not count
+    assert_eq!(cpu.step_out(), Ok(StopReason::Returned));
+    assert_eq!(cpu.registers.pc, 0x0203);
+    assert_eq!(cpu.registers.y, 0x02);
+}
+
+#[test]
+fn test_run_waiting() {
+    let mut cpu = Cpu6502::with_variant(Ram::new(), CpuVariant::Cmos65C02);
+
+    // WAI
+    cpu.memory.write(0x0000, 0xCB);
+    assert_eq!(cpu.run_instructions(5), Ok(StopReason::Waiting));
+    assert_eq!(cpu.run_cycles(5), Ok(StopReason::CyclesElapsed));
+}
+
+#[test]
+fn test_run_brk() {
+    let mut cpu = get_program_cpu(CpuVariant::Nmos6502);
+    // BRK ; NOP, the IRQ vector points to INX ; KIL
+    cpu.memory.load(&[0x00, 0xEA], 0x0400).unwrap();
+    cpu.memory.load(&[0xE8, 0x02], 0x0500).unwrap();
+    cpu.memory.load(&[0x00, 0x05], 0xFFFE).unwrap();
+    cpu.registers.pc = 0x0400;
+
+    // BRK goes through its vector like any other instruction
+    assert_eq!(cpu.run_until(|_| false), Ok(StopReason::Stopped));
+    assert_eq!(cpu.registers.x, 0x01);
+    assert_eq!(cpu.registers.pc, 0x0502);
+
+    cpu.reset();
+    cpu.registers.pc = 0x0400;
+    cpu.brk = BrkBehavior::Stop;
+    assert_eq!(cpu.run_until(|_| false), Ok(StopReason::Stopped));
+    // the interrupt is taken but the handler does not run
+    assert_eq!(cpu.registers.pc, 0x0500);
+    assert_eq!(cpu.registers.x, 0x01);
+}
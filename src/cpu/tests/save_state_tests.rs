@@ -0,0 +1,138 @@
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::save_state::SAVE_STATE_VERSION;
+use crate::cpu::variant::CpuVariant;
+use crate::error::EmulatorError;
+use crate::memory::address_decoder::AddressDecoder;
+use crate::memory::ram::Ram;
+use crate::memory::rom::Rom;
+
+// LDX #$00 ; INX ; STX $10 ; JMP $0202
+fn get_program_cpu() -> Cpu6502 {
+    let mut cpu = Cpu6502::with_variant(Ram::new(), CpuVariant::Cmos65C02);
+    cpu.reset();
+    cpu.memory.load(&[0xA2, 0x00, 0xE8, 0x86, 0x10, 0x4C, 0x02, 0x02], 0x0200).unwrap();
+    cpu.registers.pc = 0x0200;
+    cpu
+}
+
+#[test]
+fn test_save_state_round_trip() {
+    let mut cpu = get_program_cpu();
+
+    cpu.run_instructions(20).unwrap();
+    cpu.assert_irq();
+    let state = cpu.save_state().unwrap();
+    cpu.run_instructions(20).unwrap();
+
+    let mut restored = Cpu6502::new(Ram::new());
+    restored.load_state(&state).unwrap();
+    assert_eq!(restored.variant, CpuVariant::Cmos65C02);
+    assert_eq!(restored.irq_asserted(), true);
+    assert_eq!(restored.memory.read(0x0010), 0x06);
+    restored.run_instructions(20).unwrap();
+
+    assert_eq!(restored.registers.pc, cpu.registers.pc);
+    assert_eq!(restored.registers.x, cpu.registers.x);
+    assert_eq!(restored.registers.sp, cpu.registers.sp);
+    assert_eq!(restored.registers.status, cpu.registers.status);
+    assert_eq!(restored.cycles, cpu.cycles);
+    assert_eq!(restored.memory.dump(0x0000, 0x10000), cpu.memory.dump(0x0000, 0x10000));
+}
+
+#[test]
+fn test_save_state_version() {
+    let cpu = get_program_cpu();
+    let mut state = cpu.save_state().unwrap();
+
+    state[4..6].copy_from_slice(&(SAVE_STATE_VERSION + 1).to_le_bytes());
+    let mut restored = Cpu6502::new(Ram::new());
+    assert_eq!(
+        restored.load_state(&state),
+        Err(EmulatorError::SaveStateVersion { version: SAVE_STATE_VERSION + 1 })
+    );
+}
+
+#[test]
+fn test_save_state_invalid() {
+    let cpu = get_program_cpu();
+    let state = cpu.save_state().unwrap();
+    let mut restored = Cpu6502::new(Ram::new());
+
+    assert!(matches!(restored.load_state(b"nope"), Err(EmulatorError::InvalidSaveState { .. })));
+    assert!(matches!(
+        restored.load_state(&state[..state.len() - 1]),
+        Err(EmulatorError::InvalidSaveState { .. })
+    ));
+    let mut longer = state.clone();
+    longer.push(0x00);
+    assert!(matches!(restored.load_state(&longer), Err(EmulatorError::InvalidSaveState { .. })));
+}
+
+#[test]
+fn test_save_state_memory_map() {
+    let mut decoder = AddressDecoder::new();
+    decoder.map(0x0000, 0x7FFF, Ram::new());
+    decoder.map(0x8000, 0xFFFF, Rom::new(&[0xEA; 0x8000]));
+    let mut cpu = Cpu6502::new(decoder);
+    cpu.memory.write(0x1234, 0x42);
+    let state = cpu.save_state().unwrap();
+
+    let mut decoder = AddressDecoder::new();
+    decoder.map(0x0000, 0x7FFF, Ram::new());
+    decoder.map(0x8000, 0xFFFF, Rom::new(&[0xEA; 0x8000]));
+    let mut restored = Cpu6502::new(decoder);
+    restored.load_state(&state).unwrap();
+    assert_eq!(restored.memory.read(0x1234), 0x42);
+
+    let mut decoder = AddressDecoder::new();
+    decoder.map(0x0000, 0xFFFF, Ram::new());
+    let mut restored = Cpu6502::new(decoder);
+    assert!(matches!(restored.load_state(&state), Err(EmulatorError::InvalidSaveState { .. })));
+}
+
+#[test]
+fn test_save_state_failed_load_changes_nothing() {
+    let mut saved = get_program_cpu();
+    saved.run_instructions(20).unwrap();
+    let mut state = saved.save_state().unwrap();
+    state.push(0x00);
+
+    let mut cpu = Cpu6502::new(Ram::new());
+    cpu.memory.write(0x0010, 0x42);
+    cpu.registers.x = 0x24;
+    cpu.registers.pc = 0x1234;
+    assert!(matches!(cpu.load_state(&state), Err(EmulatorError::InvalidSaveState { .. })));
+    assert_eq!(cpu.variant, CpuVariant::Nmos6502);
+    assert_eq!(cpu.registers.x, 0x24);
+    assert_eq!(cpu.registers.pc, 0x1234);
+    assert_eq!(cpu.memory.read(0x0010), 0x42);
+    assert_eq!(cpu.memory.read(0x0200), 0x00);
+
+    // the first region matches and is restored before the second one is found different
+    let mut decoder = AddressDecoder::new();
+    decoder.map(0x0000, 0x7FFF, Ram::new());
+    decoder.map(0x8000, 0xFFFF, Ram::new());
+    let mut saved = Cpu6502::new(decoder);
+    saved.memory.write(0x0010, 0x99);
+    let state = saved.save_state().unwrap();
+
+    let mut decoder = AddressDecoder::new();
+    decoder.map(0x0000, 0x7FFF, Ram::new());
+    decoder.map(0x8000, 0xBFFF, Ram::new());
+    let mut cpu = Cpu6502::new(decoder);
+    cpu.memory.write(0x0010, 0x42);
+    assert!(matches!(cpu.load_state(&state), Err(EmulatorError::InvalidSaveState { .. })));
+    assert_eq!(cpu.memory.read(0x0010), 0x42);
+}
+
+#[test]
+fn test_save_state_in_instruction() {
+    let mut cpu = get_program_cpu();
+
+    cpu.tick().unwrap();
+    assert!(cpu.save_state().is_err());
+    while cpu.in_instruction() {
+        cpu.tick().unwrap();
+    }
+    assert!(cpu.save_state().is_ok());
+}
@@ -0,0 +1,103 @@
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::decimal_mode::DecimalMode;
+use crate::cpu::flag::Flag;
+use crate::cpu::function::sbc;
+use super::*;
+
+fn sbc_decimal(decimal_mode: DecimalMode, a: u8, b: u8, carry: bool) -> Cpu6502 {
+    let mut cpu = get_cpu();
+
+    cpu.decimal_mode = decimal_mode;
+    cpu.registers.a = a;
+    cpu.memory.write(0x0001, b);
+    cpu.set_flag(Flag::Decimal, true);
+    cpu.set_flag(Flag::Carry, carry);
+    run(&mut cpu, sbc, AddressingMode::Immediate);
+    cpu
+}
+
+fn sbc_binary(a: u8, b: u8, carry: bool) -> Cpu6502 {
+    let mut cpu = get_cpu();
+
+    cpu.registers.a = a;
+    cpu.memory.write(0x0001, b);
+    cpu.set_flag(Flag::Carry, carry);
+    run(&mut cpu, sbc, AddressingMode::Immediate);
+    cpu
+}
+
+#[test]
+fn test_sbc_borrow_of_ff() {
+    let cpu = sbc_binary(0x05, 0xFF, false);
+    assert_eq!(cpu.registers.a, 0x05);
+    assert_eq!(cpu.get_flag(Flag::Carry), false);
+}
+
+#[test]
+fn test_sbc_overflow() {
+    let cpu = sbc_binary(0x80, 0x01, true);
+    assert_eq!(cpu.registers.a, 0x7F);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Overflow), true);
+    let cpu = sbc_binary(0x00, 0x00, false);
+    assert_eq!(cpu.registers.a, 0xFF);
+    assert_eq!(cpu.get_flag(Flag::Carry), false);
+    assert_eq!(cpu.get_flag(Flag::Overflow), false);
+}
+
+#[test]
+fn test_sbc_decimal_simple() {
+    let cpu = sbc_decimal(DecimalMode::Nmos, 0x46, 0x12, true);
+    assert_eq!(cpu.registers.a, 0x34);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+}
+
+#[test]
+fn test_sbc_decimal_half_borrow() {
+    let cpu = sbc_decimal(DecimalMode::Nmos, 0x40, 0x13, true);
+    assert_eq!(cpu.registers.a, 0x27);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+}
+
+#[test]
+fn test_sbc_decimal_borrow_in() {
+    let cpu = sbc_decimal(DecimalMode::Nmos, 0x32, 0x02, false);
+    assert_eq!(cpu.registers.a, 0x29);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+}
+
+#[test]
+fn test_sbc_decimal_borrow_out() {
+    let cpu = sbc_decimal(DecimalMode::Nmos, 0x12, 0x21, true);
+    assert_eq!(cpu.registers.a, 0x91);
+    assert_eq!(cpu.get_flag(Flag::Carry), false);
+}
+
+#[test]
+fn test_sbc_decimal_nmos_flags() {
+    // the NMOS flags come from the binary subtraction 0x00 - 0x01 = 0xFF
+    let cpu = sbc_decimal(DecimalMode::Nmos, 0x00, 0x01, true);
+    assert_eq!(cpu.registers.a, 0x99);
+    assert_eq!(cpu.get_flag(Flag::Carry), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+    assert_eq!(cpu.get_flag(Flag::Zero), false);
+}
+
+#[test]
+fn test_sbc_decimal_cmos() {
+    let cpu = sbc_decimal(DecimalMode::Cmos, 0x12, 0x21, true);
+    assert_eq!(cpu.registers.a, 0x91);
+    assert_eq!(cpu.get_flag(Flag::Carry), false);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+
+    let cpu = sbc_decimal(DecimalMode::Cmos, 0x21, 0x21, true);
+    assert_eq!(cpu.registers.a, 0x00);
+    assert_eq!(cpu.get_flag(Flag::Zero), true);
+}
+
+#[test]
+fn test_sbc_decimal_disabled() {
+    let cpu = sbc_decimal(DecimalMode::Disabled, 0x10, 0x01, true);
+    assert_eq!(cpu.registers.a, 0x0F);
+}
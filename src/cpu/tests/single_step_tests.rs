@@ -0,0 +1,124 @@
+use std::fs;
+
+use crate::cpu::single_step::{parse_vectors, run_directory, run_vector, BusCycle, VectorReport};
+use crate::cpu::variant::CpuVariant;
+use crate::error::EmulatorError;
+use crate::util::json::{parse, JsonValue};
+
+// INC $10 with the dummy write of the old value of the NMOS read-modify-write instructions
+const INC_ZERO_PAGE: &str = r#"[
+    {
+        "name": "e6 10 00",
+        "initial": { "pc": 512, "s": 253, "a": 0, "x": 0, "y": 0, "p": 36, "ram": [[512, 230], [513, 16], [16, 127]] },
+        "final": { "pc": 514, "s": 253, "a": 0, "x": 0, "y": 0, "p": 164, "ram": [[512, 230], [513, 16], [16, 128]] },
+        "cycles": [[512, 230, "read"], [513, 16, "read"], [16, 127, "read"], [16, 127, "write"], [16, 128, "write"]]
+    }
+]"#;
+
+// LDA #$00 expected to leave the zero flag clear, wrong on purpose
+const WRONG_LDA: &str = r#"[
+    {
+        "name": "a9 00 01",
+        "initial": { "pc": 512, "s": 253, "a": 1, "x": 0, "y": 0, "p": 36, "ram": [[512, 169], [513, 0]] },
+        "final": { "pc": 514, "s": 253, "a": 0, "x": 0, "y": 0, "p": 36, "ram": [[512, 169], [513, 0]] }
+    }
+]"#;
+
+#[test]
+fn test_json_parse() {
+    let value = parse(r#" { "a": [1, -2.5e1, true, null], "b": "x\"é" } "#).unwrap();
+    assert_eq!(
+        value.get("a"),
+        Some(&JsonValue::Array(vec![
+            JsonValue::Number(1.0),
+            JsonValue::Number(-25.0),
+            JsonValue::Bool(true),
+            JsonValue::Null,
+        ]))
+    );
+    assert_eq!(value.get("b").and_then(JsonValue::as_str), Some("x\"é"));
+    assert_eq!(value.get("c"), None);
+}
+
+#[test]
+fn test_json_parse_error() {
+    assert_eq!(parse("[1, 2"), Err(EmulatorError::InvalidJson { offset: 5 }));
+    assert_eq!(parse("[1] 2"), Err(EmulatorError::InvalidJson { offset: 4 }));
+}
+
+#[test]
+fn test_json_display() {
+    let value = JsonValue::object(vec![
+        ("seq", 7u64.into()),
+        ("ratio", 0.5.into()),
+        ("text", "a\"b\\\n\u{1}".into()),
+        ("list", vec![JsonValue::Null, true.into()].into()),
+    ]);
+    let text = value.to_string();
+    assert_eq!(text, r#"{"seq":7,"ratio":0.5,"text":"a\"b\\\n\u0001","list":[null,true]}"#);
+    assert_eq!(parse(&text), Ok(value));
+}
+
+#[test]
+fn test_parse_vectors() {
+    let vectors = parse_vectors(INC_ZERO_PAGE).unwrap();
+    assert_eq!(vectors.len(), 1);
+    assert_eq!(vectors[0].name, "e6 10 00");
+    assert_eq!(vectors[0].initial.ram, vec![(0x0200, 0xE6), (0x0201, 0x10), (0x0010, 0x7F)]);
+    assert_eq!(vectors[0].expected.p, 0xA4);
+    assert_eq!(
+        vectors[0].cycles.as_ref().unwrap()[3],
+        BusCycle { address: 0x0010, data: 0x7F, write: true }
+    );
+    assert_eq!(parse_vectors(WRONG_LDA).unwrap()[0].cycles, None);
+}
+
+#[test]
+fn test_parse_vectors_missing_field() {
+    let result = parse_vectors(r#"[{ "name": "x", "initial": {}, "final": {} }]"#);
+    assert!(matches!(result, Err(EmulatorError::InvalidTestVector { .. })));
+}
+
+#[test]
+fn test_run_vector_with_bus_log() {
+    let vectors = parse_vectors(INC_ZERO_PAGE).unwrap();
+    assert_eq!(run_vector(CpuVariant::Nmos6502, &vectors[0]), vec![]);
+}
+
+#[test]
+fn test_run_vector_bus_log_difference() {
+    // the 65C02 reads the operand again instead of writing the old value back
+    let vectors = parse_vectors(INC_ZERO_PAGE).unwrap();
+    let failures = run_vector(CpuVariant::Cmos65C02, &vectors[0]);
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].field, "bus");
+    assert_eq!(failures[0].expected, "3: write $0010 $7F");
+}
+
+#[test]
+fn test_report_groups_failures() {
+    let mut report = VectorReport::default();
+    report.check_opcode(CpuVariant::Nmos6502, 0xE6, &parse_vectors(INC_ZERO_PAGE).unwrap());
+    report.check_opcode(CpuVariant::Nmos6502, 0xA9, &parse_vectors(WRONG_LDA).unwrap());
+    assert_eq!(report.passed, 1);
+    assert_eq!(report.failed, 1);
+    assert_eq!(report.is_success(), false);
+    let fields = &report.failures[&0xA9];
+    assert_eq!(fields.keys().collect::<Vec<_>>(), vec!["p"]);
+    assert_eq!(fields["p"][0].expected, "$24");
+    assert_eq!(fields["p"][0].actual, "$26");
+    assert!(report.to_string().contains("A9 p: 1 failures, first \"a9 00 01\" expected $24 got $26"));
+}
+
+#[test]
+fn test_run_directory() {
+    let directory = std::env::temp_dir().join(format!("emul_single_step_{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("e6.json"), INC_ZERO_PAGE).unwrap();
+    let report = run_directory(&directory, CpuVariant::Nmos6502).unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(report.passed, 1);
+    assert_eq!(report.is_success(), true);
+    assert_eq!(report.missing.len(), 255);
+    assert_eq!(report.missing.contains(&0xE6), false);
+}
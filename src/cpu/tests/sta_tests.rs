@@ -0,0 +1,13 @@
+use super::*;
+
+#[test]
+fn test_sta_absolute_x_page_crossed_cycles() {
+    let mut cpu = get_cpu();
+
+    cpu.memory.write(0x0000, 0x9D);
+    cpu.memory.write(0x0001, 0xFF);
+    cpu.memory.write(0x0002, 0x00);
+    cpu.registers.x = 0x01;
+    let (_, cycles) = cpu.execute_instruction().unwrap();
+    assert_eq!(cycles, 5);
+}
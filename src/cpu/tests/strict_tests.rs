@@ -0,0 +1,110 @@
+use crate::cpu::cpu_6502::{Cpu6502, ExecutionState};
+use crate::cpu::instruction::INSTRUCTIONS;
+use crate::cpu::variant::{IllegalOpcodes, JamBehavior};
+use crate::error::EmulatorError;
+use crate::memory::ram::Ram;
+
+fn get_strict_cpu(program: &[u8]) -> Cpu6502 {
+    let mut cpu = Cpu6502::new(Ram::new());
+    cpu.reset();
+    cpu.illegal_opcodes = IllegalOpcodes::Reject;
+    cpu.registers.pc = 0x0200;
+    for (i, byte) in program.iter().enumerate() {
+        cpu.memory.write(0x0200 + i as u16, *byte);
+    }
+    cpu
+}
+
+#[test]
+fn test_documented_count() {
+    let documented = INSTRUCTIONS.iter().filter(|instruction| instruction.is_documented()).count();
+    assert_eq!(documented, 151);
+}
+
+#[test]
+fn test_strict_runs_documented() {
+    // LDA #$42 ; SBC #$01
+    let mut cpu = get_strict_cpu(&[0xA9, 0x42, 0xE9, 0x01]);
+
+    let (state, _) = cpu.execute_instruction().unwrap();
+    assert_eq!(state, ExecutionState::Running);
+    let (state, _) = cpu.execute_instruction().unwrap();
+    assert_eq!(state, ExecutionState::Running);
+    assert_eq!(cpu.registers.a, 0x40);
+}
+
+#[test]
+fn test_strict_rejects_undocumented() {
+    // LDA #$42 ; SLO $10
+    let mut cpu = get_strict_cpu(&[0xA9, 0x42, 0x07, 0x10]);
+
+    cpu.memory.write(0x0010, 0x81);
+    cpu.execute_instruction().unwrap();
+    let cycles = cpu.cycles;
+    assert_eq!(cpu.execute_instruction(), Err(EmulatorError::IllegalOpcode { opcode: 0x07, pc: 0x0202 }));
+    assert_eq!(cpu.cycles, cycles);
+    assert_eq!(cpu.registers.pc, 0x0202);
+    assert_eq!(cpu.registers.a, 0x42);
+    assert_eq!(cpu.memory.read(0x0010), 0x81);
+}
+
+#[test]
+fn test_strict_rejects_sbc_copy() {
+    let mut cpu = get_strict_cpu(&[0xEB, 0x01]);
+
+    assert_eq!(cpu.execute_instruction(), Err(EmulatorError::IllegalOpcode { opcode: 0xEB, pc: 0x0200 }));
+}
+
+#[test]
+fn test_strict_rejects_in_tick() {
+    let mut cpu = get_strict_cpu(&[0xEA, 0x1A]);
+
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+    let cycles = cpu.cycles;
+    assert_eq!(cpu.tick(), Err(EmulatorError::IllegalOpcode { opcode: 0x1A, pc: 0x0201 }));
+    assert_eq!(cpu.cycles, cycles);
+    assert_eq!(cpu.in_instruction(), false);
+}
+
+#[test]
+fn test_kil_halts() {
+    let mut cpu = get_strict_cpu(&[0x02, 0xEA]);
+
+    cpu.illegal_opcodes = IllegalOpcodes::Execute;
+    let (state, _) = cpu.execute_instruction().unwrap();
+    assert_eq!(state, ExecutionState::Stopped);
+    let (state, _) = cpu.execute_instruction().unwrap();
+    assert_eq!(state, ExecutionState::Stopped);
+
+    cpu.reset();
+    cpu.registers.pc = 0x0201;
+    let (state, _) = cpu.execute_instruction().unwrap();
+    assert_eq!(state, ExecutionState::Running);
+}
+
+#[test]
+fn test_kil_error() {
+    let mut cpu = get_strict_cpu(&[0xEA, 0x12]);
+
+    cpu.illegal_opcodes = IllegalOpcodes::Execute;
+    cpu.jam = JamBehavior::Error;
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.execute_instruction(), Err(EmulatorError::Jam { opcode: 0x12, pc: 0x0201 }));
+    assert_eq!(cpu.registers.pc, 0x0201);
+    assert_eq!(cpu.execute_instruction(), Err(EmulatorError::Jam { opcode: 0x12, pc: 0x0201 }));
+}
+
+#[test]
+fn test_kil_error_in_tick() {
+    let mut cpu = get_strict_cpu(&[0x12]);
+
+    cpu.illegal_opcodes = IllegalOpcodes::Execute;
+    cpu.jam = JamBehavior::Error;
+    let mut state = cpu.tick();
+    while cpu.in_instruction() {
+        state = cpu.tick();
+    }
+    assert_eq!(state, Err(EmulatorError::Jam { opcode: 0x12, pc: 0x0200 }));
+    assert_eq!(cpu.registers.pc, 0x0200);
+}
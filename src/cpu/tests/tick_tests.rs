@@ -0,0 +1,268 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::variant::CpuVariant;
+use crate::memory::bus::Bus;
+use crate::memory::ram::Ram;
+use crate::util::constants::OPCODE_KIL;
+use crate::util::types::{Byte, Address};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Cycle {
+    Read(Address, Byte),
+    Write(Address, Byte),
+}
+
+///
+/// ram recording every bus access of the cpu
+///
+struct RecordingBus {
+    ram: Ram,
+    log: Rc<RefCell<Vec<Cycle>>>,
+}
+
+impl Bus for RecordingBus {
+    fn read(&mut self, address: Address) -> Byte {
+        let data = self.ram.read(address);
+        self.log.borrow_mut().push(Cycle::Read(address, data));
+        data
+    }
+
+    fn write(&mut self, address: Address, data: Byte) {
+        self.log.borrow_mut().push(Cycle::Write(address, data));
+        self.ram.write(address, data);
+    }
+
+    fn peek(&self, address: Address) -> Byte {
+        self.ram.peek(address)
+    }
+}
+
+fn get_recording_cpu(program: &[u8]) -> (Cpu6502, Rc<RefCell<Vec<Cycle>>>) {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut ram = Ram::new();
+    ram.load(program, 0x0200).unwrap();
+    let mut cpu = Cpu6502::new(RecordingBus { ram, log: log.clone() });
+    cpu.reset();
+    cpu.registers.pc = 0x0200;
+    log.borrow_mut().clear();
+    (cpu, log)
+}
+
+///
+/// tick until the instruction is over, returns the number of cycles
+///
+fn tick_instruction(cpu: &mut Cpu6502) -> u8 {
+    let mut cycles = 1;
+    cpu.tick().unwrap();
+    while cpu.in_instruction() {
+        cpu.tick().unwrap();
+        cycles += 1;
+    }
+    cycles
+}
+
+#[test]
+fn test_tick_one_access_per_cycle() {
+    // LDA $1234,X ; INC $10 ; JSR $0300
+    let (mut cpu, log) = get_recording_cpu(&[0xBD, 0xF0, 0x12, 0xE6, 0x10, 0x20, 0x00, 0x03]);
+
+    cpu.registers.x = 0x20;
+    for _ in 0..16 {
+        let before = log.borrow().len();
+        cpu.tick().unwrap();
+        assert_eq!(log.borrow().len(), before + 1);
+    }
+    assert_eq!(cpu.cycles, 7 + 16);
+}
+
+#[test]
+fn test_tick_read_modify_write_double_write() {
+    // INC $1234
+    let (mut cpu, log) = get_recording_cpu(&[0xEE, 0x34, 0x12]);
+
+    cpu.memory.write(0x1234, 0x41);
+    log.borrow_mut().clear();
+    assert_eq!(tick_instruction(&mut cpu), 6);
+    assert_eq!(*log.borrow(), vec![
+        Cycle::Read(0x0200, 0xEE),
+        Cycle::Read(0x0201, 0x34),
+        Cycle::Read(0x0202, 0x12),
+        Cycle::Read(0x1234, 0x41),
+        Cycle::Write(0x1234, 0x41),
+        Cycle::Write(0x1234, 0x42),
+    ]);
+    assert_eq!(cpu.registers.pc, 0x0203);
+}
+
+#[test]
+fn test_tick_cmos_read_modify_write_double_read() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut ram = Ram::new();
+    // ASL $10
+    ram.load(&[0x06, 0x10], 0x0200).unwrap();
+    ram.write(0x0010, 0x81);
+    let mut cpu = Cpu6502::with_variant(RecordingBus { ram, log: log.clone() }, CpuVariant::Cmos65C02);
+    cpu.registers.pc = 0x0200;
+
+    assert_eq!(tick_instruction(&mut cpu), 5);
+    assert_eq!(*log.borrow(), vec![
+        Cycle::Read(0x0200, 0x06),
+        Cycle::Read(0x0201, 0x10),
+        Cycle::Read(0x0010, 0x81),
+        Cycle::Read(0x0010, 0x81),
+        Cycle::Write(0x0010, 0x02),
+    ]);
+}
+
+#[test]
+fn test_tick_page_crossed_dummy_read() {
+    // LDA $12F0,X
+    let (mut cpu, log) = get_recording_cpu(&[0xBD, 0xF0, 0x12]);
+
+    cpu.registers.x = 0x20;
+    cpu.memory.write(0x1310, 0x99);
+    log.borrow_mut().clear();
+    assert_eq!(tick_instruction(&mut cpu), 5);
+    assert_eq!(log.borrow()[3], Cycle::Read(0x1210, 0x00));
+    assert_eq!(log.borrow()[4], Cycle::Read(0x1310, 0x99));
+    assert_eq!(cpu.registers.a, 0x99);
+}
+
+#[test]
+fn test_tick_store_indexed_always_dummy_reads() {
+    // STA $1200,X
+    let (mut cpu, log) = get_recording_cpu(&[0x9D, 0x00, 0x12]);
+
+    cpu.registers.a = 0x55;
+    cpu.registers.x = 0x01;
+    assert_eq!(tick_instruction(&mut cpu), 5);
+    assert_eq!(log.borrow()[3], Cycle::Read(0x1201, 0x00));
+    assert_eq!(log.borrow()[4], Cycle::Write(0x1201, 0x55));
+}
+
+#[test]
+fn test_tick_rts_stack_dummy_reads() {
+    // RTS
+    let (mut cpu, log) = get_recording_cpu(&[0x60]);
+
+    cpu.registers.sp = 0xFB;
+    cpu.memory.write(0x01FC, 0x33);
+    cpu.memory.write(0x01FD, 0x12);
+    log.borrow_mut().clear();
+    assert_eq!(tick_instruction(&mut cpu), 6);
+    assert_eq!(*log.borrow(), vec![
+        Cycle::Read(0x0200, 0x60),
+        Cycle::Read(0x0201, 0x00),
+        Cycle::Read(0x01FB, 0x00),
+        Cycle::Read(0x01FC, 0x33),
+        Cycle::Read(0x01FD, 0x12),
+        Cycle::Read(0x1233, 0x00),
+    ]);
+    assert_eq!(cpu.registers.pc, 0x1234);
+}
+
+#[test]
+fn test_tick_branch_taken_page_crossed() {
+    // BNE +$10 at $02F0
+    let (mut cpu, log) = get_recording_cpu(&[]);
+
+    cpu.memory.write(0x02F0, 0xD0);
+    cpu.memory.write(0x02F1, 0x10);
+    cpu.registers.pc = 0x02F0;
+    log.borrow_mut().clear();
+    assert_eq!(tick_instruction(&mut cpu), 4);
+    assert_eq!(log.borrow()[2], Cycle::Read(0x02F2, 0x00));
+    assert_eq!(log.borrow()[3], Cycle::Read(0x0202, 0x00));
+    assert_eq!(cpu.registers.pc, 0x0302);
+}
+
+#[test]
+fn test_tick_bbr_taken_page_crossed() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut ram = Ram::new();
+    // BBR0 $10,+$10 at $02F0
+    ram.load(&[0x0F, 0x10, 0x10], 0x02F0).unwrap();
+    let mut cpu = Cpu6502::with_variant(RecordingBus { ram, log: log.clone() }, CpuVariant::Cmos65C02);
+    cpu.registers.pc = 0x02F0;
+
+    assert_eq!(tick_instruction(&mut cpu), 7);
+    // the dummy reads of the branch use the program counter, then the target before its fixup
+    assert_eq!(*log.borrow(), vec![
+        Cycle::Read(0x02F0, 0x0F),
+        Cycle::Read(0x02F1, 0x10),
+        Cycle::Read(0x02F2, 0x10),
+        Cycle::Read(0x0010, 0x00),
+        Cycle::Read(0x02F3, 0x00),
+        Cycle::Read(0x0203, 0x00),
+        Cycle::Read(0x0303, 0x00),
+    ]);
+    assert_eq!(cpu.registers.pc, 0x0303);
+}
+
+#[test]
+fn test_tick_irq_sequence() {
+    let (mut cpu, log) = get_recording_cpu(&[0xEA]);
+
+    cpu.memory.write(0xFFFE, 0x00);
+    cpu.memory.write(0xFFFF, 0x80);
+    cpu.registers.sp = 0xFD;
+    cpu.set_flag(crate::cpu::flag::Flag::Interrupt, false);
+    cpu.assert_irq();
+    log.borrow_mut().clear();
+    assert_eq!(tick_instruction(&mut cpu), 7);
+    assert_eq!(cpu.registers.pc, 0x8000);
+    assert_eq!(log.borrow()[2], Cycle::Write(0x01FD, 0x02));
+    assert_eq!(log.borrow()[3], Cycle::Write(0x01FC, 0x00));
+}
+
+///
+/// every opcode must leave the same state and use the same cycles in both execution modes
+///
+fn compare_with_execute_instruction(variant: CpuVariant) {
+    for opcode in 0..=255u8 {
+        // a jam has no cycle count to compare with
+        if variant.instructions()[opcode as usize].name == OPCODE_KIL {
+            continue;
+        }
+        let mut ram = Ram::new();
+        for address in 0..=0xFFFFu16 {
+            ram.write(address, (address as u8).wrapping_mul(7).wrapping_add((address >> 8) as u8));
+        }
+        ram.load(&[opcode, 0xF8, 0x12], 0x0200).unwrap();
+        ram.load(&[0x00, 0x03], 0xFFFE).unwrap();
+
+        let mut cpus = [Cpu6502::with_variant(ram, variant), Cpu6502::with_variant(ram, variant)];
+        for cpu in cpus.iter_mut() {
+            cpu.registers.pc = 0x0200;
+            cpu.registers.a = 0x5A;
+            cpu.registers.x = 0x13;
+            cpu.registers.y = 0xF1;
+            cpu.registers.sp = 0xF0;
+            cpu.registers.status = 0b0010_1001;
+        }
+        let (_, cycles) = cpus[0].execute_instruction().unwrap();
+        let ticks = tick_instruction(&mut cpus[1]);
+
+        let [executed, ticked] = &cpus;
+        assert_eq!(ticks, cycles, "cycles of opcode {:02X}", opcode);
+        assert_eq!(executed.registers.pc, ticked.registers.pc, "pc of opcode {:02X}", opcode);
+        assert_eq!(executed.registers.a, ticked.registers.a, "a of opcode {:02X}", opcode);
+        assert_eq!(executed.registers.x, ticked.registers.x, "x of opcode {:02X}", opcode);
+        assert_eq!(executed.registers.y, ticked.registers.y, "y of opcode {:02X}", opcode);
+        assert_eq!(executed.registers.sp, ticked.registers.sp, "sp of opcode {:02X}", opcode);
+        assert_eq!(executed.registers.status, ticked.registers.status, "status of opcode {:02X}", opcode);
+        assert_eq!(executed.memory.dump(0, 0x10000), ticked.memory.dump(0, 0x10000), "memory of opcode {:02X}", opcode);
+    }
+}
+
+#[test]
+fn test_tick_matches_execute_instruction_nmos() {
+    compare_with_execute_instruction(CpuVariant::Nmos6502);
+}
+
+#[test]
+fn test_tick_matches_execute_instruction_cmos() {
+    compare_with_execute_instruction(CpuVariant::Cmos65C02);
+}
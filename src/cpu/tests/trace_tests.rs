@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::trace::trace_line;
+use crate::memory::ram::Ram;
+
+///
+/// trace sink that can still be read while the cpu owns it
+///
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SharedBuffer {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+fn get_trace_cpu(pc: u16, program: &[u8]) -> Cpu6502 {
+    let mut cpu = Cpu6502::new(Ram::new());
+    cpu.memory.load(program, pc).unwrap();
+    cpu.registers.pc = pc;
+    cpu.registers.sp = 0xFD;
+    cpu.registers.status = 0x24;
+    cpu
+}
+
+#[test]
+fn test_trace_immediate_and_zero_page() {
+    let mut cpu = get_trace_cpu(0xC5F5, &[0xA2, 0x00]);
+    cpu.cycles = 7;
+    assert_eq!(trace_line(&cpu), "C5F5  A2 00     LDX #$00                        A:00 X:00 Y:00 P:24 SP:FD CYC:7");
+
+    let mut cpu = get_trace_cpu(0xC5FE, &[0x86, 0x00]);
+    cpu.registers.status = 0x26;
+    assert_eq!(trace_line(&cpu), "C5FE  86 00     STX $00 = 00                    A:00 X:00 Y:00 P:26 SP:FD CYC:0");
+}
+
+#[test]
+fn test_trace_indirect() {
+    let mut cpu = get_trace_cpu(0xD959, &[0xA1, 0x80]);
+    cpu.memory.write(0x0080, 0x00);
+    cpu.memory.write(0x0081, 0x02);
+    cpu.memory.write(0x0200, 0x5A);
+    assert!(trace_line(&cpu).starts_with("D959  A1 80     LDA ($80,X) @ 80 = 0200 = 5A    A:00"));
+
+    let mut cpu = get_trace_cpu(0xD9B2, &[0xB1, 0x89]);
+    cpu.registers.y = 0x34;
+    cpu.memory.write(0x0089, 0x00);
+    cpu.memory.write(0x008A, 0x03);
+    cpu.memory.write(0x0334, 0x89);
+    assert!(trace_line(&cpu).starts_with("D9B2  B1 89     LDA ($89),Y = 0300 @ 0334 = 89  A:00"));
+
+    // the NMOS page wrap of the pointer
+    let mut cpu = get_trace_cpu(0xDBB5, &[0x6C, 0xFF, 0x02]);
+    cpu.memory.write(0x02FF, 0x7E);
+    cpu.memory.write(0x0200, 0xDB);
+    assert!(trace_line(&cpu).starts_with("DBB5  6C FF 02  JMP ($02FF) = DB7E              A:00"));
+}
+
+#[test]
+fn test_trace_indexed_and_branch() {
+    let mut cpu = get_trace_cpu(0xC000, &[0xBD, 0xF0, 0x12]);
+    cpu.registers.x = 0x20;
+    assert!(trace_line(&cpu).starts_with("C000  BD F0 12  LDA $12F0,X @ 1310 = 00         A:00 X:20"));
+
+    let mut cpu = get_trace_cpu(0xC000, &[0xB6, 0xF0]);
+    cpu.registers.y = 0x20;
+    assert!(trace_line(&cpu).starts_with("C000  B6 F0     LDX $F0,Y @ 10 = 00             A:00"));
+
+    let cpu = get_trace_cpu(0xC72A, &[0xB0, 0xFE]);
+    assert!(trace_line(&cpu).starts_with("C72A  B0 FE     BCS $C72A                       A:00"));
+
+    let cpu = get_trace_cpu(0xC72D, &[0x0A]);
+    assert!(trace_line(&cpu).starts_with("C72D  0A        ASL A                           A:00"));
+}
+
+#[test]
+fn test_trace_undocumented() {
+    let cpu = get_trace_cpu(0xC6BD, &[0x04, 0xA9]);
+    assert!(trace_line(&cpu).starts_with("C6BD  04 A9    *NOP $A9 = 00                    A:00"));
+
+    let mut cpu = get_trace_cpu(0xE51E, &[0xE3, 0x45]);
+    cpu.registers.x = 0x02;
+    cpu.memory.write(0x0047, 0x47);
+    cpu.memory.write(0x0048, 0x06);
+    cpu.memory.write(0x0647, 0xEB);
+    assert!(trace_line(&cpu).starts_with("E51E  E3 45    *ISB ($45,X) @ 47 = 0647 = EB    A:00"));
+}
+
+#[test]
+fn test_trace_plp() {
+    // LDA #$FF ; PHA ; PLP ; NOP
+    let mut cpu = get_trace_cpu(0xC000, &[0xA9, 0xFF, 0x48, 0x28, 0xEA]);
+    cpu.run_instructions(3).unwrap();
+    // the break bit pushed by PHA is not pulled into the status
+    assert!(trace_line(&cpu).starts_with("C004  EA        NOP                             A:FF X:00 Y:00 P:EF SP:FD"));
+}
+
+#[test]
+fn test_trace_sink() {
+    // LDA #$01 ; STA $10 ; JMP $C000
+    let mut cpu = get_trace_cpu(0xC000, &[0xA9, 0x01, 0x85, 0x10, 0x4C, 0x00, 0xC0]);
+    let buffer = SharedBuffer::default();
+    cpu.set_trace(Box::new(buffer.clone()));
+
+    cpu.run_instructions(4).unwrap();
+    let lines: Vec<String> = buffer.text().lines().map(|line| line.to_owned()).collect();
+    assert_eq!(lines, vec![
+        "C000  A9 01     LDA #$01                        A:00 X:00 Y:00 P:24 SP:FD CYC:0",
+        "C002  85 10     STA $10 = 00                    A:01 X:00 Y:00 P:24 SP:FD CYC:2",
+        "C004  4C 00 C0  JMP $C000                       A:01 X:00 Y:00 P:24 SP:FD CYC:5",
+        "C000  A9 01     LDA #$01                        A:01 X:00 Y:00 P:24 SP:FD CYC:8",
+    ]);
+
+    // the cycle by cycle execution writes the same lines
+    let mut ticked = get_trace_cpu(0xC000, &[0xA9, 0x01, 0x85, 0x10, 0x4C, 0x00, 0xC0]);
+    let ticked_buffer = SharedBuffer::default();
+    ticked.set_trace(Box::new(ticked_buffer.clone()));
+    for _ in 0..10 {
+        ticked.tick().unwrap();
+    }
+    assert_eq!(ticked_buffer.text(), buffer.text());
+
+    assert!(cpu.take_trace().is_some());
+    cpu.run_instructions(1).unwrap();
+    assert_eq!(buffer.text().lines().count(), 4);
+}
+
+#[test]
+fn test_dumps() {
+    let mut cpu = get_trace_cpu(0xC000, b"HI");
+
+    assert_eq!(cpu.memory.hexdump(), "C000  48 49 00 00 00 00 00 00 00 00 00 00 00 00 00 00   HI..............\n");
+    cpu.registers.a = 0x12;
+    assert_eq!(cpu.dump(), "A: 12 X: 00 Y: 00 SP: FD PC: C000 Status: 24");
+}
@@ -0,0 +1,96 @@
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::decimal_mode::DecimalMode;
+use crate::cpu::flag::Flag;
+use crate::cpu::variant::CpuVariant;
+use crate::memory::ram::Ram;
+
+fn get_cpu_variant(variant: CpuVariant) -> Cpu6502 {
+    let mut cpu = Cpu6502::with_variant(Ram::new(), variant);
+    cpu.reset();
+    cpu
+}
+
+fn jmp_indirect_page_end(variant: CpuVariant) -> u16 {
+    let mut cpu = get_cpu_variant(variant);
+
+    cpu.memory.write(0x0000, 0x6C);
+    cpu.memory.write(0x0001, 0xFF);
+    cpu.memory.write(0x0002, 0x02);
+    cpu.memory.write(0x02FF, 0x34);
+    cpu.memory.write(0x0200, 0x12);
+    cpu.memory.write(0x0300, 0x56);
+    cpu.execute_instruction().unwrap();
+    cpu.registers.pc
+}
+
+#[test]
+fn test_default_variant() {
+    let cpu = Cpu6502::new(Ram::new());
+    assert_eq!(cpu.variant, CpuVariant::Nmos6502);
+    assert_eq!(cpu.decimal_mode, DecimalMode::Nmos);
+}
+
+#[test]
+fn test_decimal_mode() {
+    assert_eq!(get_cpu_variant(CpuVariant::Cmos65C02).decimal_mode, DecimalMode::Cmos);
+    assert_eq!(get_cpu_variant(CpuVariant::Ricoh2A03).decimal_mode, DecimalMode::Disabled);
+    assert_eq!(get_cpu_variant(CpuVariant::RevA).decimal_mode, DecimalMode::Nmos);
+}
+
+#[test]
+fn test_jmp_indirect_bug() {
+    assert_eq!(jmp_indirect_page_end(CpuVariant::Nmos6502), 0x1234);
+    assert_eq!(jmp_indirect_page_end(CpuVariant::Ricoh2A03), 0x1234);
+    assert_eq!(jmp_indirect_page_end(CpuVariant::Cmos65C02), 0x5634);
+}
+
+#[test]
+fn test_ricoh_2a03_ignores_decimal() {
+    let mut cpu = get_cpu_variant(CpuVariant::Ricoh2A03);
+
+    cpu.memory.write(0x0000, 0x69);
+    cpu.memory.write(0x0001, 0x01);
+    cpu.registers.a = 0x09;
+    cpu.set_flag(Flag::Decimal, true);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.a, 0x0A);
+}
+
+#[test]
+fn test_illegal_opcode_executed_on_nmos() {
+    let mut cpu = get_cpu_variant(CpuVariant::Nmos6502);
+
+    // LAX #$42
+    cpu.memory.write(0x0000, 0xAB);
+    cpu.memory.write(0x0001, 0x42);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.a, 0x42);
+    assert_eq!(cpu.registers.x, 0x42);
+}
+
+#[test]
+fn test_illegal_opcode_skipped_on_cmos() {
+    let mut cpu = get_cpu_variant(CpuVariant::Cmos65C02);
+
+    cpu.memory.write(0x0000, 0xAB);
+    cpu.memory.write(0x0001, 0x42);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.a, 0x00);
+    assert_eq!(cpu.registers.x, 0x00);
+    // $AB is a one byte NOP on the 65C02
+    assert_eq!(cpu.registers.pc, 0x0001);
+}
+
+#[test]
+fn test_rev_a_ror() {
+    let mut cpu = get_cpu_variant(CpuVariant::RevA);
+
+    // ROR A shifts left and leaves the carry alone
+    cpu.memory.write(0x0000, 0x6A);
+    cpu.registers.a = 0b1100_0001;
+    cpu.set_flag(Flag::Carry, true);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.registers.a, 0b1000_0010);
+    assert_eq!(cpu.get_flag(Flag::Carry), true);
+    assert_eq!(cpu.get_flag(Flag::Negative), true);
+}
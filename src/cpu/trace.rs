@@ -0,0 +1,192 @@
+//!
+//! File: cpu/trace.rs
+//! The trace module contains the execution tracer.
+//! One line is written per instruction before it runs, in the format of the nestest.log
+//! reference log: program counter, raw bytes, disassembly with the resolved operand,
+//! registers and cycle count. The PPU columns of the NES log are left out.
+//! Memory is only peeked, tracing has no side effect on the bus.
+//!
+//! # See
+//! * [https://www.qmtpro.com/~nes/misc/nestest.log](https://www.qmtpro.com/~nes/misc/nestest.log)
+//!
+
+use std::io::Write;
+
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::instruction::Instruction;
+use crate::error::EmulatorError;
+use crate::util::types::{Byte, Word, Address};
+
+impl Cpu6502 {
+    ///
+    /// write one trace line per instruction to `sink`, replacing the previous sink
+    ///
+    pub fn set_trace(&mut self, sink: Box<dyn Write>) {
+        self.trace = Some(sink);
+    }
+
+    ///
+    /// stop tracing and give the sink back
+    ///
+    pub fn take_trace(&mut self) -> Option<Box<dyn Write>> {
+        self.trace.take()
+    }
+
+    ///
+    /// write the line of the instruction at the program counter to the trace sink, if any
+    ///
+    pub(crate) fn trace_instruction(&mut self) -> Result<(), EmulatorError> {
+        if self.trace.is_none() {
+            return Ok(());
+        }
+        let line = trace_line(self);
+        if let Some(sink) = self.trace.as_mut() {
+            writeln!(sink, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// the trace line of the instruction at the program counter, without the line feed
+///
+/// # Example
+/// ```
+/// use emul::cpu::cpu_6502::Cpu6502;
+/// use emul::cpu::trace::trace_line;
+/// use emul::memory::ram::Ram;
+///
+/// let mut cpu = Cpu6502::new(Ram::new());
+/// cpu.memory.load(&[0x4C, 0xF5, 0xC5], 0xC000).unwrap();
+/// cpu.registers.pc = 0xC000;
+/// cpu.registers.sp = 0xFD;
+/// cpu.registers.status = 0x24;
+/// cpu.cycles = 7;
+/// assert_eq!(
+///     trace_line(&cpu),
+///     "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD CYC:7"
+/// );
+/// ```
+pub fn trace_line(cpu: &Cpu6502) -> String {
+    let pc = cpu.registers.pc;
+    let instruction = cpu.variant.instructions()[cpu.memory.peek(pc) as usize];
+    let bytes: Vec<String> = (0..instruction.length as Word)
+        .map(|i| format!("{:02X}", cpu.memory.peek(pc.wrapping_add(i))))
+        .collect();
+    // undocumented opcodes are marked with a star in front of the mnemonic
+    let marker = if instruction.is_documented() { ' ' } else { '*' };
+    let mut disassembly = mnemonic(&instruction).to_owned();
+    let operand = operand_text(cpu, &instruction, pc);
+    if !operand.is_empty() {
+        disassembly.push(' ');
+        disassembly.push_str(&operand);
+    }
+    format!(
+        "{:04X}  {:<8} {}{:<31} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
+        pc,
+        bytes.join(" "),
+        marker,
+        disassembly,
+        cpu.registers.a,
+        cpu.registers.x,
+        cpu.registers.y,
+        cpu.registers.status,
+        cpu.registers.sp,
+        cpu.cycles,
+    )
+}
+
+///
+/// mnemonic as spelled in nestest.log, which calls ISC by its other name
+///
+fn mnemonic<'a>(instruction: &Instruction<'a>) -> &'a str {
+    match instruction.name {
+        "ISC" => "ISB",
+        name => name,
+    }
+}
+
+fn peek_word(cpu: &Cpu6502, address: Address) -> Word {
+    cpu.memory.peek(address) as Word | (cpu.memory.peek(address.wrapping_add(1)) as Word) << 8
+}
+
+///
+/// read a pointer from the zero page, the high byte wraps around inside the zero page
+///
+fn peek_zero_page_word(cpu: &Cpu6502, pointer: Byte) -> Word {
+    cpu.memory.peek(pointer as Address) as Word | (cpu.memory.peek(pointer.wrapping_add(1) as Address) as Word) << 8
+}
+
+///
+/// the operand with the effective address and the value found there before the instruction runs
+///
+fn operand_text(cpu: &Cpu6502, instruction: &Instruction, pc: Address) -> String {
+    let byte = cpu.memory.peek(pc.wrapping_add(1));
+    let word = peek_word(cpu, pc.wrapping_add(1));
+    let x = cpu.registers.x;
+    let y = cpu.registers.y;
+    let value = |address: Address| cpu.memory.peek(address);
+    match instruction.addressing_mode {
+        AddressingMode::Implied => String::new(),
+        AddressingMode::Accumulator => "A".to_owned(),
+        AddressingMode::Immediate => format!("#${:02X}", byte),
+        AddressingMode::ZeroPage => format!("${:02X} = {:02X}", byte, value(byte as Address)),
+        AddressingMode::ZeroPageX => {
+            let address = byte.wrapping_add(x);
+            format!("${:02X},X @ {:02X} = {:02X}", byte, address, value(address as Address))
+        }
+        AddressingMode::ZeroPageY => {
+            let address = byte.wrapping_add(y);
+            format!("${:02X},Y @ {:02X} = {:02X}", byte, address, value(address as Address))
+        }
+        AddressingMode::Absolute => match instruction.name {
+            "JMP" | "JSR" => format!("${:04X}", word),
+            _ => format!("${:04X} = {:02X}", word, value(word)),
+        },
+        AddressingMode::AbsoluteX => {
+            let address = word.wrapping_add(x as Word);
+            format!("${:04X},X @ {:04X} = {:02X}", word, address, value(address))
+        }
+        AddressingMode::AbsoluteY => {
+            let address = word.wrapping_add(y as Word);
+            format!("${:04X},Y @ {:04X} = {:02X}", word, address, value(address))
+        }
+        AddressingMode::Indirect => {
+            // the NMOS parts do not carry into the high byte of the pointer
+            let target = if word & 0xFF == 0xFF && cpu.variant.has_jmp_indirect_bug() {
+                value(word) as Word | (value(word & !0xFF) as Word) << 8
+            } else {
+                peek_word(cpu, word)
+            };
+            format!("(${:04X}) = {:04X}", word, target)
+        }
+        AddressingMode::IndirectX => {
+            let pointer = byte.wrapping_add(x);
+            let address = peek_zero_page_word(cpu, pointer);
+            format!("(${:02X},X) @ {:02X} = {:04X} = {:02X}", byte, pointer, address, value(address))
+        }
+        AddressingMode::IndirectY => {
+            let base = peek_zero_page_word(cpu, byte);
+            let address = base.wrapping_add(y as Word);
+            format!("(${:02X}),Y = {:04X} @ {:04X} = {:02X}", byte, base, address, value(address))
+        }
+        AddressingMode::Relative => {
+            let target = pc.wrapping_add(2).wrapping_add(byte as i8 as Address);
+            format!("${:04X}", target)
+        }
+        AddressingMode::ZeroPageIndirect => {
+            let address = peek_zero_page_word(cpu, byte);
+            format!("(${:02X}) = {:04X} = {:02X}", byte, address, value(address))
+        }
+        AddressingMode::AbsoluteIndirectX => {
+            let target = peek_word(cpu, word.wrapping_add(x as Word));
+            format!("(${:04X},X) = {:04X}", word, target)
+        }
+        AddressingMode::ZeroPageRelative => {
+            let offset = cpu.memory.peek(pc.wrapping_add(2)) as i8;
+            let target = pc.wrapping_add(3).wrapping_add(offset as Address);
+            format!("${:02X},${:04X}", byte, target)
+        }
+    }
+}
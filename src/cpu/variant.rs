@@ -0,0 +1,90 @@
+//!
+//! File: cpu/variant.rs
+//! The variant module contains the enum of the supported 6502 family members
+//! and the differences between them.
+//!
+
+use crate::cpu::decimal_mode::DecimalMode;
+use crate::cpu::instruction::{Instruction, INSTRUCTIONS, CMOS_INSTRUCTIONS, REV_A_INSTRUCTIONS};
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum CpuVariant {
+    // MOS 6502, the NMOS part found in most 8 bit computers
+    #[default]
+    Nmos6502,
+    // WDC 65C02, the CMOS part of the Apple IIe enhanced
+    Cmos65C02,
+    // Ricoh 2A03, the NES cpu, an NMOS 6502 without decimal mode
+    Ricoh2A03,
+    // first MOS 6502 revision, shipped before ROR was working
+    RevA,
+}
+
+///
+/// what the cpu does when it meets an opcode outside the documented instruction set
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IllegalOpcodes {
+    // run the undocumented behavior of the NMOS decoder
+    Execute,
+    // skip the instruction like a NOP of the same length
+    Nop,
+    // stop before the instruction and report it, to catch code running into data
+    Reject,
+}
+
+///
+/// what the cpu does when it executes one of the KIL opcodes that lock up the NMOS decoder
+///
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum JamBehavior {
+    // stop the cpu until the next reset like the real chip
+    #[default]
+    Halt,
+    // report the jam and leave the program counter on the opcode
+    Error,
+}
+
+///
+/// what the cpu reports once a BRK has taken its interrupt through $FFFE
+///
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum BrkBehavior {
+    // go on with the handler like the real chip
+    #[default]
+    Interrupt,
+    // report the cpu as stopped, for the programs ending on BRK
+    Stop,
+}
+
+impl CpuVariant {
+    pub fn instructions(&self) -> &'static [Instruction<'static>; 256] {
+        match self {
+            CpuVariant::Cmos65C02 => &CMOS_INSTRUCTIONS,
+            CpuVariant::RevA => &REV_A_INSTRUCTIONS,
+            _ => &INSTRUCTIONS,
+        }
+    }
+
+    pub fn decimal_mode(&self) -> DecimalMode {
+        match self {
+            CpuVariant::Nmos6502 | CpuVariant::RevA => DecimalMode::Nmos,
+            CpuVariant::Cmos65C02 => DecimalMode::Cmos,
+            CpuVariant::Ricoh2A03 => DecimalMode::Disabled,
+        }
+    }
+
+    ///
+    /// true if JMP ($xxFF) reads the high byte of the target from $xx00 instead of the next page
+    ///
+    pub fn has_jmp_indirect_bug(&self) -> bool {
+        !matches!(self, CpuVariant::Cmos65C02)
+    }
+
+    pub fn illegal_opcodes(&self) -> IllegalOpcodes {
+        match self {
+            CpuVariant::Cmos65C02 => IllegalOpcodes::Nop,
+            _ => IllegalOpcodes::Execute,
+        }
+    }
+}
\ No newline at end of file
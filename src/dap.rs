@@ -0,0 +1,746 @@
+//!
+//! File: dap.rs
+//! The dap module contains a server of the Debug Adapter Protocol over a pair of streams,
+//! stdin and stdout when started with `--dap`, so editors can launch and debug programs.
+//! A program ending in `.s` or `.asm` is assembled with `assembler::assemble_listing`
+//! and its listing maps the program counter back to the source lines, any other file is
+//! loaded as a binary and only has the instruction addresses.
+//! The call stack is followed from the JSR run by the debugger: a frame is popped once
+//! the stack pointer is back where it was before its JSR.
+//! The requests are read by a thread so `pause` reaches the server while the cpu runs,
+//! the other requests received meanwhile are answered once it stopped.
+//!
+//! # See
+//! * [https://microsoft.github.io/debug-adapter-protocol/specification](https://microsoft.github.io/debug-adapter-protocol/specification)
+//!
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+
+use crate::assembler::assemble_listing;
+use crate::cpu::breakpoint::BreakpointKind;
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::expression::Expression;
+use crate::cpu::run::StopReason;
+use crate::error::EmulatorError;
+use crate::monitor::parse_number;
+use crate::util::constants::MEMORY_SIZE;
+use crate::util::json::{parse, JsonValue};
+use crate::util::types::{Address, Byte};
+
+// the cpu is the only thread
+const THREAD_ID: u64 = 1;
+// instructions run between two looks at the requests
+const CHUNK_INSTRUCTIONS: usize = 10_000;
+const REGISTERS_REFERENCE: u64 = 1;
+const FLAGS_REFERENCE: u64 = 2;
+const FLAGS: [(&str, Byte); 7] =
+    [("N", 0x80), ("V", 0x40), ("B", 0x10), ("D", 0x08), ("I", 0x04), ("Z", 0x02), ("C", 0x01)];
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+///
+/// how the cpu runs until the next stop
+///
+#[derive(Clone, Copy)]
+enum Resume {
+    Continue,
+    StepIn,
+    // the depth of the call stack when the step started
+    StepOver(usize),
+    StepOut(usize),
+}
+
+struct Frame {
+    // address of the JSR
+    call: Address,
+    routine: Address,
+    // stack pointer before the JSR
+    sp: Byte,
+}
+
+///
+/// the assembly source of the launched program
+///
+struct Source {
+    path: String,
+    // line number, from 1, and address of every line holding an instruction, in source order
+    lines: Vec<(usize, Address)>,
+    line_of: HashMap<Address, usize>,
+    labels: HashMap<Address, String>,
+}
+
+fn read_message<R: BufRead>(input: &mut R) -> Result<Option<JsonValue>, EmulatorError> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() && length.is_some() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; length.unwrap_or(0)];
+    input.read_exact(&mut body)?;
+    parse(&String::from_utf8_lossy(&body)).map(Some)
+}
+
+fn write_message(output: &mut dyn Write, message: &JsonValue) -> Result<(), EmulatorError> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()?;
+    Ok(())
+}
+
+fn encode_base64(data: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64[(bits >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut bits = 0u32;
+    let mut count = 0;
+    for byte in text.bytes().filter(|&byte| byte != b'=') {
+        bits = bits << 6 | BASE64.iter().position(|&digit| digit == byte)? as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            data.push((bits >> count) as u8);
+        }
+    }
+    Some(data)
+}
+
+///
+/// an address given as `0x` hex or decimal
+///
+fn parse_reference(text: &str) -> Option<i64> {
+    match text.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+fn same_file(first: &str, second: &str) -> bool {
+    match (fs::canonicalize(first), fs::canonicalize(second)) {
+        (Ok(first), Ok(second)) => first == second,
+        _ => Path::new(first) == Path::new(second),
+    }
+}
+
+fn argument<'a>(arguments: &'a JsonValue, name: &str) -> Result<&'a JsonValue, String> {
+    arguments.get(name).ok_or_else(|| format!("missing argument: {}", name))
+}
+
+fn string_argument<'a>(arguments: &'a JsonValue, name: &str) -> Result<&'a str, String> {
+    argument(arguments, name)?.as_str().ok_or_else(|| format!("invalid argument: {}", name))
+}
+
+fn number_argument(arguments: &JsonValue, name: &str) -> Result<u64, String> {
+    argument(arguments, name)?.as_u64().ok_or_else(|| format!("invalid argument: {}", name))
+}
+
+pub struct DapServer {
+    pub cpu: Cpu6502,
+    seq: u64,
+    source: Option<Source>,
+    frames: Vec<Frame>,
+    // where the program started, the bottom of the call stack
+    start: Address,
+    // Some while the cpu runs
+    resume: Option<Resume>,
+    // the steps go from source line to source line rather than from instruction to instruction
+    source_steps: bool,
+    stop_on_entry: bool,
+    lines_start_at_1: bool,
+    source_breakpoints: Vec<usize>,
+    instruction_breakpoints: Vec<usize>,
+    // requests received while the cpu runs
+    pending: VecDeque<JsonValue>,
+    // events to send after the response to the current request, with their body
+    events: Vec<(&'static str, JsonValue)>,
+    terminated: bool,
+}
+
+impl DapServer {
+    pub fn new(cpu: Cpu6502) -> DapServer {
+        let start = cpu.registers.pc;
+        DapServer {
+            cpu,
+            seq: 0,
+            source: None,
+            frames: Vec::new(),
+            start,
+            resume: None,
+            source_steps: true,
+            stop_on_entry: false,
+            lines_start_at_1: true,
+            source_breakpoints: Vec::new(),
+            instruction_breakpoints: Vec::new(),
+            pending: VecDeque::new(),
+            events: Vec::new(),
+            terminated: false,
+        }
+    }
+
+    ///
+    /// serve the requests of `input` until the client disconnects or closes it
+    ///
+    pub fn run<R: BufRead + Send + 'static, W: Write>(&mut self, input: R, mut output: W) -> Result<(), EmulatorError> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut input = input;
+            loop {
+                let message = match read_message(&mut input) {
+                    Ok(Some(message)) => message,
+                    // a message that is not JSON has no seq to answer to
+                    Err(EmulatorError::InvalidJson { .. }) => continue,
+                    Ok(None) | Err(_) => break,
+                };
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut connected = true;
+        while !self.terminated {
+            if self.resume.is_some() {
+                if connected {
+                    match receiver.try_recv() {
+                        Ok(message) => self.receive_while_running(message, &mut output)?,
+                        Err(TryRecvError::Empty) => {}
+                        // let the program run to its next stop
+                        Err(TryRecvError::Disconnected) => connected = false,
+                    }
+                }
+                self.run_chunk(&mut output)?;
+            } else if let Some(message) = self.pending.pop_front() {
+                self.handle(&message, &mut output)?;
+            } else {
+                match receiver.recv() {
+                    Ok(message) => self.handle(&message, &mut output)?,
+                    Err(_) => break,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn receive_while_running(&mut self, message: JsonValue, output: &mut dyn Write) -> Result<(), EmulatorError> {
+        match message.get("command").and_then(JsonValue::as_str) {
+            Some("pause" | "disconnect" | "terminate" | "threads") => self.handle(&message, output),
+            _ => {
+                self.pending.push_back(message);
+                Ok(())
+            }
+        }
+    }
+
+    fn send(&mut self, output: &mut dyn Write, kind: &str, mut members: Vec<(&str, JsonValue)>) -> Result<(), EmulatorError> {
+        self.seq += 1;
+        members.splice(0..0, [("seq", self.seq.into()), ("type", kind.into())]);
+        write_message(output, &JsonValue::object(members))
+    }
+
+    fn event(&mut self, event: &'static str, body: JsonValue) {
+        self.events.push((event, body));
+    }
+
+    fn flush_events(&mut self, output: &mut dyn Write) -> Result<(), EmulatorError> {
+        for (event, body) in std::mem::take(&mut self.events) {
+            self.send(output, "event", vec![("event", event.into()), ("body", body)])?;
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, request: &JsonValue, output: &mut dyn Write) -> Result<(), EmulatorError> {
+        let command = request.get("command").and_then(JsonValue::as_str).unwrap_or_default();
+        let empty = JsonValue::Object(Vec::new());
+        let arguments = request.get("arguments").unwrap_or(&empty);
+        let result = match command {
+            "initialize" => self.initialize(arguments),
+            "launch" => self.launch(arguments),
+            "configurationDone" => self.configuration_done(),
+            "setBreakpoints" => self.set_breakpoints(arguments),
+            "setInstructionBreakpoints" => self.set_instruction_breakpoints(arguments),
+            "setExceptionBreakpoints" => Ok(JsonValue::object(vec![("breakpoints", JsonValue::Array(Vec::new()))])),
+            "threads" => Ok(JsonValue::object(vec![(
+                "threads",
+                vec![JsonValue::object(vec![("id", THREAD_ID.into()), ("name", "6502".into())])].into(),
+            )])),
+            "stackTrace" => Ok(self.stack_trace()),
+            "scopes" => Ok(self.scopes()),
+            "variables" => self.variables(arguments),
+            "setVariable" => self.set_variable(arguments),
+            "evaluate" => self.evaluate(arguments),
+            "readMemory" => self.read_memory(arguments),
+            "writeMemory" => self.write_memory(arguments),
+            "continue" => {
+                self.resume = Some(Resume::Continue);
+                Ok(JsonValue::object(vec![("allThreadsContinued", true.into())]))
+            }
+            "next" | "stepIn" | "stepOut" => {
+                self.source_steps = arguments.get("granularity").and_then(JsonValue::as_str) != Some("instruction");
+                self.resume = Some(match command {
+                    "next" => Resume::StepOver(self.frames.len()),
+                    "stepIn" => Resume::StepIn,
+                    _ => Resume::StepOut(self.frames.len()),
+                });
+                Ok(JsonValue::Null)
+            }
+            "pause" => {
+                if self.resume.take().is_some() {
+                    self.stopped("pause", None, Vec::new());
+                }
+                Ok(JsonValue::Null)
+            }
+            "disconnect" | "terminate" => {
+                self.resume = None;
+                self.terminated = true;
+                if command == "terminate" {
+                    self.event("terminated", JsonValue::Object(Vec::new()));
+                }
+                Ok(JsonValue::Null)
+            }
+            _ => Err(format!("unsupported request: {}", command)),
+        };
+        let mut response = vec![
+            ("request_seq", request.get("seq").cloned().unwrap_or(JsonValue::Null)),
+            ("success", result.is_ok().into()),
+            ("command", command.into()),
+        ];
+        match result {
+            Ok(JsonValue::Null) => {}
+            Ok(body) => response.push(("body", body)),
+            Err(message) => response.push(("message", message.into())),
+        }
+        self.send(output, "response", response)?;
+        self.flush_events(output)
+    }
+
+    fn initialize(&mut self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        self.lines_start_at_1 = arguments.get("linesStartAt1").and_then(JsonValue::as_bool).unwrap_or(true);
+        Ok(JsonValue::object(vec![
+            ("supportsConfigurationDoneRequest", true.into()),
+            ("supportsConditionalBreakpoints", true.into()),
+            ("supportsHitConditionalBreakpoints", true.into()),
+            ("supportsInstructionBreakpoints", true.into()),
+            ("supportsSteppingGranularity", true.into()),
+            ("supportsSetVariable", true.into()),
+            ("supportsReadMemoryRequest", true.into()),
+            ("supportsWriteMemoryRequest", true.into()),
+            ("supportsTerminateRequest", true.into()),
+        ]))
+    }
+
+    ///
+    /// load the program at `origin`, $0000 by default, and start it at `start`, the origin by default
+    ///
+    fn launch(&mut self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        let path = string_argument(arguments, "program")?;
+        let origin = match arguments.get("origin") {
+            Some(_) => Address::try_from(number_argument(arguments, "origin")?).map_err(|error| error.to_string())?,
+            None => 0x0000,
+        };
+        let start = match arguments.get("start") {
+            Some(_) => Address::try_from(number_argument(arguments, "start")?).map_err(|error| error.to_string())?,
+            None => origin,
+        };
+        let extension = Path::new(path).extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        let machine_code = if extension.eq_ignore_ascii_case("s") || extension.eq_ignore_ascii_case("asm") {
+            let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+            let listing = assemble_listing(&text, origin).map_err(|error| error.to_string())?;
+            self.source = Some(Source {
+                path: path.to_owned(),
+                line_of: listing.lines.iter().map(|&(line, address)| (address, line)).collect(),
+                lines: listing.lines,
+                labels: listing.labels.into_iter().map(|(label, address)| (address, label)).collect(),
+            });
+            listing.machine_code
+        } else {
+            self.source = None;
+            fs::read(path).map_err(|error| format!("{}: {}", path, error))?
+        };
+        self.cpu.memory.load(&machine_code, origin).map_err(|error| error.to_string())?;
+        self.cpu.reset();
+        self.cpu.registers.pc = start;
+        self.start = start;
+        self.frames.clear();
+        self.stop_on_entry = arguments.get("stopOnEntry").and_then(JsonValue::as_bool).unwrap_or(false);
+        self.event("initialized", JsonValue::Object(Vec::new()));
+        Ok(JsonValue::Null)
+    }
+
+    fn configuration_done(&mut self) -> Result<JsonValue, String> {
+        let pc = self.cpu.registers.pc;
+        let breakpoint = self
+            .cpu
+            .breakpoints()
+            .iter()
+            .find(|breakpoint| breakpoint.enabled && breakpoint.kind == BreakpointKind::Execution(pc))
+            .map(|breakpoint| breakpoint.id);
+        if self.stop_on_entry {
+            self.stopped("entry", None, Vec::new());
+        } else if let Some(id) = breakpoint {
+            // the run loops only check the breakpoints after an instruction
+            self.stopped("breakpoint", None, vec![id]);
+        } else {
+            self.resume = Some(Resume::Continue);
+        }
+        Ok(JsonValue::Null)
+    }
+
+    fn client_line(&self, line: usize) -> usize {
+        if self.lines_start_at_1 {
+            line
+        } else {
+            line - 1
+        }
+    }
+
+    ///
+    /// add a breakpoint from a request, with its `condition` and `hitCondition`
+    ///
+    fn add_breakpoint(&mut self, kind: BreakpointKind, request: &JsonValue) -> Result<usize, String> {
+        let condition = match request.get("condition").and_then(JsonValue::as_str) {
+            Some(condition) if !condition.trim().is_empty() => {
+                Some(Expression::parse(condition).map_err(|error| error.to_string())?)
+            }
+            _ => None,
+        };
+        let ignore_count = match request.get("hitCondition").and_then(JsonValue::as_str) {
+            Some(hits) if !hits.trim().is_empty() => {
+                hits.trim().parse::<u64>().map_err(|_| format!("invalid hit count: {}", hits))?.saturating_sub(1)
+            }
+            _ => 0,
+        };
+        let id = self.cpu.add_breakpoint_with(kind, condition, false);
+        if let Some(breakpoint) = self.cpu.breakpoint_mut(id) {
+            breakpoint.ignore_count = ignore_count;
+        }
+        Ok(id)
+    }
+
+    ///
+    /// replace the breakpoints of the source, a breakpoint on a line without instruction
+    /// moves to the next instruction
+    ///
+    fn set_breakpoints(&mut self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        for id in std::mem::take(&mut self.source_breakpoints) {
+            self.cpu.remove_breakpoint(id);
+        }
+        let path = argument(arguments, "source")?.get("path").and_then(JsonValue::as_str).unwrap_or_default();
+        let requests = arguments.get("breakpoints").and_then(JsonValue::as_array).unwrap_or_default();
+        let mut breakpoints = Vec::new();
+        for request in requests {
+            let line = request.get("line").and_then(JsonValue::as_u64).unwrap_or(0) as usize;
+            let line = if self.lines_start_at_1 { line } else { line + 1 };
+            let found = self
+                .source
+                .as_ref()
+                .filter(|source| same_file(&source.path, path))
+                .and_then(|source| source.lines.iter().find(|&&(source_line, _)| source_line >= line))
+                .copied();
+            let breakpoint = match found {
+                Some((line, address)) => match self.add_breakpoint(BreakpointKind::Execution(address), request) {
+                    Ok(id) => {
+                        self.source_breakpoints.push(id);
+                        JsonValue::object(vec![
+                            ("id", id.into()),
+                            ("verified", true.into()),
+                            ("line", self.client_line(line).into()),
+                            ("instructionReference", format!("0x{:04X}", address).into()),
+                        ])
+                    }
+                    Err(message) => JsonValue::object(vec![("verified", false.into()), ("message", message.into())]),
+                },
+                None => JsonValue::object(vec![
+                    ("verified", false.into()),
+                    ("message", "no instruction at or after this line".into()),
+                ]),
+            };
+            breakpoints.push(breakpoint);
+        }
+        Ok(JsonValue::object(vec![("breakpoints", breakpoints.into())]))
+    }
+
+    fn set_instruction_breakpoints(&mut self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        for id in std::mem::take(&mut self.instruction_breakpoints) {
+            self.cpu.remove_breakpoint(id);
+        }
+        let requests = arguments.get("breakpoints").and_then(JsonValue::as_array).unwrap_or_default();
+        let mut breakpoints = Vec::new();
+        for request in requests {
+            let reference = request.get("instructionReference").and_then(JsonValue::as_str).and_then(parse_reference);
+            let offset = request.get("offset").and_then(JsonValue::as_f64).unwrap_or(0.0) as i64;
+            let address = reference.and_then(|reference| Address::try_from(reference + offset).ok());
+            let result = address
+                .ok_or_else(|| "invalid instruction reference".to_owned())
+                .and_then(|address| self.add_breakpoint(BreakpointKind::Execution(address), request));
+            let breakpoint = match result {
+                Ok(id) => {
+                    self.instruction_breakpoints.push(id);
+                    JsonValue::object(vec![("id", id.into()), ("verified", true.into())])
+                }
+                Err(message) => JsonValue::object(vec![("verified", false.into()), ("message", message.into())]),
+            };
+            breakpoints.push(breakpoint);
+        }
+        Ok(JsonValue::object(vec![("breakpoints", breakpoints.into())]))
+    }
+
+    fn routine_name(&self, address: Address) -> String {
+        match self.source.as_ref().and_then(|source| source.labels.get(&address)) {
+            Some(label) => label.clone(),
+            None => format!("${:04X}", address),
+        }
+    }
+
+    fn stack_frame(&self, id: usize, routine: Address, pc: Address) -> JsonValue {
+        let mut frame = vec![
+            ("id", id.into()),
+            ("name", self.routine_name(routine).into()),
+            ("instructionPointerReference", format!("0x{:04X}", pc).into()),
+        ];
+        let source = self.source.as_ref().and_then(|source| Some((source, *source.line_of.get(&pc)?)));
+        match source {
+            Some((source, line)) => {
+                let name = Path::new(&source.path).file_name().map(|name| name.to_string_lossy().into_owned());
+                frame.push((
+                    "source",
+                    JsonValue::object(vec![("name", name.unwrap_or_default().into()), ("path", source.path.as_str().into())]),
+                ));
+                frame.push(("line", self.client_line(line).into()));
+                frame.push(("column", 1u64.into()));
+            }
+            None => {
+                frame.push(("line", 0u64.into()));
+                frame.push(("column", 0u64.into()));
+            }
+        }
+        JsonValue::object(frame)
+    }
+
+    ///
+    /// the innermost frame first, every frame is at the instruction it runs or calls from
+    ///
+    fn stack_trace(&self) -> JsonValue {
+        let mut frames = Vec::new();
+        let mut pc = self.cpu.registers.pc;
+        for frame in self.frames.iter().rev() {
+            frames.push(self.stack_frame(frames.len(), frame.routine, pc));
+            pc = frame.call;
+        }
+        frames.push(self.stack_frame(frames.len(), self.start, pc));
+        JsonValue::object(vec![("totalFrames", frames.len().into()), ("stackFrames", frames.into())])
+    }
+
+    fn scopes(&self) -> JsonValue {
+        let scope = |name: &str, reference: u64| {
+            JsonValue::object(vec![
+                ("name", name.into()),
+                ("variablesReference", reference.into()),
+                ("expensive", false.into()),
+            ])
+        };
+        JsonValue::object(vec![(
+            "scopes",
+            vec![scope("Registers", REGISTERS_REFERENCE), scope("Flags", FLAGS_REFERENCE)].into(),
+        )])
+    }
+
+    fn registers(&self) -> [(&'static str, String); 6] {
+        let registers = &self.cpu.registers;
+        [
+            ("A", format!("${:02X}", registers.a)),
+            ("X", format!("${:02X}", registers.x)),
+            ("Y", format!("${:02X}", registers.y)),
+            ("SP", format!("${:02X}", registers.sp)),
+            ("PC", format!("${:04X}", registers.pc)),
+            ("P", format!("${:02X}", registers.status)),
+        ]
+    }
+
+    fn variables(&self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        let variable = |name: &str, value: String| {
+            JsonValue::object(vec![("name", name.into()), ("value", value.into()), ("variablesReference", 0u64.into())])
+        };
+        let variables: Vec<JsonValue> = match number_argument(arguments, "variablesReference")? {
+            REGISTERS_REFERENCE => self.registers().into_iter().map(|(name, value)| variable(name, value)).collect(),
+            FLAGS_REFERENCE => FLAGS
+                .iter()
+                .map(|&(name, mask)| variable(name, ((self.cpu.registers.status & mask != 0) as u8).to_string()))
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(JsonValue::object(vec![("variables", variables.into())]))
+    }
+
+    fn set_variable(&mut self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        let name = string_argument(arguments, "name")?;
+        let value = parse_number(string_argument(arguments, "value")?).map_err(|error| error.to_string())?;
+        let byte = Byte::try_from(value).map_err(|_| format!("value out of range: {}", value));
+        let registers = &mut self.cpu.registers;
+        match number_argument(arguments, "variablesReference")? {
+            REGISTERS_REFERENCE => match name {
+                "A" => registers.a = byte?,
+                "X" => registers.x = byte?,
+                "Y" => registers.y = byte?,
+                "SP" => registers.sp = byte?,
+                "PC" => registers.pc = value,
+                "P" => registers.status = byte?,
+                _ => return Err(format!("unknown register: {}", name)),
+            },
+            FLAGS_REFERENCE => {
+                let mask = FLAGS.iter().find(|&&(flag, _)| flag == name).map(|&(_, mask)| mask);
+                match (mask, value) {
+                    (Some(mask), 0) => registers.status &= !mask,
+                    (Some(mask), 1) => registers.status |= mask,
+                    (Some(_), _) => return Err(format!("a flag is 0 or 1: {}", value)),
+                    (None, _) => return Err(format!("unknown flag: {}", name)),
+                }
+                return Ok(JsonValue::object(vec![("value", value.to_string().into())]));
+            }
+            _ => return Err("unknown variables reference".to_owned()),
+        }
+        let value = self.registers().into_iter().find(|&(register, _)| register == name).map(|(_, value)| value);
+        Ok(JsonValue::object(vec![("value", value.unwrap_or_default().into()), ("variablesReference", 0u64.into())]))
+    }
+
+    fn evaluate(&self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        let expression = Expression::parse(string_argument(arguments, "expression")?).map_err(|error| error.to_string())?;
+        let value = expression.evaluate(&self.cpu);
+        let result = if value >= 0 { format!("{} (${:X})", value, value) } else { value.to_string() };
+        Ok(JsonValue::object(vec![("result", result.into()), ("variablesReference", 0u64.into())]))
+    }
+
+    ///
+    /// the address of `memoryReference` and `offset`, None outside of the address space
+    ///
+    fn memory_address(arguments: &JsonValue) -> Result<Option<Address>, String> {
+        let reference = parse_reference(string_argument(arguments, "memoryReference")?)
+            .ok_or_else(|| "invalid memory reference".to_owned())?;
+        let offset = arguments.get("offset").and_then(JsonValue::as_f64).unwrap_or(0.0) as i64;
+        Ok(Address::try_from(reference + offset).ok())
+    }
+
+    fn read_memory(&self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        let count = number_argument(arguments, "count")? as usize;
+        let (address, data) = match DapServer::memory_address(arguments)? {
+            Some(address) => {
+                let length = count.min(MEMORY_SIZE - address as usize);
+                (address, self.cpu.memory.dump(address, length))
+            }
+            None => (0, Vec::new()),
+        };
+        Ok(JsonValue::object(vec![
+            ("address", format!("0x{:04X}", address).into()),
+            ("data", encode_base64(&data).into()),
+            ("unreadableBytes", (count - data.len()).into()),
+        ]))
+    }
+
+    fn write_memory(&mut self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        let address = DapServer::memory_address(arguments)?.ok_or_else(|| "address out of range".to_owned())?;
+        let data = decode_base64(string_argument(arguments, "data")?).ok_or_else(|| "invalid base64 data".to_owned())?;
+        self.cpu.memory.load(&data, address).map_err(|error| error.to_string())?;
+        Ok(JsonValue::object(vec![("bytesWritten", data.len().into())]))
+    }
+
+    fn stopped(&mut self, reason: &'static str, text: Option<String>, breakpoints: Vec<usize>) {
+        let mut body = vec![
+            ("reason", reason.into()),
+            ("threadId", THREAD_ID.into()),
+            ("allThreadsStopped", true.into()),
+        ];
+        if let Some(text) = text {
+            body.push(("text", text.into()));
+        }
+        if !breakpoints.is_empty() {
+            body.push(("hitBreakpointIds", breakpoints.into_iter().map(JsonValue::from).collect::<Vec<_>>().into()));
+        }
+        self.event("stopped", JsonValue::object(body));
+    }
+
+    fn in_source(&self) -> bool {
+        !self.source_steps
+            || self.source.as_ref().is_none_or(|source| source.line_of.contains_key(&self.cpu.registers.pc))
+    }
+
+    ///
+    /// run one instruction and follow the call stack
+    ///
+    fn step(&mut self) -> Result<StopReason, EmulatorError> {
+        let pc = self.cpu.registers.pc;
+        let sp = self.cpu.registers.sp;
+        let is_jsr = self.cpu.variant.instructions()[self.cpu.memory.peek(pc) as usize].name == "JSR";
+        let result = self.cpu.run_instructions(1);
+        if is_jsr && result.is_ok() && self.cpu.registers.sp == sp.wrapping_sub(2) {
+            self.frames.push(Frame { call: pc, routine: self.cpu.registers.pc, sp });
+        }
+        while self.frames.last().is_some_and(|frame| frame.sp <= self.cpu.registers.sp) {
+            self.frames.pop();
+        }
+        result
+    }
+
+    ///
+    /// run a bounded number of instructions, sends the stopped event if the run ends
+    ///
+    fn run_chunk(&mut self, output: &mut dyn Write) -> Result<(), EmulatorError> {
+        for _ in 0..CHUNK_INSTRUCTIONS {
+            let resume = match self.resume {
+                Some(resume) => resume,
+                None => return Ok(()),
+            };
+            let (reason, text, breakpoints) = match self.step() {
+                Err(error) => ("exception", Some(error.to_string()), Vec::new()),
+                Ok(StopReason::Breakpoint(id)) => ("breakpoint", None, vec![id]),
+                Ok(StopReason::Watchpoint { id, .. }) => ("data breakpoint", None, vec![id]),
+                Ok(StopReason::Waiting) => ("pause", Some("waiting for an interrupt".to_owned()), Vec::new()),
+                Ok(StopReason::Stopped) => {
+                    self.resume = None;
+                    self.event("exited", JsonValue::object(vec![("exitCode", 0u64.into())]));
+                    self.event("terminated", JsonValue::Object(Vec::new()));
+                    return self.flush_events(output);
+                }
+                Ok(_) => {
+                    let done = self.in_source()
+                        && match resume {
+                            Resume::Continue => false,
+                            Resume::StepIn => true,
+                            Resume::StepOver(depth) => self.frames.len() <= depth,
+                            Resume::StepOut(depth) => self.frames.len() < depth,
+                        };
+                    if !done {
+                        continue;
+                    }
+                    ("step", None, Vec::new())
+                }
+            };
+            self.resume = None;
+            self.stopped(reason, text, breakpoints);
+            return self.flush_events(output);
+        }
+        Ok(())
+    }
+}
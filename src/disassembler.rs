@@ -0,0 +1,253 @@
+//!
+//! File: disassembler.rs
+//! The disassembler module contains the disassembly of machine code through the
+//! instruction tables, one structured line per instruction with the operand written
+//! the usual way for its addressing mode: `#$12`, `$1234,X`, `($12),Y`...
+//! The branches show their target address rather than their offset.
+//! An instruction cut by the end of the bytes becomes `.byte` lines.
+//! `disassemble_source` writes source for the assembler instead, which builds back
+//! the same bytes: the code is found by following the jumps from the entry points,
+//! the targets inside the code get `L_xxxx` labels and the other bytes become `.byte` blocks.
+//!
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::cpu::addressing_mode::AddressingMode;
+use crate::cpu::instruction::{find_instruction_by_name_and_mode, Instruction, INSTRUCTIONS};
+use crate::util::constants::MEMORY_SIZE;
+use crate::util::types::{Address, Word};
+
+// bytes on a line of a `.byte` block
+const BYTES_PER_LINE: usize = 8;
+// addresses of the NMI, reset and IRQ vectors
+const VECTORS: [Address; 3] = [0xFFFA, 0xFFFC, 0xFFFE];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct DisassembledLine {
+    pub address: Address,
+    pub bytes: Vec<u8>,
+    // `.byte` for a byte that is not a whole instruction
+    pub mnemonic: &'static str,
+    pub addressing_mode: AddressingMode,
+    pub operand: String,
+    // where a branch, a JMP or a JSR goes, None for the other instructions and JMP indirect
+    pub target: Option<Address>,
+}
+
+impl fmt::Display for DisassembledLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes: Vec<String> = self.bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+        let line = format!("{:04X}  {:<8}  {} {}", self.address, bytes.join(" "), self.mnemonic, self.operand);
+        write!(f, "{}", line.trim_end())
+    }
+}
+
+///
+/// disassemble the bytes loaded at `origin` with the instructions of the NMOS 6502
+///
+/// # Example
+/// ```
+/// use emul::disassembler::disassemble;
+///
+/// // LDA ($12),Y ; BNE -4
+/// let lines = disassemble(&[0xB1, 0x12, 0xD0, 0xFC], 0x0200);
+/// assert_eq!(lines[0].operand, "($12),Y");
+/// assert_eq!(lines[1].target, Some(0x0200));
+/// assert_eq!(lines[1].to_string(), "0202  D0 FC     BNE $0200");
+/// ```
+pub fn disassemble(bytes: &[u8], origin: Address) -> Vec<DisassembledLine> {
+    disassemble_with(&INSTRUCTIONS, bytes, origin)
+}
+
+///
+/// disassemble the bytes loaded at `origin` with the instructions of a variant
+///
+pub fn disassemble_with(instructions: &[Instruction<'static>; 256], bytes: &[u8], origin: Address) -> Vec<DisassembledLine> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let line = disassemble_instruction(instructions, &bytes[offset..], origin.wrapping_add(offset as Address));
+        offset += line.bytes.len();
+        lines.push(line);
+    }
+    lines
+}
+
+///
+/// disassemble the instruction at the start of `bytes`, loaded at `address`,
+/// a `.byte` line for its first byte if the bytes end before the instruction
+///
+pub fn disassemble_instruction(
+    instructions: &[Instruction<'static>; 256],
+    bytes: &[u8],
+    address: Address,
+) -> DisassembledLine {
+    let instruction = instructions[bytes[0] as usize];
+    let length = instruction.length as usize;
+    if bytes.len() < length {
+        return DisassembledLine {
+            address,
+            bytes: vec![bytes[0]],
+            mnemonic: ".byte",
+            addressing_mode: AddressingMode::Immediate,
+            operand: format!("${:02X}", bytes[0]),
+            target: None,
+        };
+    }
+    let byte = bytes.get(1).copied().unwrap_or(0);
+    let word = byte as Word | (bytes.get(2).copied().unwrap_or(0) as Word) << 8;
+    let branch = address.wrapping_add(2).wrapping_add(byte as i8 as Address);
+    let (operand, target) = match instruction.addressing_mode {
+        AddressingMode::Implied => (String::new(), None),
+        AddressingMode::Accumulator => ("A".to_owned(), None),
+        AddressingMode::Immediate => (format!("#${:02X}", byte), None),
+        AddressingMode::ZeroPage => (format!("${:02X}", byte), None),
+        AddressingMode::ZeroPageX => (format!("${:02X},X", byte), None),
+        AddressingMode::ZeroPageY => (format!("${:02X},Y", byte), None),
+        AddressingMode::Absolute => {
+            let jump = matches!(instruction.name, "JMP" | "JSR");
+            (format!("${:04X}", word), jump.then_some(word))
+        }
+        AddressingMode::AbsoluteX => (format!("${:04X},X", word), None),
+        AddressingMode::AbsoluteY => (format!("${:04X},Y", word), None),
+        AddressingMode::Indirect => (format!("(${:04X})", word), None),
+        AddressingMode::IndirectX => (format!("(${:02X},X)", byte), None),
+        AddressingMode::IndirectY => (format!("(${:02X}),Y", byte), None),
+        AddressingMode::Relative => (format!("${:04X}", branch), Some(branch)),
+        AddressingMode::ZeroPageIndirect => (format!("(${:02X})", byte), None),
+        AddressingMode::AbsoluteIndirectX => (format!("(${:04X},X)", word), None),
+        AddressingMode::ZeroPageRelative => {
+            let offset = bytes.get(2).copied().unwrap_or(0) as i8;
+            let target = address.wrapping_add(3).wrapping_add(offset as Address);
+            (format!("${:02X},${:04X}", byte, target), Some(target))
+        }
+    };
+    DisassembledLine {
+        address,
+        bytes: bytes[..length].to_vec(),
+        mnemonic: instruction.name,
+        addressing_mode: instruction.addressing_mode,
+        operand,
+        target,
+    }
+}
+
+///
+/// true if the assembler builds the instruction back to the same opcode,
+/// the undocumented ones are left to the `.byte` blocks
+///
+fn reassemblable(line: &DisassembledLine) -> bool {
+    let opcode = line.bytes[0];
+    INSTRUCTIONS[opcode as usize].is_documented()
+        && line.mnemonic != ".byte"
+        && find_instruction_by_name_and_mode(line.mnemonic, &line.addressing_mode.to_string())
+            .is_some_and(|instruction| instruction.opcode == opcode)
+}
+
+///
+/// the instructions reached from the entry points, indexed by their offset in the bytes
+///
+fn trace_code(bytes: &[u8], origin: Address, entries: &[Address]) -> Vec<Option<DisassembledLine>> {
+    let mut code: Vec<Option<DisassembledLine>> = vec![None; bytes.len()];
+    let mut covered = vec![false; bytes.len()];
+    let mut pending = entries.to_vec();
+    while let Some(address) = pending.pop() {
+        let offset = address.wrapping_sub(origin) as usize;
+        if offset >= bytes.len() || covered[offset] {
+            continue;
+        }
+        let line = disassemble_instruction(&INSTRUCTIONS, &bytes[offset..], address);
+        let end = offset + line.bytes.len();
+        if !reassemblable(&line) || covered[offset..end].iter().any(|&covered| covered) {
+            continue;
+        }
+        covered[offset..end].fill(true);
+        let next = address.wrapping_add(line.bytes.len() as Address);
+        match (line.mnemonic, line.addressing_mode) {
+            ("JMP", AddressingMode::Absolute) => pending.extend(line.target),
+            // the end of the flow, BRK included since its return skips a byte
+            ("JMP", _) | ("RTS", _) | ("RTI", _) | ("BRK", _) => {}
+            _ => {
+                pending.extend(line.target);
+                pending.push(next);
+            }
+        }
+        code[offset] = Some(line);
+    }
+    code
+}
+
+///
+/// the operand of a line in the syntax of the assembler, with the label of its target
+///
+fn source_operand(line: &DisassembledLine, labels: &BTreeSet<Address>) -> String {
+    let label = line.target.filter(|target| labels.contains(target)).map(|target| format!("L_{:04X}", target));
+    match line.addressing_mode {
+        AddressingMode::Implied | AddressingMode::Accumulator => String::new(),
+        // an unlabelled branch keeps its offset
+        AddressingMode::Relative => label.unwrap_or_else(|| format!("${:02X}", line.bytes[1])),
+        _ if line.bytes.len() == 3 => {
+            label.unwrap_or_else(|| format!("${:04X}", line.bytes[1] as Word | (line.bytes[2] as Word) << 8))
+        }
+        _ => format!("${:02X}", line.bytes[1]),
+    }
+}
+
+///
+/// disassemble the bytes loaded at `origin` into source that `assembler::assemble_listing`
+/// builds back to the same bytes at the same origin,
+/// the code starts at the entry points and at the NMI, reset and IRQ vectors found in the bytes
+///
+/// # Example
+/// ```
+/// use emul::assembler::assemble_listing;
+/// use emul::disassembler::disassemble_source;
+///
+/// // LDX #$00 ; INX ; BNE $0202 ; RTS ; data
+/// let bytes = [0xA2, 0x00, 0xE8, 0xD0, 0xFD, 0x60, 0x12, 0x34];
+/// let source = disassemble_source(&bytes, 0x0200, &[0x0200]);
+/// assert_eq!(source.lines().nth(2), Some("L_0202:"));
+/// assert_eq!(source.lines().last(), Some(".byte $12,$34"));
+/// assert_eq!(assemble_listing(&source, 0x0200).unwrap().machine_code, bytes);
+/// ```
+pub fn disassemble_source(bytes: &[u8], origin: Address, entries: &[Address]) -> String {
+    let bytes = &bytes[..bytes.len().min(MEMORY_SIZE - origin as usize)];
+    let contains = |address: Address| (address.wrapping_sub(origin) as usize) < bytes.len();
+    let mut entries = entries.to_vec();
+    for vector in VECTORS {
+        if contains(vector) && contains(vector.wrapping_add(1)) {
+            let offset = vector.wrapping_sub(origin) as usize;
+            entries.push(bytes[offset] as Address | (bytes[offset + 1] as Address) << 8);
+        }
+    }
+    let code = trace_code(bytes, origin, &entries);
+    let is_code = |address: Address| contains(address) && code[address.wrapping_sub(origin) as usize].is_some();
+    let labels: BTreeSet<Address> = entries
+        .iter()
+        .copied()
+        .chain(code.iter().flatten().filter_map(|line| line.target))
+        .filter(|&address| is_code(address))
+        .collect();
+    let mut source = String::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        if let Some(line) = &code[offset] {
+            if labels.contains(&line.address) {
+                source.push_str(&format!("L_{:04X}:\n", line.address));
+            }
+            let text = format!("{} {} {}", line.mnemonic, line.addressing_mode, source_operand(line, &labels));
+            source.push_str(text.trim_end());
+            source.push('\n');
+            offset += line.bytes.len();
+        } else {
+            let end = (offset..bytes.len()).find(|&end| code[end].is_some()).unwrap_or(bytes.len());
+            for chunk in bytes[offset..end].chunks(BYTES_PER_LINE) {
+                let values: Vec<String> = chunk.iter().map(|byte| format!("${:02X}", byte)).collect();
+                source.push_str(&format!(".byte {}\n", values.join(",")));
+            }
+            offset = end;
+        }
+    }
+    source
+}
@@ -0,0 +1,75 @@
+//!
+//! File: error.rs
+//! The error module contains the error type shared by the cpu, the memory and the assembler.
+//! Nothing in the library prints or panics, every failure is returned to the caller.
+//!
+
+use std::fmt;
+use std::io;
+
+use crate::util::types::{Byte, Address};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum EmulatorError {
+    // undocumented opcode met while the cpu rejects them, nothing was executed
+    IllegalOpcode { opcode: Byte, pc: Address },
+    // KIL executed while jams are reported as errors
+    Jam { opcode: Byte, pc: Address },
+    // the instruction at the program counter runs past the end of the address space
+    PcOverflow { pc: Address },
+    // the data does not fit in the 64K address space at this offset
+    LoadTooLarge { offset: Address, len: usize },
+    // the file to load could not be read
+    Io(io::ErrorKind),
+    // a component could not serve an access at this address
+    BusFault { address: Address },
+    // the assembler could not understand a line, lines are counted from 1
+    Assembly { line: usize, message: String },
+    // the save state was written by a version of the format this build can not read
+    SaveStateVersion { version: u16 },
+    // the save state is truncated, corrupted or does not match the machine
+    InvalidSaveState { message: String },
+    // a line of a reference trace is not a trace line, lines are counted from 1
+    InvalidTrace { line: usize },
+    // the JSON document is malformed at this byte offset
+    InvalidJson { offset: usize },
+    // a test vector is missing a field or has a field of the wrong type
+    InvalidTestVector { message: String },
+    // a breakpoint condition could not be parsed, the offset is the one of the first character not understood
+    InvalidExpression { offset: usize, message: String },
+    // a monitor command is unknown or has wrong arguments
+    InvalidCommand { message: String },
+}
+
+impl fmt::Display for EmulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmulatorError::IllegalOpcode { opcode, pc } => write!(f, "illegal opcode {:02X} at {:04X}", opcode, pc),
+            EmulatorError::Jam { opcode, pc } => write!(f, "cpu jammed by opcode {:02X} at {:04X}", opcode, pc),
+            EmulatorError::PcOverflow { pc } => write!(f, "instruction at {:04X} runs past the end of memory", pc),
+            EmulatorError::LoadTooLarge { offset, len } => {
+                write!(f, "{} bytes do not fit in memory at {:04X}", len, offset)
+            }
+            EmulatorError::Io(kind) => write!(f, "i/o error: {}", kind),
+            EmulatorError::BusFault { address } => write!(f, "bus fault at {:04X}", address),
+            EmulatorError::Assembly { line, message } => write!(f, "line {}: {}", line, message),
+            EmulatorError::SaveStateVersion { version } => write!(f, "unsupported save state version {}", version),
+            EmulatorError::InvalidSaveState { message } => write!(f, "invalid save state: {}", message),
+            EmulatorError::InvalidTrace { line } => write!(f, "line {} is not a trace line", line),
+            EmulatorError::InvalidJson { offset } => write!(f, "invalid JSON at offset {}", offset),
+            EmulatorError::InvalidTestVector { message } => write!(f, "invalid test vector: {}", message),
+            EmulatorError::InvalidExpression { offset, message } => {
+                write!(f, "invalid expression at offset {}: {}", offset, message)
+            }
+            EmulatorError::InvalidCommand { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for EmulatorError {}
+
+impl From<io::Error> for EmulatorError {
+    fn from(error: io::Error) -> Self {
+        EmulatorError::Io(error.kind())
+    }
+}
@@ -0,0 +1,466 @@
+//!
+//! File: gdb.rs
+//! The gdb module contains a stub of the GDB remote serial protocol over TCP,
+//! so GDB and the front-ends built on it can attach to the emulator.
+//! The registers are A, X, Y, SP, PC and P, numbered from 0 in this order,
+//! PC is 16 bits wide and sent little endian, the target description sent to the debugger says so.
+//! Software breakpoints (`Z0`) and watchpoints (`Z2` to `Z4`) are the breakpoints of the cpu,
+//! `continue` runs until one of them fires, the cpu stops or the debugger sends an interrupt.
+//!
+//! # See
+//! * [https://sourceware.org/gdb/current/onlinedocs/gdb.html/Remote-Protocol.html](https://sourceware.org/gdb/current/onlinedocs/gdb.html/Remote-Protocol.html)
+//!
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use crate::cpu::breakpoint::{Access, BreakpointKind};
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::run::StopReason;
+use crate::error::EmulatorError;
+use crate::util::constants::MEMORY_SIZE;
+use crate::util::types::{Address, Byte};
+
+// cycles run between two checks of the interrupt sent by the debugger
+const CHUNK_CYCLES: u64 = 10_000;
+// largest packet the debugger may send, it bounds the memory transfers
+const PACKET_SIZE: usize = 0x4000;
+
+// signals of the stop replies, in the numbering of GDB
+const SIGINT: Byte = 2;
+const SIGILL: Byte = 4;
+const SIGTRAP: Byte = 5;
+const SIGBUS: Byte = 10;
+const SIGSEGV: Byte = 11;
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.gnu.gdb.m6502.core">
+    <reg name="a" bitsize="8" regnum="0" type="uint8"/>
+    <reg name="x" bitsize="8" type="uint8"/>
+    <reg name="y" bitsize="8" type="uint8"/>
+    <reg name="sp" bitsize="8" type="uint8"/>
+    <reg name="pc" bitsize="16" type="code_ptr"/>
+    <reg name="p" bitsize="8" type="uint8"/>
+  </feature>
+</target>
+"#;
+
+///
+/// what the stub does after a packet
+///
+enum Action {
+    Reply(String),
+    // reply if there is something to say, then close the connection
+    Close(Option<String>),
+}
+
+///
+/// packet layer of a connection, with the bytes read ahead while looking for an interrupt
+///
+struct Connection {
+    stream: TcpStream,
+    pending: VecDeque<u8>,
+    // acknowledge every packet until the debugger asks for the no-ack mode
+    ack: bool,
+}
+
+impl Connection {
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        if self.pending.is_empty() {
+            let mut buffer = [0; 1024];
+            let count = self.stream.read(&mut buffer)?;
+            self.pending.extend(&buffer[..count]);
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    ///
+    /// the content of the next packet with a valid checksum, None at the end of the stream
+    ///
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(b'$') => {}
+                // acknowledgements and interrupts outside of a run
+                Some(_) => continue,
+            }
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(b'#') => break,
+                    Some(byte) => data.push(byte),
+                }
+            }
+            let mut checksum = [0; 2];
+            for digit in checksum.iter_mut() {
+                *digit = match self.read_byte()? {
+                    Some(byte) => byte,
+                    None => return Ok(None),
+                };
+            }
+            let expected = std::str::from_utf8(&checksum).ok().and_then(|text| u8::from_str_radix(text, 16).ok());
+            if expected != Some(checksum_of(&data)) {
+                if self.ack {
+                    self.stream.write_all(b"-")?;
+                }
+                continue;
+            }
+            if self.ack {
+                self.stream.write_all(b"+")?;
+            }
+            return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+        }
+    }
+
+    fn send(&mut self, data: &str) -> io::Result<()> {
+        let packet = format!("${}#{:02x}", data, checksum_of(data.as_bytes()));
+        loop {
+            self.stream.write_all(packet.as_bytes())?;
+            if !self.ack {
+                return Ok(());
+            }
+            // resend until acknowledged, anything else than a nack counts as an acknowledgement
+            if self.read_byte()? != Some(b'-') {
+                return Ok(());
+            }
+        }
+    }
+
+    ///
+    /// true if the debugger sent an interrupt, checked without blocking
+    ///
+    fn interrupted(&mut self) -> io::Result<bool> {
+        self.stream.set_nonblocking(true)?;
+        let mut buffer = [0; 1024];
+        let result = match self.stream.read(&mut buffer) {
+            Ok(count) => {
+                self.pending.extend(&buffer[..count]);
+                Ok(())
+            }
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            Err(error) => Err(error),
+        };
+        self.stream.set_nonblocking(false)?;
+        result?;
+        match self.pending.iter().position(|&byte| byte == 0x03) {
+            Some(index) => {
+                self.pending.remove(index);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum: u8, &byte| sum.wrapping_add(byte))
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse_hex_bytes(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| text.get(i..i + 2).and_then(|digits| u8::from_str_radix(digits, 16).ok()))
+        .collect()
+}
+
+fn parse_hex(text: &str) -> Option<usize> {
+    usize::from_str_radix(text, 16).ok()
+}
+
+///
+/// the address and the length of `addr,length`, the address must be in the address space
+///
+fn parse_range(text: &str) -> Option<(Address, usize)> {
+    let (address, length) = text.split_once(',')?;
+    let address = Address::try_from(parse_hex(address)?).ok()?;
+    Some((address, parse_hex(length)?))
+}
+
+fn error_reply() -> Action {
+    Action::Reply("E01".to_owned())
+}
+
+pub struct GdbStub {
+    pub cpu: Cpu6502,
+    // breakpoints of the cpu set by the debugger, by type of `Z` packet and address
+    breakpoints: HashMap<(u8, Address), usize>,
+}
+
+impl GdbStub {
+    pub fn new(cpu: Cpu6502) -> GdbStub {
+        GdbStub {
+            cpu,
+            breakpoints: HashMap::new(),
+        }
+    }
+
+    ///
+    /// wait for a debugger on the listener and serve it until it detaches
+    ///
+    pub fn listen(&mut self, listener: &TcpListener) -> Result<(), EmulatorError> {
+        let (stream, _) = listener.accept()?;
+        self.serve(stream)
+    }
+
+    ///
+    /// answer the packets of a connected debugger until it detaches, kills the target
+    /// or closes the connection
+    ///
+    pub fn serve(&mut self, stream: TcpStream) -> Result<(), EmulatorError> {
+        stream.set_nodelay(true)?;
+        let mut connection = Connection { stream, pending: VecDeque::new(), ack: true };
+        while let Some(packet) = connection.read_packet()? {
+            match self.handle(&packet, &mut connection)? {
+                Action::Reply(reply) => connection.send(&reply)?,
+                Action::Close(reply) => {
+                    if let Some(reply) = reply {
+                        connection.send(&reply)?;
+                    }
+                    break;
+                }
+            }
+            if packet == "QStartNoAckMode" {
+                connection.ack = false;
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, packet: &str, connection: &mut Connection) -> Result<Action, EmulatorError> {
+        let (command, arguments) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        let reply = match command {
+            "?" => format!("S{:02x}", SIGTRAP),
+            "g" => self.read_registers(),
+            "G" => return Ok(self.write_registers(arguments)),
+            "p" => return Ok(self.read_register(arguments)),
+            "P" => return Ok(self.write_register(arguments)),
+            "m" => return Ok(self.read_memory(arguments)),
+            "M" => return Ok(self.write_memory(arguments)),
+            "Z" | "z" => return Ok(self.breakpoint(command == "Z", arguments)),
+            "c" | "C" | "s" | "S" => {
+                // the signal of C and S is dropped, an address moves the program counter first
+                let address = match command {
+                    "c" | "s" => arguments,
+                    _ => arguments.split_once(';').map_or("", |(_, address)| address),
+                };
+                if let Some(address) = parse_hex(address).and_then(|address| Address::try_from(address).ok()) {
+                    self.cpu.registers.pc = address;
+                }
+                self.resume(command == "s" || command == "S", connection)?
+            }
+            "v" => return self.handle_v(arguments, connection),
+            "q" => return Ok(self.query(arguments)),
+            "Q" if arguments == "StartNoAckMode" => "OK".to_owned(),
+            "H" | "T" => "OK".to_owned(),
+            "D" => return Ok(Action::Close(Some("OK".to_owned()))),
+            "k" => return Ok(Action::Close(None)),
+            // an empty reply tells the debugger the packet is not supported
+            _ => String::new(),
+        };
+        Ok(Action::Reply(reply))
+    }
+
+    fn handle_v(&mut self, arguments: &str, connection: &mut Connection) -> Result<Action, EmulatorError> {
+        if arguments == "Cont?" {
+            return Ok(Action::Reply("vCont;c;C;s;S".to_owned()));
+        }
+        match arguments.strip_prefix("Cont;") {
+            // there is a single thread, only the first action matters
+            Some(actions) => {
+                let step = matches!(actions.chars().next(), Some('s' | 'S'));
+                Ok(Action::Reply(self.resume(step, connection)?))
+            }
+            None => Ok(Action::Reply(String::new())),
+        }
+    }
+
+    fn query(&self, arguments: &str) -> Action {
+        let reply = if arguments.starts_with("Supported") {
+            format!("PacketSize={:x};qXfer:features:read+;swbreak+;QStartNoAckMode+;vContSupported+", PACKET_SIZE)
+        } else if let Some(range) = arguments.strip_prefix("Xfer:features:read:target.xml:") {
+            let (offset, length) = match range.split_once(',').and_then(|(offset, length)| Some((parse_hex(offset)?, parse_hex(length)?))) {
+                Some(range) => range,
+                None => return error_reply(),
+            };
+            let start = offset.min(TARGET_XML.len());
+            let end = offset.saturating_add(length).min(TARGET_XML.len());
+            let marker = if end == TARGET_XML.len() { 'l' } else { 'm' };
+            format!("{}{}", marker, &TARGET_XML[start..end])
+        } else {
+            match arguments {
+                "Attached" => "1".to_owned(),
+                "C" => "QC1".to_owned(),
+                "fThreadInfo" => "m1".to_owned(),
+                "sThreadInfo" => "l".to_owned(),
+                _ => String::new(),
+            }
+        };
+        Action::Reply(reply)
+    }
+
+    fn register_bytes(&self) -> [Byte; 7] {
+        let registers = &self.cpu.registers;
+        let [pc_low, pc_high] = registers.pc.to_le_bytes();
+        [registers.a, registers.x, registers.y, registers.sp, pc_low, pc_high, registers.status]
+    }
+
+    fn read_registers(&self) -> String {
+        hex(&self.register_bytes())
+    }
+
+    fn write_registers(&mut self, arguments: &str) -> Action {
+        let bytes = match parse_hex_bytes(arguments) {
+            Some(bytes) if bytes.len() == 7 => bytes,
+            _ => return error_reply(),
+        };
+        let registers = &mut self.cpu.registers;
+        registers.a = bytes[0];
+        registers.x = bytes[1];
+        registers.y = bytes[2];
+        registers.sp = bytes[3];
+        registers.pc = Address::from_le_bytes([bytes[4], bytes[5]]);
+        registers.status = bytes[6];
+        Action::Reply("OK".to_owned())
+    }
+
+    fn read_register(&self, arguments: &str) -> Action {
+        let bytes = self.register_bytes();
+        let reply = match parse_hex(arguments) {
+            Some(number @ 0..=3) => hex(&bytes[number..=number]),
+            Some(4) => hex(&bytes[4..6]),
+            Some(5) => hex(&bytes[6..]),
+            _ => return error_reply(),
+        };
+        Action::Reply(reply)
+    }
+
+    fn write_register(&mut self, arguments: &str) -> Action {
+        let (number, value) = match arguments.split_once('=') {
+            Some((number, value)) => (parse_hex(number), parse_hex_bytes(value)),
+            None => return error_reply(),
+        };
+        let registers = &mut self.cpu.registers;
+        match (number, value.as_deref()) {
+            (Some(0), Some(&[value])) => registers.a = value,
+            (Some(1), Some(&[value])) => registers.x = value,
+            (Some(2), Some(&[value])) => registers.y = value,
+            (Some(3), Some(&[value])) => registers.sp = value,
+            (Some(4), Some(&[low, high])) => registers.pc = Address::from_le_bytes([low, high]),
+            (Some(5), Some(&[value])) => registers.status = value,
+            _ => return error_reply(),
+        }
+        Action::Reply("OK".to_owned())
+    }
+
+    ///
+    /// read memory without side effect, the transfer stops at the end of the address space
+    ///
+    fn read_memory(&self, arguments: &str) -> Action {
+        let (address, length) = match parse_range(arguments) {
+            Some(range) => range,
+            None => return error_reply(),
+        };
+        let length = length.min(PACKET_SIZE / 2).min(MEMORY_SIZE - address as usize);
+        Action::Reply(hex(&self.cpu.memory.dump(address, length)))
+    }
+
+    fn write_memory(&mut self, arguments: &str) -> Action {
+        let (range, data) = match arguments.split_once(':') {
+            Some(split) => split,
+            None => return error_reply(),
+        };
+        let (address, data) = match (parse_range(range), parse_hex_bytes(data)) {
+            (Some((address, length)), Some(data)) if data.len() == length => (address, data),
+            _ => return error_reply(),
+        };
+        match self.cpu.memory.load(&data, address) {
+            Ok(()) => Action::Reply("OK".to_owned()),
+            Err(_) => error_reply(),
+        }
+    }
+
+    ///
+    /// insert or remove a breakpoint, `type,address,kind`
+    ///
+    fn breakpoint(&mut self, insert: bool, arguments: &str) -> Action {
+        let mut fields = arguments.split(',');
+        let kind = fields.next().and_then(|kind| kind.parse::<u8>().ok());
+        let address = fields.next().and_then(parse_hex).and_then(|address| Address::try_from(address).ok());
+        // the kind of a watchpoint is its length
+        let length = fields.next().and_then(parse_hex).unwrap_or(1).max(1);
+        let (kind, address) = match (kind, address) {
+            (Some(kind), Some(address)) => (kind, address),
+            _ => return error_reply(),
+        };
+        let end = address.saturating_add((length - 1).min(Address::MAX as usize) as Address);
+        let breakpoint = match kind {
+            0 => BreakpointKind::Execution(address),
+            2 => BreakpointKind::Watch { start: address, end, access: Access::Write },
+            3 => BreakpointKind::Watch { start: address, end, access: Access::Read },
+            4 => BreakpointKind::Watch { start: address, end, access: Access::ReadWrite },
+            // hardware breakpoints are not supported
+            _ => return Action::Reply(String::new()),
+        };
+        if insert {
+            if !self.breakpoints.contains_key(&(kind, address)) {
+                let id = self.cpu.add_breakpoint(breakpoint);
+                self.breakpoints.insert((kind, address), id);
+            }
+        } else if let Some(id) = self.breakpoints.remove(&(kind, address)) {
+            self.cpu.remove_breakpoint(id);
+        }
+        Action::Reply("OK".to_owned())
+    }
+
+    ///
+    /// run one instruction or until something stops the cpu, returns the stop reply
+    ///
+    fn resume(&mut self, step: bool, connection: &mut Connection) -> Result<String, EmulatorError> {
+        let result = if step {
+            self.cpu.run_instructions(1)
+        } else {
+            loop {
+                match self.cpu.run_cycles(CHUNK_CYCLES) {
+                    Ok(StopReason::CyclesElapsed) => {
+                        if connection.interrupted()? {
+                            return Ok(format!("S{:02x}", SIGINT));
+                        }
+                    }
+                    result => break result,
+                }
+            }
+        };
+        let reason = match result {
+            Ok(reason) => reason,
+            Err(EmulatorError::IllegalOpcode { .. } | EmulatorError::Jam { .. }) => return Ok(format!("S{:02x}", SIGILL)),
+            Err(EmulatorError::BusFault { .. }) => return Ok(format!("S{:02x}", SIGBUS)),
+            Err(EmulatorError::PcOverflow { .. }) => return Ok(format!("S{:02x}", SIGSEGV)),
+            Err(error) => return Err(error),
+        };
+        let reply = match reason {
+            StopReason::Breakpoint(_) if !step => format!("T{:02x}swbreak:;", SIGTRAP),
+            StopReason::Watchpoint { id, address, .. } => {
+                let kind = match self.cpu.breakpoint(id).map(|breakpoint| breakpoint.kind) {
+                    Some(BreakpointKind::Watch { access: Access::Read, .. }) => "rwatch",
+                    Some(BreakpointKind::Watch { access: Access::ReadWrite, .. }) => "awatch",
+                    _ => "watch",
+                };
+                format!("T{:02x}{}:{:04x};", SIGTRAP, kind, address)
+            }
+            _ => format!("S{:02x}", SIGTRAP),
+        };
+        Ok(reply)
+    }
+}
@@ -1,4 +1,9 @@
 pub mod cpu;
+pub mod error;
 pub mod memory;
 pub mod util;
-pub mod assembler;
\ No newline at end of file
+pub mod assembler;
+pub mod disassembler;
+pub mod monitor;
+pub mod gdb;
+pub mod dap;
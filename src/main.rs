@@ -1,17 +1,37 @@
 #![allow(dead_code)]
 
-///
-/// File: main.rs
-/// The main module contains the implementation of the main function.
-///
-
-use std::fs::File;
-use std::io::Read;
+//!
+//! File: main.rs
+//! The main module contains the implementation of the main function.
+//! Started with `--monitor [file [address]]` it loads the file, at $0000 by default,
+//! and hands the cpu to the interactive monitor instead of running the demo loop.
+//! Started with `--gdb port [file [address]]` it loads the file the same way and waits
+//! for a debugger speaking the GDB remote protocol on this port of the loopback interface.
+//! Started with `--dap` it serves the Debug Adapter Protocol on stdin and stdout for an editor,
+//! which launches the program itself.
+//! Started with `--disassemble file [address [entry ...]]` it prints the file, loaded at $0000
+//! by default, as source for the assembler, the code starting at the entries, the load address by default.
+//! Started with `--compare file address log [context]` it loads the file at the address, starts the cpu
+//! from the state of the first line of the reference log and prints the first divergence from the log
+//! with the `context` lines before it, 5 by default.
+//!
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::net::TcpListener;
 use std::{thread, time};
 
 use emul::util::types::Address;
 use emul::cpu::cpu_6502::{Cpu6502, ExecutionState};
+use emul::cpu::golden::{TraceComparison, TraceRecord};
+use emul::cpu::variant::BrkBehavior;
+use emul::error::EmulatorError;
 use emul::memory::ram::Ram;
+use emul::dap::DapServer;
+use emul::disassembler::disassemble_source;
+use emul::gdb::GdbStub;
+use emul::monitor::{parse_number, Monitor};
 
 #[derive(Clone, Copy)]
 pub struct InputOutput {
@@ -19,19 +39,15 @@ pub struct InputOutput {
     display: [bool; 64 * 32],
 }
 
-#[derive(Clone, Copy)]
 pub struct Emulator {
-    memory: Ram,
     pub cpu: Cpu6502,
     io: InputOutput,
 }
 
 impl Emulator {
     pub fn new() -> Emulator {
-        let ram = Ram::new();
         Emulator {
-            memory: ram,
-            cpu: Cpu6502::new(ram),
+            cpu: Cpu6502::new(Ram::new()),
             io: InputOutput {
                 keyboard: [false; 16],
                 display: [false; 64 * 32],
@@ -40,65 +56,188 @@ impl Emulator {
     }
 
     pub fn reset(&mut self) {
-        self.memory.reset();
+        self.cpu.memory.reset();
         self.cpu.reset();
     }
 
-    pub fn flash_ram(&mut self) {
-        self.cpu.memory = self.memory;
-    }
-
-    pub fn load(&mut self, data: &[u8], offset: Address) {
-        self.memory.load(data, offset);
+    pub fn load(&mut self, data: &[u8], offset: Address) -> Result<(), EmulatorError> {
+        self.cpu.memory.load(data, offset)
     }
 
     pub fn dump(&self, offset: Address, len: usize) -> Vec<u8> {
-        self.memory.dump(offset, len)
+        self.cpu.memory.dump(offset, len)
     }
 
-    pub fn hexdump(&self) {
-        self.memory.hexdump();
+    pub fn hexdump(&self) -> String {
+        self.cpu.memory.hexdump()
     }
 
-    pub fn dump_cpu(&self) {
-        self.cpu.dump();
+    pub fn dump_cpu(&self) -> String {
+        self.cpu.dump()
     }
 
-    pub fn load_binary(&mut self, path: &str, offset: Address) {
-        let mut file = File::open(path).unwrap();
+    pub fn load_binary(&mut self, path: &str, offset: Address) -> Result<(), EmulatorError> {
+        let mut file = File::open(path)?;
         let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer).unwrap();
-        self.load(&buffer, offset);
+        file.read_to_end(&mut buffer)?;
+        self.load(&buffer, offset)
     }
 }
 
-fn main() {
-    let mut emulator = Emulator::new();
+impl Default for Emulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+///
+/// the emulator after a reset, with the file and the optional load address of `arguments`
+///
+fn load(arguments: &[String]) -> Result<Emulator, EmulatorError> {
+    let mut emulator = Emulator::new();
     emulator.reset();
-    emulator.load_binary("bin/loop.bin", 0x0000);
+    if let Some(path) = arguments.first() {
+        let offset = match arguments.get(1) {
+            Some(offset) => parse_number(offset)?,
+            None => 0x0000,
+        };
+        emulator.load_binary(path, offset)?;
+        emulator.cpu.reset();
+    }
+    Ok(emulator)
+}
+
+///
+/// run the monitor on stdin and stdout with the file given after `--monitor`
+///
+fn monitor(arguments: &[String]) -> Result<(), EmulatorError> {
+    let emulator = load(arguments)?;
+    Monitor::new(emulator.cpu).run(io::stdin().lock(), io::stdout())
+}
+
+///
+/// serve one debugger on the port given after `--gdb`, with the file given after the port
+///
+fn gdb(arguments: &[String]) -> Result<(), EmulatorError> {
+    let port = match arguments.first().and_then(|port| port.parse::<u16>().ok()) {
+        Some(port) => port,
+        None => {
+            return Err(EmulatorError::InvalidCommand { message: "usage: --gdb port [file [address]]".to_owned() })
+        }
+    };
+    let emulator = load(&arguments[1..])?;
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    println!("waiting for gdb on {}", listener.local_addr()?);
+    GdbStub::new(emulator.cpu).listen(&listener)
+}
+
+///
+/// serve an editor on stdin and stdout, the program comes with its launch request
+///
+fn dap() -> Result<(), EmulatorError> {
+    let emulator = load(&[])?;
+    DapServer::new(emulator.cpu).run(BufReader::new(io::stdin()), io::stdout())
+}
 
-    emulator.hexdump();
+///
+/// print the file given after `--disassemble` as source that assembles back to it
+///
+fn disassemble(arguments: &[String]) -> Result<(), EmulatorError> {
+    let path = match arguments.first() {
+        Some(path) => path,
+        None => {
+            return Err(EmulatorError::InvalidCommand {
+                message: "usage: --disassemble file [address [entry ...]]".to_owned(),
+            })
+        }
+    };
+    let origin = match arguments.get(1) {
+        Some(origin) => parse_number(origin)?,
+        None => 0x0000,
+    };
+    let mut entries = arguments.iter().skip(2).map(|entry| parse_number(entry)).collect::<Result<Vec<Address>, _>>()?;
+    if entries.is_empty() {
+        entries.push(origin);
+    }
+    print!("{}", disassemble_source(&fs::read(path)?, origin, &entries));
+    Ok(())
+}
+
+///
+/// run the file given after `--compare` against the reference log and print the first divergence
+///
+fn compare(arguments: &[String]) -> Result<(), EmulatorError> {
+    if arguments.len() < 3 {
+        return Err(EmulatorError::InvalidCommand {
+            message: "usage: --compare file address log [context]".to_owned(),
+        });
+    }
+    let context = match arguments.get(3) {
+        Some(context) => parse_number(context)? as usize,
+        None => 5,
+    };
+    let mut emulator = load(&arguments[..2])?;
+    let mut reference = BufReader::new(File::open(&arguments[2])?);
+    // the run starts from the state of the first line, the reset vector is not used
+    let first = reference.fill_buf()?.split(|&byte| byte == b'\n').next().unwrap_or_default();
+    let first = TraceRecord::parse(&String::from_utf8_lossy(first)).ok_or(EmulatorError::InvalidTrace { line: 1 })?;
+    let cpu = &mut emulator.cpu;
+    cpu.registers.pc = first.pc;
+    cpu.registers.a = first.a.unwrap_or(cpu.registers.a);
+    cpu.registers.x = first.x.unwrap_or(cpu.registers.x);
+    cpu.registers.y = first.y.unwrap_or(cpu.registers.y);
+    cpu.registers.status = first.p.unwrap_or(cpu.registers.status);
+    cpu.registers.sp = first.sp.unwrap_or(cpu.registers.sp);
+    cpu.cycles = first.cycles.unwrap_or(cpu.cycles);
+    match cpu.compare_with_trace(reference, context)? {
+        TraceComparison::Matched(count) => println!("{} instructions match the reference", count),
+        TraceComparison::Diverged(divergence) => println!("{}", divergence),
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), EmulatorError> {
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    if arguments.first().map(String::as_str) == Some("--monitor") {
+        return monitor(&arguments[1..]);
+    }
+    if arguments.first().map(String::as_str) == Some("--gdb") {
+        return gdb(&arguments[1..]);
+    }
+    if arguments.first().map(String::as_str) == Some("--dap") {
+        return dap();
+    }
+    if arguments.first().map(String::as_str) == Some("--disassemble") {
+        return disassemble(&arguments[1..]);
+    }
+    if arguments.first().map(String::as_str) == Some("--compare") {
+        return compare(&arguments[1..]);
+    }
+
+    let mut emulator = Emulator::new();
 
-    emulator.flash_ram();
     emulator.reset();
+    emulator.load_binary("bin/loop.bin", 0x0000)?;
+
+    print!("{}", emulator.hexdump());
+
+    emulator.cpu.reset();
+    // the program ends on the BRK of the empty memory following it
+    emulator.cpu.brk = BrkBehavior::Stop;
 
     loop {
-        let kill = emulator.cpu.execute_instruction();
-        emulator.dump_cpu();
-        match kill {
-            None => {}
-            Some(x) => {
-                match x {
-                    ExecutionState::Running => {
-                        thread::sleep(time::Duration::from_millis(100));
-                    }
-                    ExecutionState::Error | ExecutionState::Stopped => {
-                        emulator.cpu.memory.hexdump();
-                        break
-                    }
-                }
+        let (state, _cycles) = emulator.cpu.execute_instruction()?;
+        println!("{}", emulator.dump_cpu());
+        match state {
+            ExecutionState::Running | ExecutionState::Waiting => {
+                thread::sleep(time::Duration::from_millis(100));
+            }
+            ExecutionState::Stopped => {
+                print!("{}", emulator.hexdump());
+                break
             }
         }
     }
+
+    Ok(())
 }
@@ -0,0 +1,188 @@
+//!
+//! File: memory/address_decoder.rs
+//! The address decoder module contains the implementation of the address decoder.
+//! The address decoder is a bus made of several components, each one mapped on a range
+//! of addresses, it forwards every access to the component owning the address.
+//! The address given to a component is relative to the start of its region.
+//! Accesses to unmapped addresses can be reported as bus faults.
+//!
+
+use crate::error::EmulatorError;
+use crate::memory::bus::Bus;
+use crate::util::types::{Byte, Address};
+use crate::util::state::{invalid_state, StateReader, StateWriter};
+
+struct Region {
+    start: Address,
+    end: Address,
+    device: Box<dyn Bus>,
+}
+
+#[derive(Default)]
+pub struct AddressDecoder {
+    regions: Vec<Region>,
+    // report the accesses to unmapped addresses as bus faults
+    fault_unmapped: bool,
+    // last access that could not be served, until it is taken
+    fault: Option<Address>,
+}
+
+impl AddressDecoder {
+    pub fn new() -> AddressDecoder {
+        AddressDecoder {
+            regions: Vec::new(),
+            fault_unmapped: false,
+            fault: None,
+        }
+    }
+
+    ///
+    /// report the reads and writes of unmapped addresses as bus faults
+    /// instead of reading zero and ignoring the writes
+    ///
+    pub fn set_fault_unmapped(&mut self, enabled: bool) {
+        self.fault_unmapped = enabled;
+    }
+
+    ///
+    /// map a component on the range `start..=end`,
+    /// regions are searched in the order they were mapped so the first one wins on overlap
+    ///
+    /// # Example
+    /// ```
+    /// use emul::cpu::cpu_6502::Cpu6502;
+    /// use emul::memory::address_decoder::AddressDecoder;
+    /// use emul::memory::ram::Ram;
+    /// use emul::memory::rom::Rom;
+    ///
+    /// let mut decoder = AddressDecoder::new();
+    /// decoder.map(0x0000, 0x7FFF, Ram::new());
+    /// decoder.map(0x8000, 0xFFFF, Rom::new(&[0xEA; 0x8000]));
+    ///
+    /// let mut cpu = Cpu6502::new(decoder);
+    /// cpu.memory.write(0x0010, 0x42);
+    /// cpu.memory.write(0x8000, 0x42);
+    /// assert_eq!(cpu.memory.read(0x0010), 0x42);
+    /// assert_eq!(cpu.memory.read(0x8000), 0xEA);
+    /// ```
+    pub fn map<B: Bus + 'static>(&mut self, start: Address, end: Address, device: B) {
+        self.regions.push(Region {
+            start,
+            end,
+            device: Box::new(device),
+        });
+    }
+
+    fn load_regions(&mut self, reader: &mut StateReader) -> Result<(), EmulatorError> {
+        if reader.read_word()? as usize != self.regions.len() {
+            return Err(invalid_state("the memory map does not match"));
+        }
+        for region in self.regions.iter_mut() {
+            if reader.read_word()? != region.start || reader.read_word()? != region.end {
+                return Err(invalid_state("the memory map does not match"));
+            }
+            region.device.load_state(reader)?;
+        }
+        Ok(())
+    }
+
+    fn region_index(&self, address: Address) -> Option<usize> {
+        self.regions
+            .iter()
+            .position(|region| region.start <= address && address <= region.end)
+    }
+}
+
+impl Bus for AddressDecoder {
+    ///
+    /// read a byte, unmapped addresses read as zero
+    ///
+    fn read(&mut self, address: Address) -> Byte {
+        match self.region_index(address) {
+            Some(index) => {
+                let region = &mut self.regions[index];
+                region.device.read(address - region.start)
+            }
+            None => {
+                if self.fault_unmapped {
+                    self.fault = Some(address);
+                }
+                0
+            }
+        }
+    }
+
+    ///
+    /// write a byte, writes to unmapped addresses are ignored
+    ///
+    fn write(&mut self, address: Address, data: Byte) {
+        match self.region_index(address) {
+            Some(index) => {
+                let region = &mut self.regions[index];
+                region.device.write(address - region.start, data);
+            }
+            None => {
+                if self.fault_unmapped {
+                    self.fault = Some(address);
+                }
+            }
+        }
+    }
+
+    fn peek(&self, address: Address) -> Byte {
+        match self.region_index(address) {
+            Some(index) => {
+                let region = &self.regions[index];
+                region.device.peek(address - region.start)
+            }
+            None => 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.fault = None;
+        for region in self.regions.iter_mut() {
+            region.device.reset();
+        }
+    }
+
+    ///
+    /// save the state of every component, preceded by the map so a state
+    /// is only restored into a decoder with the same regions
+    ///
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_word(self.regions.len() as u16);
+        for region in self.regions.iter() {
+            writer.write_word(region.start);
+            writer.write_word(region.end);
+            region.device.save_state(writer);
+        }
+    }
+
+    ///
+    /// restore the state of every component, the components already restored
+    /// are put back in their previous state when the map does not match further on
+    ///
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), EmulatorError> {
+        let mut backup = StateWriter::new();
+        self.save_state(&mut backup);
+        let loaded = self.load_regions(reader);
+        if loaded.is_err() {
+            self.load_regions(&mut StateReader::new(&backup.into_bytes()))?;
+        }
+        loaded
+    }
+
+    ///
+    /// take the fault of the decoder or of a component, the address of a component fault
+    /// is moved back into the address space of the cpu
+    ///
+    fn take_fault(&mut self) -> Option<Address> {
+        if let Some(address) = self.fault.take() {
+            return Some(address);
+        }
+        self.regions.iter_mut().find_map(|region| {
+            region.device.take_fault().map(|address| region.start.wrapping_add(address))
+        })
+    }
+}
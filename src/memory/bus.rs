@@ -0,0 +1,96 @@
+//!
+//! File: memory/bus.rs
+//! The bus module contains the definition of the bus trait.
+//! Every component the cpu can address (RAM, ROM, memory-mapped devices)
+//! implements this trait, so the cpu never needs to know what sits behind an address.
+//!
+
+use crate::error::EmulatorError;
+use crate::util::types::{Byte, Address};
+use crate::util::constants::MEMORY_SIZE;
+use crate::util::state::{StateReader, StateWriter};
+
+pub trait Bus {
+    ///
+    /// read a byte at the given address, devices may have side effects on read
+    ///
+    fn read(&mut self, address: Address) -> Byte;
+
+    ///
+    /// write a byte at the given address
+    ///
+    fn write(&mut self, address: Address, data: Byte);
+
+    ///
+    /// read a byte at the given address without any side effect,
+    /// used by the host to inspect the memory
+    ///
+    fn peek(&self, address: Address) -> Byte;
+
+    ///
+    /// reset the component, does nothing by default
+    ///
+    fn reset(&mut self) {}
+
+    ///
+    /// take the address of the last access the component could not serve,
+    /// the fault is cleared once taken, components that never fail return None
+    ///
+    fn take_fault(&mut self) -> Option<Address> {
+        None
+    }
+
+    ///
+    /// append the internal state of the component to a save state,
+    /// components without state write nothing
+    ///
+    fn save_state(&self, _writer: &mut StateWriter) {}
+
+    ///
+    /// restore the state written by `save_state`
+    ///
+    fn load_state(&mut self, _reader: &mut StateReader) -> Result<(), EmulatorError> {
+        Ok(())
+    }
+
+    ///
+    /// write the data starting at `offset`, it must fit below the end of the address space
+    ///
+    fn load(&mut self, data: &[u8], offset: Address) -> Result<(), EmulatorError> {
+        if offset as usize + data.len() > MEMORY_SIZE {
+            return Err(EmulatorError::LoadTooLarge { offset, len: data.len() });
+        }
+        for (i, &byte) in data.iter().enumerate() {
+            self.write(offset + i as Address, byte);
+        }
+        Ok(())
+    }
+
+    fn dump(&self, offset: Address, len: usize) -> Vec<u8> {
+        (0..len).map(|i| self.peek(offset.wrapping_add(i as Address))).collect()
+    }
+
+    ///
+    /// the memory as lines of 16 bytes with their address and their ascii characters,
+    /// the lines holding only zeros are left out
+    ///
+    fn hexdump(&self) -> String {
+        let mut dump = String::new();
+        for address in (0..MEMORY_SIZE).step_by(16) {
+            let line = self.dump(address as Address, 16);
+            if line.iter().all(|&byte| byte == 0) {
+                continue;
+            }
+            dump.push_str(&format!("{:04X}  ", address));
+            for byte in line.iter() {
+                dump.push_str(&format!("{:02X} ", byte));
+            }
+            dump.push_str("  ");
+            for &byte in line.iter() {
+                dump.push(if (32..=126).contains(&byte) { byte as char } else { '.' });
+            }
+            dump.push('\n');
+        }
+        dump
+    }
+}
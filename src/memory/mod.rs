@@ -1 +1,4 @@
+pub mod address_decoder;
+pub mod bus;
 pub mod ram;
+pub mod rom;
\ No newline at end of file
@@ -0,0 +1,46 @@
+//!
+//! File: memory/rom.rs
+//! The ROM module contains the implementation of a read only memory chip.
+//! Writes are ignored, the content is only set when the chip is created.
+//! The address received is relative to the start of the region the chip is mapped at,
+//! the content is mirrored when the region is larger than the chip.
+//!
+
+use crate::memory::bus::Bus;
+use crate::util::types::{Byte, Address};
+
+#[derive(Clone)]
+pub struct Rom {
+    memory: Vec<Byte>,
+}
+
+impl Rom {
+    pub fn new(data: &[u8]) -> Rom {
+        Rom {
+            memory: data.to_vec(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.memory.is_empty()
+    }
+}
+
+impl Bus for Rom {
+    fn read(&mut self, address: Address) -> Byte {
+        self.peek(address)
+    }
+
+    fn write(&mut self, _address: Address, _data: Byte) {}
+
+    fn peek(&self, address: Address) -> Byte {
+        if self.memory.is_empty() {
+            return 0;
+        }
+        self.memory[address as usize % self.memory.len()]
+    }
+}
@@ -0,0 +1,480 @@
+//!
+//! File: monitor.rs
+//! The monitor module contains an interactive machine language monitor in the spirit
+//! of the VICE and WozMon monitors, driving a `Cpu6502` directly.
+//! It reads one command per line and writes its answers to any `io::Write`,
+//! so it runs on the terminal as well as on a script.
+//! Numbers are hexadecimal, with or without `$`, `%` prefixes binary and `+` decimal numbers.
+//! Breakpoint conditions use the syntax of the expression module, where bare numbers are decimal
+//! and hexadecimal numbers need the `$` prefix, `%` for binary, and breakpoints are numbered in decimal.
+//!
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::cpu::breakpoint::{Access, BreakpointKind};
+use crate::cpu::cpu_6502::Cpu6502;
+use crate::cpu::expression::Expression;
+use crate::cpu::flag::Flag;
+use crate::cpu::run::StopReason;
+use crate::cpu::trace::trace_line;
+use crate::disassembler::{disassemble_instruction, DisassembledLine};
+use crate::error::EmulatorError;
+use crate::util::types::{Address, Byte, Word};
+
+const HELP: &str = "\
+r [reg=value ...]        show or set the registers A X Y SP P PC
+z [count]                step instructions
+n                        step over a subroutine call
+ret                      run until the current subroutine returns
+g [address [count]]      continue, from address if given, until a breakpoint, a stop or count instructions
+m [start [end]]          examine memory
+> address byte ...       deposit bytes
+d [start [end]]          disassemble
+l file address           load a binary file
+s file start end         save memory to a binary file
+break [address [if condition]]      set a breakpoint or list them
+tbreak address [if condition]       set a temporary breakpoint
+watch r|w|rw start [end] [if condition]  set a watchpoint
+delete id | enable id | disable id | ignore id count
+reset                    reset the cpu
+x                        leave the monitor
+";
+
+// bytes shown by `m` and instructions shown by `d` without an end address
+const EXAMINE_LENGTH: Address = 0x80;
+const DISASSEMBLE_COUNT: usize = 16;
+// instructions run by `g` between two checks of the break request
+const GO_CHUNK: usize = 10_000;
+
+pub struct Monitor {
+    pub cpu: Cpu6502,
+    // where `m` and `d` go on when they are given no address
+    next_examine: Address,
+    next_disassemble: Address,
+    // set from another thread to stop a running `g`
+    break_request: Arc<AtomicBool>,
+}
+
+///
+/// parse a number of the monitor: hexadecimal by default or with `$`, binary with `%`, decimal with `+`
+///
+/// # Example
+/// ```
+/// use emul::monitor::parse_number;
+///
+/// assert_eq!(parse_number("$C000").unwrap(), 0xC000);
+/// assert_eq!(parse_number("ff").unwrap(), 0xFF);
+/// assert_eq!(parse_number("%1010").unwrap(), 10);
+/// assert_eq!(parse_number("+100").unwrap(), 100);
+/// ```
+pub fn parse_number(text: &str) -> Result<Word, EmulatorError> {
+    let (digits, radix) = if let Some(digits) = text.strip_prefix('$') {
+        (digits, 16)
+    } else if let Some(digits) = text.strip_prefix('%') {
+        (digits, 2)
+    } else if let Some(digits) = text.strip_prefix('+') {
+        (digits, 10)
+    } else {
+        (text, 16)
+    };
+    Word::from_str_radix(digits, radix).map_err(|_| invalid_command(&format!("invalid number: {}", text)))
+}
+
+fn parse_byte(text: &str) -> Result<Byte, EmulatorError> {
+    let value = parse_number(text)?;
+    Byte::try_from(value).map_err(|_| invalid_command(&format!("not a byte: {}", text)))
+}
+
+fn invalid_command(message: &str) -> EmulatorError {
+    EmulatorError::InvalidCommand { message: message.to_owned() }
+}
+
+///
+/// the argument at `index`, an error naming what is missing otherwise
+///
+fn argument<'a>(arguments: &[&'a str], index: usize, name: &str) -> Result<&'a str, EmulatorError> {
+    arguments.get(index).copied().ok_or_else(|| invalid_command(&format!("missing {}", name)))
+}
+
+///
+/// disassemble the instruction at `address` without executing anything
+///
+fn disassemble_line(cpu: &Cpu6502, address: Address) -> DisassembledLine {
+    let bytes = cpu.memory.dump(address, 3);
+    disassemble_instruction(cpu.variant.instructions(), &bytes, address)
+}
+
+impl Monitor {
+    pub fn new(cpu: Cpu6502) -> Monitor {
+        let pc = cpu.registers.pc;
+        Monitor {
+            cpu,
+            next_examine: pc,
+            next_disassemble: pc,
+            break_request: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    ///
+    /// the flag stopping a running `g` once set, for a Ctrl-C handler or another thread,
+    /// it is checked every few thousand instructions
+    ///
+    pub fn break_request(&self) -> Arc<AtomicBool> {
+        self.break_request.clone()
+    }
+
+    ///
+    /// read and execute commands until `x` or the end of the input
+    ///
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> Result<(), EmulatorError> {
+        write!(output, "{}\n. ", trace_line(&self.cpu))?;
+        output.flush()?;
+        for line in input.lines() {
+            if !self.execute(&line?, &mut output)? {
+                break;
+            }
+            write!(output, ". ")?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// execute one command line, returns false when the monitor must be left,
+    /// a command that can not be executed is reported on the output and the monitor goes on
+    ///
+    pub fn execute(&mut self, line: &str, output: &mut dyn Write) -> io::Result<bool> {
+        // the condition of a breakpoint is an expression with its own spaces
+        let (line, condition) = match line.split_once(" if ") {
+            Some((line, condition)) => (line, Some(condition)),
+            None => (line, None),
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (command, arguments) = match words.split_first() {
+            Some((command, arguments)) => (*command, arguments),
+            None => return Ok(true),
+        };
+        let result = match command {
+            "x" | "q" | "quit" => return Ok(false),
+            "help" | "?" => write!(output, "{}", HELP).map_err(EmulatorError::from),
+            "r" => self.registers(arguments, output),
+            "z" | "step" => self.step(arguments, output),
+            "n" | "next" => self.cpu.step_over().and_then(|reason| self.stopped(reason, output)),
+            "ret" => self.cpu.step_out().and_then(|reason| self.stopped(reason, output)),
+            "g" | "continue" => self.go(arguments, output),
+            "m" => self.examine(arguments, output),
+            ">" => self.deposit(arguments),
+            "d" => self.disassemble(arguments, output),
+            "l" => self.load(arguments, output),
+            "s" => self.save(arguments, output),
+            "break" | "tbreak" => self.breakpoint(command == "tbreak", arguments, condition, output),
+            "watch" => self.watchpoint(arguments, condition, output),
+            "delete" | "enable" | "disable" | "ignore" => self.edit_breakpoint(command, arguments),
+            "reset" => {
+                self.cpu.reset();
+                writeln!(output, "{}", trace_line(&self.cpu)).map_err(EmulatorError::from)
+            }
+            _ => Err(invalid_command(&format!("unknown command: {}, try help", command))),
+        };
+        if let Err(error) = result {
+            writeln!(output, "error: {}", error)?;
+        }
+        Ok(true)
+    }
+
+    fn registers(&mut self, arguments: &[&str], output: &mut dyn Write) -> Result<(), EmulatorError> {
+        for assignment in arguments {
+            let (name, value) = assignment
+                .split_once('=')
+                .ok_or_else(|| invalid_command(&format!("expected register=value: {}", assignment)))?;
+            let value = parse_number(value)?;
+            let registers = &mut self.cpu.registers;
+            match name.to_ascii_uppercase().as_str() {
+                "PC" => registers.pc = value,
+                name => {
+                    let value = Byte::try_from(value).map_err(|_| invalid_command(&format!("not a byte: ${:X}", value)))?;
+                    match name {
+                        "A" => registers.a = value,
+                        "X" => registers.x = value,
+                        "Y" => registers.y = value,
+                        "SP" => registers.sp = value,
+                        "P" => registers.status = value,
+                        _ => return Err(invalid_command(&format!("unknown register: {}", name))),
+                    }
+                }
+            }
+        }
+        let flags: String = [
+            (Flag::Negative, 'N'),
+            (Flag::Overflow, 'V'),
+            (Flag::Unused, '-'),
+            (Flag::Break, 'B'),
+            (Flag::Decimal, 'D'),
+            (Flag::Interrupt, 'I'),
+            (Flag::Zero, 'Z'),
+            (Flag::Carry, 'C'),
+        ]
+        .iter()
+        .map(|&(flag, name)| if self.cpu.get_flag(flag) { name } else { '.' })
+        .collect();
+        let registers = &self.cpu.registers;
+        writeln!(
+            output,
+            "PC:{:04X} A:{:02X} X:{:02X} Y:{:02X} SP:{:02X} P:{:02X} {} CYC:{}",
+            registers.pc, registers.a, registers.x, registers.y, registers.sp, registers.status, flags, self.cpu.cycles
+        )?;
+        Ok(())
+    }
+
+    fn step(&mut self, arguments: &[&str], output: &mut dyn Write) -> Result<(), EmulatorError> {
+        let count = match arguments.first() {
+            Some(count) => parse_number(count)? as usize,
+            None => 1,
+        };
+        for _ in 0..count {
+            let reason = self.cpu.run_instructions(1)?;
+            if reason != StopReason::InstructionsExecuted {
+                return self.stopped(reason, output);
+            }
+            writeln!(output, "{}", trace_line(&self.cpu))?;
+        }
+        Ok(())
+    }
+
+    fn go(&mut self, arguments: &[&str], output: &mut dyn Write) -> Result<(), EmulatorError> {
+        if let Some(address) = arguments.first() {
+            self.cpu.registers.pc = parse_number(address)?;
+        }
+        let limit = match arguments.get(1) {
+            Some(count) => Some(parse_number(count)? as usize),
+            None => None,
+        };
+        self.break_request.store(false, Ordering::Relaxed);
+        let mut executed = 0;
+        loop {
+            let chunk = limit.map_or(GO_CHUNK, |limit| GO_CHUNK.min(limit - executed));
+            let reason = self.cpu.run_instructions(chunk)?;
+            if reason != StopReason::InstructionsExecuted {
+                return self.stopped(reason, output);
+            }
+            executed += chunk;
+            if limit == Some(executed) {
+                return self.stopped(reason, output);
+            }
+            if self.break_request.swap(false, Ordering::Relaxed) {
+                writeln!(output, "break")?;
+                return self.stopped(reason, output);
+            }
+        }
+    }
+
+    ///
+    /// report why a run stopped and show where
+    ///
+    fn stopped(&mut self, reason: StopReason, output: &mut dyn Write) -> Result<(), EmulatorError> {
+        match reason {
+            StopReason::Breakpoint(id) => writeln!(output, "breakpoint {}", id)?,
+            StopReason::Watchpoint { id, address, write } => {
+                let access = if write { "write" } else { "read" };
+                writeln!(output, "watchpoint {}, {} ${:04X}", id, access, address)?
+            }
+            StopReason::Stopped => writeln!(output, "stopped")?,
+            StopReason::Waiting => writeln!(output, "waiting for an interrupt")?,
+            _ => {}
+        }
+        self.next_disassemble = self.cpu.registers.pc;
+        writeln!(output, "{}", trace_line(&self.cpu))?;
+        Ok(())
+    }
+
+    ///
+    /// the range given by the arguments, `length` bytes from the default start without an end
+    ///
+    fn range(&self, arguments: &[&str], default: Address, length: Address) -> Result<(Address, Address), EmulatorError> {
+        let start = match arguments.first() {
+            Some(start) => parse_number(start)?,
+            None => default,
+        };
+        let end = match arguments.get(1) {
+            Some(end) => parse_number(end)?,
+            None => start.saturating_add(length - 1),
+        };
+        if end < start {
+            return Err(invalid_command("the end is before the start"));
+        }
+        Ok((start, end))
+    }
+
+    fn examine(&mut self, arguments: &[&str], output: &mut dyn Write) -> Result<(), EmulatorError> {
+        let (start, end) = self.range(arguments, self.next_examine, EXAMINE_LENGTH)?;
+        let mut address = start as usize;
+        while address <= end as usize {
+            let line_end = (address + 15).min(end as usize);
+            let bytes = self.cpu.memory.dump(address as Address, line_end - address + 1);
+            let hex: Vec<String> = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+            let ascii: String = bytes
+                .iter()
+                .map(|&byte| if (32..=126).contains(&byte) { byte as char } else { '.' })
+                .collect();
+            writeln!(output, "{:04X}  {:<47}  {}", address, hex.join(" "), ascii)?;
+            address = line_end + 1;
+        }
+        self.next_examine = (end as usize + 1) as Address;
+        Ok(())
+    }
+
+    fn deposit(&mut self, arguments: &[&str]) -> Result<(), EmulatorError> {
+        let address = parse_number(argument(arguments, 0, "address")?)?;
+        let bytes = arguments[1..].iter().map(|byte| parse_byte(byte)).collect::<Result<Vec<Byte>, _>>()?;
+        if bytes.is_empty() {
+            return Err(invalid_command("missing bytes"));
+        }
+        for (i, &byte) in bytes.iter().enumerate() {
+            self.cpu.memory.write(address.wrapping_add(i as Address), byte);
+        }
+        Ok(())
+    }
+
+    fn disassemble(&mut self, arguments: &[&str], output: &mut dyn Write) -> Result<(), EmulatorError> {
+        let start = match arguments.first() {
+            Some(start) => parse_number(start)?,
+            None => self.next_disassemble,
+        };
+        let end = match arguments.get(1) {
+            Some(end) => Some(parse_number(end)?),
+            None => None,
+        };
+        let mut address = start;
+        for count in 0.. {
+            match end {
+                Some(end) if address > end || address < start => break,
+                None if count == DISASSEMBLE_COUNT => break,
+                _ => {}
+            }
+            let line = disassemble_line(&self.cpu, address);
+            writeln!(output, "{}", line)?;
+            address = address.wrapping_add(line.bytes.len() as Address);
+        }
+        self.next_disassemble = address;
+        Ok(())
+    }
+
+    fn load(&mut self, arguments: &[&str], output: &mut dyn Write) -> Result<(), EmulatorError> {
+        let path = argument(arguments, 0, "file")?;
+        let address = parse_number(argument(arguments, 1, "address")?)?;
+        let data = fs::read(path)?;
+        self.cpu.memory.load(&data, address)?;
+        writeln!(output, "loaded ${:04X} bytes at ${:04X}", data.len(), address)?;
+        Ok(())
+    }
+
+    fn save(&mut self, arguments: &[&str], output: &mut dyn Write) -> Result<(), EmulatorError> {
+        let path = argument(arguments, 0, "file")?;
+        let start = parse_number(argument(arguments, 1, "start address")?)?;
+        let end = parse_number(argument(arguments, 2, "end address")?)?;
+        if end < start {
+            return Err(invalid_command("the end is before the start"));
+        }
+        let data = self.cpu.memory.dump(start, (end - start) as usize + 1);
+        fs::write(path, &data)?;
+        writeln!(output, "saved ${:04X} bytes from ${:04X}", data.len(), start)?;
+        Ok(())
+    }
+
+    fn breakpoint(
+        &mut self,
+        temporary: bool,
+        arguments: &[&str],
+        condition: Option<&str>,
+        output: &mut dyn Write,
+    ) -> Result<(), EmulatorError> {
+        let address = match arguments.first() {
+            Some(address) => parse_number(address)?,
+            None => return self.list_breakpoints(output),
+        };
+        let condition = condition.map(Expression::parse).transpose()?;
+        let id = self.cpu.add_breakpoint_with(BreakpointKind::Execution(address), condition, temporary);
+        writeln!(output, "breakpoint {} at ${:04X}", id, address)?;
+        Ok(())
+    }
+
+    fn watchpoint(&mut self, arguments: &[&str], condition: Option<&str>, output: &mut dyn Write) -> Result<(), EmulatorError> {
+        let access = match argument(arguments, 0, "access")? {
+            "r" => Access::Read,
+            "w" => Access::Write,
+            "rw" => Access::ReadWrite,
+            access => return Err(invalid_command(&format!("expected r, w or rw: {}", access))),
+        };
+        let start = parse_number(argument(arguments, 1, "address")?)?;
+        let end = match arguments.get(2) {
+            Some(end) => parse_number(end)?,
+            None => start,
+        };
+        if end < start {
+            return Err(invalid_command("the end is before the start"));
+        }
+        let condition = condition.map(Expression::parse).transpose()?;
+        let id = self.cpu.add_breakpoint_with(BreakpointKind::Watch { start, end, access }, condition, false);
+        writeln!(output, "watchpoint {} on ${:04X}-${:04X}", id, start, end)?;
+        Ok(())
+    }
+
+    fn list_breakpoints(&self, output: &mut dyn Write) -> Result<(), EmulatorError> {
+        for breakpoint in self.cpu.breakpoints() {
+            let place = match breakpoint.kind {
+                BreakpointKind::Execution(address) => format!("break ${:04X}", address),
+                BreakpointKind::Watch { start, end, access } => {
+                    let access = match access {
+                        Access::Read => "r",
+                        Access::Write => "w",
+                        Access::ReadWrite => "rw",
+                    };
+                    format!("watch {} ${:04X}-${:04X}", access, start, end)
+                }
+            };
+            write!(output, "{}: {}, {} hits", breakpoint.id, place, breakpoint.hits)?;
+            if let Some(condition) = &breakpoint.condition {
+                write!(output, ", if {}", condition)?;
+            }
+            if breakpoint.ignore_count > 0 {
+                write!(output, ", ignore {}", breakpoint.ignore_count)?;
+            }
+            if breakpoint.temporary {
+                write!(output, ", temporary")?;
+            }
+            if !breakpoint.enabled {
+                write!(output, ", disabled")?;
+            }
+            writeln!(output)?;
+        }
+        Ok(())
+    }
+
+    fn edit_breakpoint(&mut self, command: &str, arguments: &[&str]) -> Result<(), EmulatorError> {
+        let id = argument(arguments, 0, "breakpoint id")?;
+        let id: usize = id.parse().map_err(|_| invalid_command(&format!("invalid breakpoint id: {}", id)))?;
+        if command == "delete" {
+            if !self.cpu.remove_breakpoint(id) {
+                return Err(invalid_command(&format!("no breakpoint {}", id)));
+            }
+            return Ok(());
+        }
+        let count = match command {
+            "ignore" => Some(parse_number(argument(arguments, 1, "count")?)? as u64),
+            _ => None,
+        };
+        let breakpoint = self
+            .cpu
+            .breakpoint_mut(id)
+            .ok_or_else(|| invalid_command(&format!("no breakpoint {}", id)))?;
+        match command {
+            "enable" => breakpoint.enabled = true,
+            "disable" => breakpoint.enabled = false,
+            _ => breakpoint.ignore_count = count.unwrap_or_default(),
+        }
+        Ok(())
+    }
+}
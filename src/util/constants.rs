@@ -9,4 +9,10 @@ pub const STACK_SIZE: usize = 0x100;
 pub const RESET_ADDRESS_LOW: Address = 0xFFFC;
 pub const RESET_ADDRESS_HIGH: Address = 0xFFFD;
 
+pub const NMI_ADDRESS_LOW: Address = 0xFFFA;
+pub const NMI_ADDRESS_HIGH: Address = 0xFFFB;
+
+pub const IRQ_ADDRESS_LOW: Address = 0xFFFE;
+pub const IRQ_ADDRESS_HIGH: Address = 0xFFFF;
+
 pub const OPCODE_KIL: &str = "KIL";
\ No newline at end of file
@@ -1,2 +1,4 @@
 pub mod types;
-pub mod constants;
\ No newline at end of file
+pub mod constants;
+pub mod state;
+pub mod json;
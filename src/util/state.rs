@@ -0,0 +1,104 @@
+//!
+//! File: util/state.rs
+//! The state module contains the writer and the reader of the save state format.
+//! Every value is stored little endian without any padding, the cpu and the components
+//! read their fields back in the order they wrote them.
+//!
+
+use crate::error::EmulatorError;
+use crate::util::types::{Byte, Word};
+
+#[derive(Default)]
+pub struct StateWriter {
+    data: Vec<Byte>,
+}
+
+impl StateWriter {
+    pub fn new() -> StateWriter {
+        StateWriter {
+            data: Vec::new(),
+        }
+    }
+
+    pub fn write_byte(&mut self, value: Byte) {
+        self.data.push(value);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.data.push(value as Byte);
+    }
+
+    pub fn write_word(&mut self, value: Word) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[Byte]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    pub fn into_bytes(self) -> Vec<Byte> {
+        self.data
+    }
+}
+
+pub struct StateReader<'a> {
+    data: &'a [Byte],
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(data: &'a [Byte]) -> StateReader<'a> {
+        StateReader {
+            data,
+        }
+    }
+
+    ///
+    /// take the next `len` bytes, fails if the state is truncated
+    ///
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [Byte], EmulatorError> {
+        if self.data.len() < len {
+            return Err(invalid_state("truncated save state"));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    pub fn read_byte(&mut self) -> Result<Byte, EmulatorError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, EmulatorError> {
+        match self.read_byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(invalid_state(&format!("invalid boolean {:02X}", value))),
+        }
+    }
+
+    pub fn read_word(&mut self) -> Result<Word, EmulatorError> {
+        let bytes = self.read_bytes(2)?;
+        Ok(Word::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, EmulatorError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    ///
+    /// true once every byte has been read
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+pub fn invalid_state(message: &str) -> EmulatorError {
+    EmulatorError::InvalidSaveState { message: message.to_owned() }
+}
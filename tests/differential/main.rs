@@ -0,0 +1,414 @@
+//!
+//! File: tests/differential/main.rs
+//! The differential module runs random programs on the core of the crate and on the
+//! reference model of `reference.rs`, both behind the `Core` trait,
+//! and compares the registers, the cycle count and the written memory after every instruction.
+//! A failing case is shrunk to the smallest program and initial state still diverging.
+//! The number of cases is taken from `FUZZ_CASES`, 500 by default, and the seed of
+//! the random generator from `FUZZ_SEED`, 6502 by default so that every run checks
+//! the same cases, it is printed with every failure to replay it.
+//!
+
+mod reference;
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt;
+use std::rc::Rc;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use emul::cpu::cpu_6502::Cpu6502;
+use emul::memory::bus::Bus;
+use emul::memory::ram::Ram;
+use emul::util::types::{Address, Byte};
+
+use reference::Reference;
+
+// seed of the random generator when `FUZZ_SEED` is not set
+const DEFAULT_SEED: u64 = 6502;
+
+// the program is always loaded here, the initial memory is kept away from it
+const PROGRAM_START: Address = 0x0200;
+// loops end there, most programs leave their code much earlier
+const MAX_STEPS: usize = 200;
+const MAX_INSTRUCTIONS: usize = 24;
+const MAX_POKES: usize = 48;
+
+///
+/// registers of a core between two instructions
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Registers {
+    pc: Address,
+    a: Byte,
+    x: Byte,
+    y: Byte,
+    s: Byte,
+    p: Byte,
+}
+
+///
+/// a 6502 the harness can run, the written addresses are reported so the memory
+/// does not have to be compared in full after every instruction
+///
+trait Core {
+    fn registers(&self) -> Registers;
+    // execute one instruction, an error ends the case as a divergence
+    fn step(&mut self) -> Result<(), String>;
+    fn cycles(&self) -> u64;
+    fn peek(&self, address: Address) -> Byte;
+    // the addresses written since the last call
+    fn take_written(&mut self) -> Vec<Address>;
+}
+
+///
+/// RAM remembering the written addresses
+///
+struct WatchedRam {
+    ram: Ram,
+    written: Rc<RefCell<Vec<Address>>>,
+}
+
+impl Bus for WatchedRam {
+    fn read(&mut self, address: Address) -> Byte {
+        self.ram.read(address)
+    }
+
+    fn write(&mut self, address: Address, data: Byte) {
+        self.written.borrow_mut().push(address);
+        self.ram.write(address, data);
+    }
+
+    fn peek(&self, address: Address) -> Byte {
+        self.ram.peek(address)
+    }
+}
+
+struct Emulator {
+    cpu: Cpu6502,
+    written: Rc<RefCell<Vec<Address>>>,
+}
+
+impl Emulator {
+    fn new(case: &Case) -> Emulator {
+        let mut ram = Ram::new();
+        for (address, data) in case.memory() {
+            ram.write(address, data);
+        }
+        let written = Rc::new(RefCell::new(Vec::new()));
+        let mut cpu = Cpu6502::new(WatchedRam { ram, written: written.clone() });
+        cpu.registers.pc = PROGRAM_START;
+        cpu.registers.a = case.a;
+        cpu.registers.x = case.x;
+        cpu.registers.y = case.y;
+        cpu.registers.sp = case.s;
+        cpu.registers.status = case.p;
+        Emulator { cpu, written }
+    }
+}
+
+impl Core for Emulator {
+    fn registers(&self) -> Registers {
+        let registers = &self.cpu.registers;
+        Registers {
+            pc: registers.pc,
+            a: registers.a,
+            x: registers.x,
+            y: registers.y,
+            s: registers.sp,
+            p: registers.status,
+        }
+    }
+
+    fn step(&mut self) -> Result<(), String> {
+        self.cpu.execute_instruction().map(|_| ()).map_err(|error| error.to_string())
+    }
+
+    fn cycles(&self) -> u64 {
+        self.cpu.cycles
+    }
+
+    fn peek(&self, address: Address) -> Byte {
+        self.cpu.memory.peek(address)
+    }
+
+    fn take_written(&mut self) -> Vec<Address> {
+        self.written.borrow_mut().drain(..).collect()
+    }
+}
+
+impl Reference {
+    fn with_case(case: &Case) -> Reference {
+        let mut reference = Reference::new();
+        for (address, data) in case.memory() {
+            reference.memory[address as usize] = data;
+        }
+        reference.pc = PROGRAM_START;
+        reference.a = case.a;
+        reference.x = case.x;
+        reference.y = case.y;
+        reference.s = case.s;
+        reference.p = case.p;
+        reference
+    }
+}
+
+impl Core for Reference {
+    fn registers(&self) -> Registers {
+        Registers { pc: self.pc, a: self.a, x: self.x, y: self.y, s: self.s, p: self.p }
+    }
+
+    fn step(&mut self) -> Result<(), String> {
+        if Reference::step(self) {
+            Ok(())
+        } else {
+            Err("undocumented opcode".to_owned())
+        }
+    }
+
+    fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    fn peek(&self, address: Address) -> Byte {
+        self.memory[address as usize]
+    }
+
+    fn take_written(&mut self) -> Vec<Address> {
+        self.written.drain(..).collect()
+    }
+}
+
+///
+/// initial state and program of a run
+///
+#[derive(Clone, PartialEq, Debug)]
+struct Case {
+    a: Byte,
+    x: Byte,
+    y: Byte,
+    s: Byte,
+    p: Byte,
+    // bytes written in memory before the run, outside of the program
+    pokes: Vec<(Address, Byte)>,
+    // documented instructions with their operand bytes, loaded from PROGRAM_START
+    program: Vec<Vec<Byte>>,
+}
+
+impl Case {
+    fn random(rng: &mut StdRng) -> Case {
+        let opcodes: Vec<Byte> = (0..=255).filter(|&opcode| reference::decode(opcode).is_some()).collect();
+        let program = (0..rng.gen_range(1..=MAX_INSTRUCTIONS))
+            .map(|_| {
+                let opcode = opcodes[rng.gen_range(0..opcodes.len())];
+                let (_, mode, _) = reference::decode(opcode).unwrap();
+                let mut instruction = vec![opcode];
+                instruction.extend((0..mode.operand_length()).map(|_| rng.gen::<Byte>()));
+                instruction
+            })
+            .collect();
+        let pokes = (0..rng.gen_range(0..=MAX_POKES))
+            .map(|_| {
+                // mostly pointers and data in the zero page, some on the stack and after the program
+                let address = match rng.gen_range(0..4) {
+                    0 | 1 => rng.gen_range(0x0000..0x0100),
+                    2 => rng.gen_range(0x0100..0x0200),
+                    _ => rng.gen_range(0x0300..0x0400),
+                };
+                (address, rng.gen())
+            })
+            .collect();
+        Case {
+            a: rng.gen(),
+            x: rng.gen(),
+            y: rng.gen(),
+            s: rng.gen(),
+            p: rng.gen(),
+            pokes,
+            program,
+        }
+    }
+
+    ///
+    /// the pokes followed by the program
+    ///
+    fn memory(&self) -> Vec<(Address, Byte)> {
+        let program = self.program.iter().flatten().enumerate();
+        let program = program.map(|(offset, &byte)| (PROGRAM_START + offset as Address, byte));
+        self.pokes.iter().copied().chain(program).collect()
+    }
+
+    ///
+    /// smaller or simpler variants of the case, the biggest simplifications first
+    ///
+    fn simplifications(&self) -> Vec<Case> {
+        let mut cases = Vec::new();
+        for index in 0..self.program.len() {
+            let mut case = self.clone();
+            case.program.remove(index);
+            cases.push(case);
+        }
+        for index in 0..self.pokes.len() {
+            let mut case = self.clone();
+            case.pokes.remove(index);
+            cases.push(case);
+        }
+        let registers: [fn(&mut Case) -> &mut Byte; 5] =
+            [|case| &mut case.a, |case| &mut case.x, |case| &mut case.y, |case| &mut case.s, |case| &mut case.p];
+        for register in registers {
+            let mut case = self.clone();
+            if *register(&mut case) != 0 {
+                *register(&mut case) = 0;
+                cases.push(case);
+            }
+        }
+        for index in 0..self.pokes.len() {
+            if self.pokes[index].1 != 0 {
+                let mut case = self.clone();
+                case.pokes[index].1 = 0;
+                cases.push(case);
+            }
+        }
+        for (index, instruction) in self.program.iter().enumerate() {
+            for (byte, &value) in instruction.iter().enumerate().skip(1) {
+                if value != 0 {
+                    let mut case = self.clone();
+                    case.program[index][byte] = 0;
+                    cases.push(case);
+                }
+            }
+        }
+        cases
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "A:{:02X} X:{:02X} Y:{:02X} SP:{:02X} P:{:02X}", self.a, self.x, self.y, self.s, self.p)?;
+        for (address, data) in self.pokes.iter() {
+            writeln!(f, "{:04X}: {:02X}", address, data)?;
+        }
+        let mut address = PROGRAM_START;
+        for instruction in self.program.iter() {
+            let bytes: Vec<String> = instruction.iter().map(|byte| format!("{:02X}", byte)).collect();
+            let (mnemonic, mode, _) = reference::decode(instruction[0]).unwrap();
+            writeln!(f, "{:04X}: {:<8} {} {:?}", address, bytes.join(" "), mnemonic, mode)?;
+            address += instruction.len() as Address;
+        }
+        Ok(())
+    }
+}
+
+///
+/// run the case on both cores, returns the first difference
+///
+fn run_case(case: &Case) -> Option<String> {
+    let mut cores: [Box<dyn Core>; 2] = [Box::new(Emulator::new(case)), Box::new(Reference::with_case(case))];
+    let mut start_cycles = [cores[0].cycles(), cores[1].cycles()];
+    for step in 0..MAX_STEPS {
+        let pc = cores[1].registers().pc;
+        let opcode = cores[1].peek(pc);
+        // the run ends on data or on an instruction wrapping around the address space
+        match reference::decode(opcode) {
+            Some((_, mode, _)) if pc as usize + mode.operand_length() < 0x10000 => {}
+            _ => return None,
+        }
+        let results = [cores[0].step(), cores[1].step()];
+        if let Err(error) = &results[0] {
+            return Some(format!("step {} at {:04X}: the core failed with {}", step, pc, error));
+        }
+
+        let registers = [cores[0].registers(), cores[1].registers()];
+        let fields = [
+            ("PC", registers[0].pc, registers[1].pc),
+            ("A", registers[0].a as Address, registers[1].a as Address),
+            ("X", registers[0].x as Address, registers[1].x as Address),
+            ("Y", registers[0].y as Address, registers[1].y as Address),
+            ("SP", registers[0].s as Address, registers[1].s as Address),
+            ("P", registers[0].p as Address, registers[1].p as Address),
+        ];
+        for (name, actual, expected) in fields {
+            if actual != expected {
+                return Some(format!(
+                    "step {} at {:04X}: {} is {:02X} instead of {:02X}",
+                    step, pc, name, actual, expected
+                ));
+            }
+        }
+        let cycles = [cores[0].cycles() - start_cycles[0], cores[1].cycles() - start_cycles[1]];
+        if cycles[0] != cycles[1] {
+            return Some(format!("step {} at {:04X}: took {} cycles instead of {}", step, pc, cycles[0], cycles[1]));
+        }
+        start_cycles = [cores[0].cycles(), cores[1].cycles()];
+
+        let written: BTreeSet<Address> = cores[0].take_written().into_iter().chain(cores[1].take_written()).collect();
+        for address in written {
+            let (actual, expected) = (cores[0].peek(address), cores[1].peek(address));
+            if actual != expected {
+                return Some(format!(
+                    "step {} at {:04X}: {:04X} holds {:02X} instead of {:02X}",
+                    step, pc, address, actual, expected
+                ));
+            }
+        }
+    }
+    None
+}
+
+///
+/// apply the simplifications as long as one of them still fails
+///
+fn shrink<F: Fn(&Case) -> bool>(mut case: Case, fails: F) -> Case {
+    while let Some(simpler) = case.simplifications().into_iter().find(|candidate| fails(candidate)) {
+        case = simpler;
+    }
+    case
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+}
+
+#[test]
+fn test_against_reference() {
+    let seed = env_or("FUZZ_SEED", DEFAULT_SEED);
+    let cases: usize = env_or("FUZZ_CASES", 500);
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..cases {
+        let case = Case::random(&mut rng);
+        if run_case(&case).is_some() {
+            let case = shrink(case, |case| run_case(case).is_some());
+            panic!("FUZZ_SEED={} diverges, {}\n{}", seed, run_case(&case).unwrap(), case);
+        }
+    }
+}
+
+#[test]
+fn test_shrink() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut case = Case::random(&mut rng);
+    case.program.push(vec![0xE8]);
+    // any program with an INX is the smallest with only it and nothing else set
+    let case = shrink(case, |case| case.program.iter().any(|instruction| instruction[0] == 0xE8));
+    assert_eq!(case.program, vec![vec![0xE8]]);
+    assert_eq!(case.pokes, vec![]);
+    assert_eq!((case.a, case.x, case.y, case.s, case.p), (0, 0, 0, 0, 0));
+}
+
+#[test]
+fn test_adc_carry_regression() {
+    // $FF + $FF + 1 must set the carry
+    let case = Case {
+        a: 0xFF,
+        x: 0,
+        y: 0,
+        s: 0xFD,
+        p: 0x01,
+        pokes: vec![],
+        program: vec![vec![0x69, 0xFF]],
+    };
+    assert_eq!(run_case(&case), None);
+}
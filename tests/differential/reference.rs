@@ -0,0 +1,549 @@
+//!
+//! File: tests/differential/reference.rs
+//! The reference module contains a second 6502 written from the datasheets, independently
+//! of the core of the crate, to be compared with it instruction by instruction.
+//! It is a plain interpreter of the 151 documented NMOS opcodes on a flat 64K memory,
+//! with the NMOS decimal mode and the instruction timings, and nothing else:
+//! no bus, no interrupt lines and no undocumented opcode.
+//!
+//! # See
+//! * [http://www.6502.org/tutorials/decimal_mode.html](http://www.6502.org/tutorials/decimal_mode.html)
+//!
+
+const CARRY: u8 = 0x01;
+const ZERO: u8 = 0x02;
+const INTERRUPT: u8 = 0x04;
+const DECIMAL: u8 = 0x08;
+const BREAK: u8 = 0x10;
+const UNUSED: u8 = 0x20;
+const OVERFLOW: u8 = 0x40;
+const NEGATIVE: u8 = 0x80;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Mode {
+    Implied,
+    Accumulator,
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    Indirect,
+    IndirectX,
+    IndirectY,
+    Relative,
+}
+
+impl Mode {
+    ///
+    /// number of operand bytes following the opcode
+    ///
+    pub fn operand_length(self) -> usize {
+        match self {
+            Mode::Implied | Mode::Accumulator => 0,
+            Mode::Absolute | Mode::AbsoluteX | Mode::AbsoluteY | Mode::Indirect => 2,
+            _ => 1,
+        }
+    }
+}
+
+///
+/// mnemonic, addressing mode and base cycle count of a documented opcode
+///
+pub fn decode(opcode: u8) -> Option<(&'static str, Mode, u64)> {
+    use Mode::*;
+    let entry = match opcode {
+        0x69 => ("ADC", Immediate, 2),
+        0x65 => ("ADC", ZeroPage, 3),
+        0x75 => ("ADC", ZeroPageX, 4),
+        0x6D => ("ADC", Absolute, 4),
+        0x7D => ("ADC", AbsoluteX, 4),
+        0x79 => ("ADC", AbsoluteY, 4),
+        0x61 => ("ADC", IndirectX, 6),
+        0x71 => ("ADC", IndirectY, 5),
+        0x29 => ("AND", Immediate, 2),
+        0x25 => ("AND", ZeroPage, 3),
+        0x35 => ("AND", ZeroPageX, 4),
+        0x2D => ("AND", Absolute, 4),
+        0x3D => ("AND", AbsoluteX, 4),
+        0x39 => ("AND", AbsoluteY, 4),
+        0x21 => ("AND", IndirectX, 6),
+        0x31 => ("AND", IndirectY, 5),
+        0x0A => ("ASL", Accumulator, 2),
+        0x06 => ("ASL", ZeroPage, 5),
+        0x16 => ("ASL", ZeroPageX, 6),
+        0x0E => ("ASL", Absolute, 6),
+        0x1E => ("ASL", AbsoluteX, 7),
+        0x90 => ("BCC", Relative, 2),
+        0xB0 => ("BCS", Relative, 2),
+        0xF0 => ("BEQ", Relative, 2),
+        0x24 => ("BIT", ZeroPage, 3),
+        0x2C => ("BIT", Absolute, 4),
+        0x30 => ("BMI", Relative, 2),
+        0xD0 => ("BNE", Relative, 2),
+        0x10 => ("BPL", Relative, 2),
+        0x00 => ("BRK", Implied, 7),
+        0x50 => ("BVC", Relative, 2),
+        0x70 => ("BVS", Relative, 2),
+        0x18 => ("CLC", Implied, 2),
+        0xD8 => ("CLD", Implied, 2),
+        0x58 => ("CLI", Implied, 2),
+        0xB8 => ("CLV", Implied, 2),
+        0xC9 => ("CMP", Immediate, 2),
+        0xC5 => ("CMP", ZeroPage, 3),
+        0xD5 => ("CMP", ZeroPageX, 4),
+        0xCD => ("CMP", Absolute, 4),
+        0xDD => ("CMP", AbsoluteX, 4),
+        0xD9 => ("CMP", AbsoluteY, 4),
+        0xC1 => ("CMP", IndirectX, 6),
+        0xD1 => ("CMP", IndirectY, 5),
+        0xE0 => ("CPX", Immediate, 2),
+        0xE4 => ("CPX", ZeroPage, 3),
+        0xEC => ("CPX", Absolute, 4),
+        0xC0 => ("CPY", Immediate, 2),
+        0xC4 => ("CPY", ZeroPage, 3),
+        0xCC => ("CPY", Absolute, 4),
+        0xC6 => ("DEC", ZeroPage, 5),
+        0xD6 => ("DEC", ZeroPageX, 6),
+        0xCE => ("DEC", Absolute, 6),
+        0xDE => ("DEC", AbsoluteX, 7),
+        0xCA => ("DEX", Implied, 2),
+        0x88 => ("DEY", Implied, 2),
+        0x49 => ("EOR", Immediate, 2),
+        0x45 => ("EOR", ZeroPage, 3),
+        0x55 => ("EOR", ZeroPageX, 4),
+        0x4D => ("EOR", Absolute, 4),
+        0x5D => ("EOR", AbsoluteX, 4),
+        0x59 => ("EOR", AbsoluteY, 4),
+        0x41 => ("EOR", IndirectX, 6),
+        0x51 => ("EOR", IndirectY, 5),
+        0xE6 => ("INC", ZeroPage, 5),
+        0xF6 => ("INC", ZeroPageX, 6),
+        0xEE => ("INC", Absolute, 6),
+        0xFE => ("INC", AbsoluteX, 7),
+        0xE8 => ("INX", Implied, 2),
+        0xC8 => ("INY", Implied, 2),
+        0x4C => ("JMP", Absolute, 3),
+        0x6C => ("JMP", Indirect, 5),
+        0x20 => ("JSR", Absolute, 6),
+        0xA9 => ("LDA", Immediate, 2),
+        0xA5 => ("LDA", ZeroPage, 3),
+        0xB5 => ("LDA", ZeroPageX, 4),
+        0xAD => ("LDA", Absolute, 4),
+        0xBD => ("LDA", AbsoluteX, 4),
+        0xB9 => ("LDA", AbsoluteY, 4),
+        0xA1 => ("LDA", IndirectX, 6),
+        0xB1 => ("LDA", IndirectY, 5),
+        0xA2 => ("LDX", Immediate, 2),
+        0xA6 => ("LDX", ZeroPage, 3),
+        0xB6 => ("LDX", ZeroPageY, 4),
+        0xAE => ("LDX", Absolute, 4),
+        0xBE => ("LDX", AbsoluteY, 4),
+        0xA0 => ("LDY", Immediate, 2),
+        0xA4 => ("LDY", ZeroPage, 3),
+        0xB4 => ("LDY", ZeroPageX, 4),
+        0xAC => ("LDY", Absolute, 4),
+        0xBC => ("LDY", AbsoluteX, 4),
+        0x4A => ("LSR", Accumulator, 2),
+        0x46 => ("LSR", ZeroPage, 5),
+        0x56 => ("LSR", ZeroPageX, 6),
+        0x4E => ("LSR", Absolute, 6),
+        0x5E => ("LSR", AbsoluteX, 7),
+        0xEA => ("NOP", Implied, 2),
+        0x09 => ("ORA", Immediate, 2),
+        0x05 => ("ORA", ZeroPage, 3),
+        0x15 => ("ORA", ZeroPageX, 4),
+        0x0D => ("ORA", Absolute, 4),
+        0x1D => ("ORA", AbsoluteX, 4),
+        0x19 => ("ORA", AbsoluteY, 4),
+        0x01 => ("ORA", IndirectX, 6),
+        0x11 => ("ORA", IndirectY, 5),
+        0x48 => ("PHA", Implied, 3),
+        0x08 => ("PHP", Implied, 3),
+        0x68 => ("PLA", Implied, 4),
+        0x28 => ("PLP", Implied, 4),
+        0x2A => ("ROL", Accumulator, 2),
+        0x26 => ("ROL", ZeroPage, 5),
+        0x36 => ("ROL", ZeroPageX, 6),
+        0x2E => ("ROL", Absolute, 6),
+        0x3E => ("ROL", AbsoluteX, 7),
+        0x6A => ("ROR", Accumulator, 2),
+        0x66 => ("ROR", ZeroPage, 5),
+        0x76 => ("ROR", ZeroPageX, 6),
+        0x6E => ("ROR", Absolute, 6),
+        0x7E => ("ROR", AbsoluteX, 7),
+        0x40 => ("RTI", Implied, 6),
+        0x60 => ("RTS", Implied, 6),
+        0xE9 => ("SBC", Immediate, 2),
+        0xE5 => ("SBC", ZeroPage, 3),
+        0xF5 => ("SBC", ZeroPageX, 4),
+        0xED => ("SBC", Absolute, 4),
+        0xFD => ("SBC", AbsoluteX, 4),
+        0xF9 => ("SBC", AbsoluteY, 4),
+        0xE1 => ("SBC", IndirectX, 6),
+        0xF1 => ("SBC", IndirectY, 5),
+        0x38 => ("SEC", Implied, 2),
+        0xF8 => ("SED", Implied, 2),
+        0x78 => ("SEI", Implied, 2),
+        0x85 => ("STA", ZeroPage, 3),
+        0x95 => ("STA", ZeroPageX, 4),
+        0x8D => ("STA", Absolute, 4),
+        0x9D => ("STA", AbsoluteX, 5),
+        0x99 => ("STA", AbsoluteY, 5),
+        0x81 => ("STA", IndirectX, 6),
+        0x91 => ("STA", IndirectY, 6),
+        0x86 => ("STX", ZeroPage, 3),
+        0x96 => ("STX", ZeroPageY, 4),
+        0x8E => ("STX", Absolute, 4),
+        0x84 => ("STY", ZeroPage, 3),
+        0x94 => ("STY", ZeroPageX, 4),
+        0x8C => ("STY", Absolute, 4),
+        0xAA => ("TAX", Implied, 2),
+        0xA8 => ("TAY", Implied, 2),
+        0xBA => ("TSX", Implied, 2),
+        0x8A => ("TXA", Implied, 2),
+        0x9A => ("TXS", Implied, 2),
+        0x98 => ("TYA", Implied, 2),
+        _ => return None,
+    };
+    Some(entry)
+}
+
+pub struct Reference {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub s: u8,
+    pub p: u8,
+    pub pc: u16,
+    pub cycles: u64,
+    pub memory: Vec<u8>,
+    // every address written since the start, in order
+    pub written: Vec<u16>,
+}
+
+impl Reference {
+    pub fn new() -> Reference {
+        Reference {
+            a: 0,
+            x: 0,
+            y: 0,
+            s: 0xFD,
+            p: UNUSED | INTERRUPT,
+            pc: 0,
+            cycles: 0,
+            memory: vec![0; 0x10000],
+            written: Vec::new(),
+        }
+    }
+
+    fn read(&self, address: u16) -> u8 {
+        self.memory[address as usize]
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        self.memory[address as usize] = value;
+        self.written.push(address);
+    }
+
+    fn read_word(&self, address: u16) -> u16 {
+        self.read(address) as u16 | (self.read(address.wrapping_add(1)) as u16) << 8
+    }
+
+    ///
+    /// read a pointer in the zero page, the high byte of $FF is at $00
+    ///
+    fn read_zero_page_word(&self, address: u8) -> u16 {
+        self.read(address as u16) as u16 | (self.read(address.wrapping_add(1) as u16) as u16) << 8
+    }
+
+    fn push(&mut self, value: u8) {
+        self.write(0x0100 | self.s as u16, value);
+        self.s = self.s.wrapping_sub(1);
+    }
+
+    fn pull(&mut self) -> u8 {
+        self.s = self.s.wrapping_add(1);
+        self.read(0x0100 | self.s as u16)
+    }
+
+    fn flag(&self, flag: u8) -> bool {
+        self.p & flag != 0
+    }
+
+    fn set(&mut self, flag: u8, value: bool) {
+        if value {
+            self.p |= flag;
+        } else {
+            self.p &= !flag;
+        }
+    }
+
+    fn set_nz(&mut self, value: u8) {
+        self.set(ZERO, value == 0);
+        self.set(NEGATIVE, value & 0x80 != 0);
+    }
+
+    ///
+    /// execute the instruction at pc, false if the opcode is not a documented one
+    /// and nothing was executed
+    ///
+    pub fn step(&mut self) -> bool {
+        let opcode = self.read(self.pc);
+        let (mnemonic, mode, cycles) = match decode(opcode) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        let operand = self.pc.wrapping_add(1);
+        self.pc = operand.wrapping_add(mode.operand_length() as u16);
+        self.cycles += cycles;
+
+        let mut page_crossed = false;
+        let address = match mode {
+            Mode::Implied | Mode::Accumulator => 0,
+            Mode::Immediate => operand,
+            Mode::ZeroPage => self.read(operand) as u16,
+            Mode::ZeroPageX => self.read(operand).wrapping_add(self.x) as u16,
+            Mode::ZeroPageY => self.read(operand).wrapping_add(self.y) as u16,
+            Mode::Absolute => self.read_word(operand),
+            Mode::AbsoluteX | Mode::AbsoluteY => {
+                let base = self.read_word(operand);
+                let index = if mode == Mode::AbsoluteX { self.x } else { self.y };
+                let address = base.wrapping_add(index as u16);
+                page_crossed = base & 0xFF00 != address & 0xFF00;
+                address
+            }
+            Mode::Indirect => {
+                // the pointer never crosses a page, JMP ($10FF) reads its high byte at $1000
+                let pointer = self.read_word(operand);
+                let high = (pointer & 0xFF00) | (pointer.wrapping_add(1) & 0x00FF);
+                self.read(pointer) as u16 | (self.read(high) as u16) << 8
+            }
+            Mode::IndirectX => self.read_zero_page_word(self.read(operand).wrapping_add(self.x)),
+            Mode::IndirectY => {
+                let base = self.read_zero_page_word(self.read(operand));
+                let address = base.wrapping_add(self.y as u16);
+                page_crossed = base & 0xFF00 != address & 0xFF00;
+                address
+            }
+            Mode::Relative => self.pc.wrapping_add(self.read(operand) as i8 as u16),
+        };
+        if page_crossed && matches!(mnemonic, "ADC" | "AND" | "CMP" | "EOR" | "LDA" | "LDX" | "LDY" | "ORA" | "SBC") {
+            self.cycles += 1;
+        }
+
+        let value = if mode == Mode::Accumulator { self.a } else { self.read(address) };
+        match mnemonic {
+            "ADC" => self.adc(value),
+            "SBC" => self.sbc(value),
+            "AND" => {
+                self.a &= value;
+                self.set_nz(self.a);
+            }
+            "ORA" => {
+                self.a |= value;
+                self.set_nz(self.a);
+            }
+            "EOR" => {
+                self.a ^= value;
+                self.set_nz(self.a);
+            }
+            "BIT" => {
+                self.set(ZERO, self.a & value == 0);
+                self.set(OVERFLOW, value & 0x40 != 0);
+                self.set(NEGATIVE, value & 0x80 != 0);
+            }
+            "CMP" => self.compare(self.a, value),
+            "CPX" => self.compare(self.x, value),
+            "CPY" => self.compare(self.y, value),
+            "LDA" => {
+                self.a = value;
+                self.set_nz(value);
+            }
+            "LDX" => {
+                self.x = value;
+                self.set_nz(value);
+            }
+            "LDY" => {
+                self.y = value;
+                self.set_nz(value);
+            }
+            "STA" => self.write(address, self.a),
+            "STX" => self.write(address, self.x),
+            "STY" => self.write(address, self.y),
+            "ASL" | "LSR" | "ROL" | "ROR" => {
+                let carry_in = self.flag(CARRY) as u8;
+                let (result, carry_out) = match mnemonic {
+                    "ASL" => (value << 1, value & 0x80 != 0),
+                    "LSR" => (value >> 1, value & 0x01 != 0),
+                    "ROL" => (value << 1 | carry_in, value & 0x80 != 0),
+                    _ => (value >> 1 | carry_in << 7, value & 0x01 != 0),
+                };
+                self.set(CARRY, carry_out);
+                self.set_nz(result);
+                if mode == Mode::Accumulator {
+                    self.a = result;
+                } else {
+                    self.write(address, result);
+                }
+            }
+            "INC" | "DEC" => {
+                let result = if mnemonic == "INC" { value.wrapping_add(1) } else { value.wrapping_sub(1) };
+                self.set_nz(result);
+                self.write(address, result);
+            }
+            "INX" => {
+                self.x = self.x.wrapping_add(1);
+                self.set_nz(self.x);
+            }
+            "INY" => {
+                self.y = self.y.wrapping_add(1);
+                self.set_nz(self.y);
+            }
+            "DEX" => {
+                self.x = self.x.wrapping_sub(1);
+                self.set_nz(self.x);
+            }
+            "DEY" => {
+                self.y = self.y.wrapping_sub(1);
+                self.set_nz(self.y);
+            }
+            "TAX" => {
+                self.x = self.a;
+                self.set_nz(self.x);
+            }
+            "TAY" => {
+                self.y = self.a;
+                self.set_nz(self.y);
+            }
+            "TXA" => {
+                self.a = self.x;
+                self.set_nz(self.a);
+            }
+            "TYA" => {
+                self.a = self.y;
+                self.set_nz(self.a);
+            }
+            "TSX" => {
+                self.x = self.s;
+                self.set_nz(self.x);
+            }
+            "TXS" => self.s = self.x,
+            "CLC" => self.set(CARRY, false),
+            "SEC" => self.set(CARRY, true),
+            "CLD" => self.set(DECIMAL, false),
+            "SED" => self.set(DECIMAL, true),
+            "CLI" => self.set(INTERRUPT, false),
+            "SEI" => self.set(INTERRUPT, true),
+            "CLV" => self.set(OVERFLOW, false),
+            "BCC" => self.branch(!self.flag(CARRY), address),
+            "BCS" => self.branch(self.flag(CARRY), address),
+            "BNE" => self.branch(!self.flag(ZERO), address),
+            "BEQ" => self.branch(self.flag(ZERO), address),
+            "BPL" => self.branch(!self.flag(NEGATIVE), address),
+            "BMI" => self.branch(self.flag(NEGATIVE), address),
+            "BVC" => self.branch(!self.flag(OVERFLOW), address),
+            "BVS" => self.branch(self.flag(OVERFLOW), address),
+            "JMP" => self.pc = address,
+            "JSR" => {
+                let last = self.pc.wrapping_sub(1);
+                self.push((last >> 8) as u8);
+                self.push(last as u8);
+                self.pc = address;
+            }
+            "RTS" => {
+                let low = self.pull() as u16;
+                let high = self.pull() as u16;
+                self.pc = (high << 8 | low).wrapping_add(1);
+            }
+            "BRK" => {
+                let next = self.pc.wrapping_add(1);
+                self.push((next >> 8) as u8);
+                self.push(next as u8);
+                self.push(self.p | BREAK | UNUSED);
+                self.set(INTERRUPT, true);
+                self.pc = self.read_word(0xFFFE);
+            }
+            "RTI" => {
+                self.p = (self.pull() & !BREAK) | UNUSED;
+                let low = self.pull() as u16;
+                let high = self.pull() as u16;
+                self.pc = high << 8 | low;
+            }
+            "PHA" => self.push(self.a),
+            "PHP" => self.push(self.p | BREAK | UNUSED),
+            "PLA" => {
+                self.a = self.pull();
+                self.set_nz(self.a);
+            }
+            "PLP" => self.p = (self.pull() & !BREAK) | UNUSED,
+            "NOP" => {}
+            _ => unreachable!("{} has no implementation", mnemonic),
+        }
+        true
+    }
+
+    fn compare(&mut self, register: u8, value: u8) {
+        self.set(CARRY, register >= value);
+        self.set_nz(register.wrapping_sub(value));
+    }
+
+    fn branch(&mut self, taken: bool, target: u16) {
+        if taken {
+            self.cycles += if target & 0xFF00 != self.pc & 0xFF00 { 2 } else { 1 };
+            self.pc = target;
+        }
+    }
+
+    fn adc(&mut self, value: u8) {
+        let carry = self.flag(CARRY) as u16;
+        let sum = self.a as u16 + value as u16 + carry;
+        // Z always follows the binary sum, N and V the sum before the high digit is adjusted
+        self.set(ZERO, sum & 0xFF == 0);
+        if !self.flag(DECIMAL) {
+            self.set(CARRY, sum > 0xFF);
+            self.set(OVERFLOW, !(self.a ^ value) & (self.a ^ sum as u8) & 0x80 != 0);
+            self.set(NEGATIVE, sum & 0x80 != 0);
+            self.a = sum as u8;
+            return;
+        }
+        let mut low = (self.a & 0x0F) as i16 + (value & 0x0F) as i16 + carry as i16;
+        if low >= 0x0A {
+            low = ((low + 0x06) & 0x0F) + 0x10;
+        }
+        let signed = (self.a & 0xF0) as i8 as i16 + (value & 0xF0) as i8 as i16 + low;
+        let mut result = (self.a & 0xF0) as i16 + (value & 0xF0) as i16 + low;
+        self.set(NEGATIVE, signed & 0x80 != 0);
+        self.set(OVERFLOW, !(-128..=127).contains(&signed));
+        if result >= 0xA0 {
+            result += 0x60;
+        }
+        self.set(CARRY, result >= 0x100);
+        self.a = result as u8;
+    }
+
+    fn sbc(&mut self, value: u8) {
+        let borrow = !self.flag(CARRY) as i16;
+        let difference = self.a as i16 - value as i16 - borrow;
+        let binary = difference as u8;
+        // all the flags follow the binary difference, even in decimal mode
+        self.set(CARRY, difference >= 0);
+        self.set(OVERFLOW, (self.a ^ value) & (self.a ^ binary) & 0x80 != 0);
+        self.set_nz(binary);
+        if !self.flag(DECIMAL) {
+            self.a = binary;
+            return;
+        }
+        let mut low = (self.a & 0x0F) as i16 - (value & 0x0F) as i16 - borrow;
+        if low < 0 {
+            low = ((low - 0x06) & 0x0F) - 0x10;
+        }
+        let mut result = (self.a & 0xF0) as i16 - (value & 0xF0) as i16 + low;
+        if result < 0 {
+            result -= 0x60;
+        }
+        self.a = result as u8;
+    }
+}
@@ -0,0 +1,99 @@
+//!
+//! File: tests/klaus_dormann.rs
+//! The Klaus Dormann test module runs the 6502 functional and decimal test programs.
+//! The binaries are not part of the repository, they are looked up in the directory
+//! given by the `KLAUS_DORMANN_DIR` environment variable, `tests/bin` by default,
+//! and every test is skipped when its binary is missing.
+//! Both programs end in a trap, a branch or a jump to itself, the test passes
+//! when the trap is the success address of the program.
+//!
+//! # See
+//! * [https://github.com/Klaus2m5/6502_65C02_functional_tests](https://github.com/Klaus2m5/6502_65C02_functional_tests)
+//!
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use emul::cpu::cpu_6502::Cpu6502;
+use emul::cpu::run::StopReason;
+use emul::memory::ram::Ram;
+use emul::util::types::Address;
+
+// the functional test is a 64K image whose code starts at $0400
+const FUNCTIONAL_TEST: &str = "6502_functional_test.bin";
+const FUNCTIONAL_START: Address = 0x0400;
+// address of the success trap in the binary of the repository, built without report
+const FUNCTIONAL_SUCCESS: Address = 0x3469;
+
+// the decimal test is assembled at $0200 and leaves 0 in ERROR when it succeeds
+const DECIMAL_TEST: &str = "6502_decimal_test.bin";
+const DECIMAL_START: Address = 0x0200;
+const DECIMAL_ERROR: Address = 0x000B;
+
+// far more than the 96 million cycles of the functional test
+const MAX_CYCLES: u64 = 200_000_000;
+
+///
+/// read a test binary, None when it is missing so the test can be skipped
+///
+fn read_binary(name: &str) -> Option<Vec<u8>> {
+    let directory = env::var("KLAUS_DORMANN_DIR").unwrap_or_else(|_| "tests/bin".to_owned());
+    let path = PathBuf::from(directory).join(name);
+    match fs::read(&path) {
+        Ok(data) => Some(data),
+        Err(_) => {
+            eprintln!("skipped, {} not found", path.display());
+            None
+        }
+    }
+}
+
+///
+/// run until the program counter does not move after an instruction, returns the trap address
+///
+fn run_to_trap(cpu: &mut Cpu6502) -> Address {
+    let mut previous = cpu.registers.pc;
+    let reason = cpu
+        .run_until(|cpu| {
+            let trapped = cpu.registers.pc == previous;
+            previous = cpu.registers.pc;
+            trapped || cpu.cycles >= MAX_CYCLES
+        })
+        .unwrap_or_else(|error| panic!("{} after {} cycles", error, cpu.cycles));
+    assert_eq!(reason, StopReason::Condition, "pc {:04X}", cpu.registers.pc);
+    assert!(cpu.cycles < MAX_CYCLES, "no trap after {} cycles, pc {:04X}", cpu.cycles, cpu.registers.pc);
+    cpu.registers.pc
+}
+
+fn get_test_cpu(binary: &[u8], offset: Address, start: Address) -> Cpu6502 {
+    let mut cpu = Cpu6502::new(Ram::new());
+    cpu.memory.load(binary, offset).unwrap();
+    cpu.reset();
+    cpu.registers.pc = start;
+    cpu
+}
+
+#[test]
+fn test_functional() {
+    let binary = match read_binary(FUNCTIONAL_TEST) {
+        Some(binary) => binary,
+        None => return,
+    };
+    let mut cpu = get_test_cpu(&binary, 0x0000, FUNCTIONAL_START);
+
+    let trap = run_to_trap(&mut cpu);
+    assert_eq!(trap, FUNCTIONAL_SUCCESS, "trapped at {:04X}, the failing test is at the trap", trap);
+}
+
+#[test]
+fn test_decimal() {
+    let binary = match read_binary(DECIMAL_TEST) {
+        Some(binary) => binary,
+        None => return,
+    };
+    let mut cpu = get_test_cpu(&binary, DECIMAL_START, DECIMAL_START);
+
+    let trap = run_to_trap(&mut cpu);
+    assert_eq!(cpu.memory.peek(DECIMAL_ERROR), 0, "decimal test failed, trapped at {:04X}", trap);
+}
@@ -0,0 +1,50 @@
+//!
+//! File: tests/processor_tests.rs
+//! The processor tests module runs the per-opcode JSON test vectors of the community.
+//! The vectors are not part of the repository, they are looked up in the directory
+//! given by the `PROCESSOR_TESTS_DIR` environment variable, `tests/bin/6502` by default,
+//! which holds the NMOS files `00.json` to `ff.json`, the test is skipped when it is missing.
+//! The same runner checks the 65C02 vectors with `PROCESSOR_TESTS_65C02_DIR`.
+//!
+//! # See
+//! * [https://github.com/SingleStepTests/ProcessorTests](https://github.com/SingleStepTests/ProcessorTests)
+//!
+
+use std::env;
+use std::path::PathBuf;
+
+use emul::cpu::single_step::run_directory;
+use emul::cpu::variant::CpuVariant;
+
+///
+/// the directory of the vectors, None when it is missing so the test can be skipped
+///
+fn vector_directory(variable: &str, default: &str) -> Option<PathBuf> {
+    let directory = PathBuf::from(env::var(variable).unwrap_or_else(|_| default.to_owned()));
+    if directory.is_dir() {
+        Some(directory)
+    } else {
+        eprintln!("skipped, {} not found", directory.display());
+        None
+    }
+}
+
+fn check_directory(variable: &str, default: &str, variant: CpuVariant) {
+    let directory = match vector_directory(variable, default) {
+        Some(directory) => directory,
+        None => return,
+    };
+    let report = run_directory(&directory, variant).unwrap();
+    eprint!("{}", report);
+    assert!(report.is_success(), "{} vectors failed", report.failed);
+}
+
+#[test]
+fn test_nmos_6502() {
+    check_directory("PROCESSOR_TESTS_DIR", "tests/bin/6502", CpuVariant::Nmos6502);
+}
+
+#[test]
+fn test_cmos_65c02() {
+    check_directory("PROCESSOR_TESTS_65C02_DIR", "tests/bin/65c02", CpuVariant::Cmos65C02);
+}